@@ -1,21 +1,43 @@
 extern crate cc;
+use std::env;
+
+
+/// Enables the pure-Rust fallback instead of the C-shim
+fn use_fallback() {
+	println!("cargo:rustc-cfg=libselect_fallback");
+}
 
 fn main() {
-	// Select the version according to the current platform
+	println!("cargo:rustc-check-cfg=cfg(libselect_fallback)");
+	println!("cargo:rerun-if-env-changed=CARGO_FEATURE_PURE_RUST");
+	
+	// Select the version according to the target platform
+	let family = env::var("CARGO_CFG_TARGET_FAMILY").unwrap_or_default();
+	let is_unix = family.split(',').any(|f| f == "unix");
 	let (file, flags) = match true {
-		_ if cfg!(unix) => ("libselect/libselect_unix.c", ["-std=c99"].as_ref()),
-		_ if cfg!(windows) => ("libselect/libselect_win.c", [""].as_ref()),
-		_ => panic!("Unsupported platform for libselect")
+		_ if is_unix && env::var_os("CARGO_FEATURE_PURE_RUST").is_some() => return use_fallback(),
+		_ if is_unix => ("libselect/libselect_unix.c", ["-std=c99"].as_ref()),
+		_ if family.split(',').any(|f| f == "windows") => ("libselect/libselect_win.c", [""].as_ref()),
+		_ => panic!("Unsupported platform: libselect and the pure-Rust fallback require a unix- or a \
+			windows-target")
 	};
+	println!("cargo:rerun-if-changed={}", file);
 	
 	// Configure builder
 	let mut builder = cc::Build::new();
+	#[allow(deprecated)] // `static_flag` is a no-op in newer `cc`-versions, but not in older ones
 	builder.static_flag(true).extra_warnings(true).warnings_into_errors(true);
 	
 	// Add platform specific flags
 	flags.iter().for_each(|flag| { builder.flag_if_supported(flag); });
 	
-	// Compile and link library
-	builder.file(file).compile("select");
-	println!("cargo:rustc-link-lib=static=select");
-}
\ No newline at end of file
+	// Compile and link library (or fall back to the pure-Rust version if possible)
+	match builder.file(file).try_compile("select") {
+		Ok(_) => println!("cargo:rustc-link-lib=static=select"),
+		Err(e) if is_unix => {
+			println!("cargo:warning=Failed to build libselect ({}); using the pure-Rust fallback", e);
+			use_fallback()
+		},
+		Err(e) => panic!("Failed to build libselect: {}", e)
+	}
+}
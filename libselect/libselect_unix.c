@@ -1,11 +1,23 @@
+// Feature test macros (required for `SO_REUSEPORT` and `recvmmsg`/`sendmmsg` in strict C99-mode)
+#define _GNU_SOURCE
+
+
 // Includes
 #include <stdint.h>
 #include <sys/select.h>
 #include <sys/socket.h>
+#include <netinet/in.h>
+#include <netinet/tcp.h>
 #include <errno.h>
 #include <string.h>
 #include <unistd.h>
 #include <fcntl.h>
+#if defined(__linux__)
+	#include <sys/sendfile.h>
+#elif defined(__APPLE__) || defined(__FreeBSD__) || defined(__DragonFly__)
+	#include <sys/types.h>
+	#include <sys/uio.h>
+#endif
 
 
 // Constants
@@ -13,11 +25,15 @@ const uint8_t EVENT_NONE  = 0;
 const uint8_t EVENT_READ  = 1 << 1;
 const uint8_t EVENT_WRITE = 1 << 2;
 const uint8_t EVENT_ERROR = 1 << 3;
+const uint8_t EVENT_HANGUP = 1 << 4;
 
 const uint64_t INVALID_FD = ~0;
 
+#define BATCH_SIZE_MAX 64
+const size_t BATCH_SIZE = BATCH_SIZE_MAX;
+
 
-uint8_t wait_for_event(uint64_t timeout_ms, uint64_t const* fds, uint8_t* events) {
+int wait_for_event_us(uint64_t timeout_us, uint64_t const* fds, uint8_t* events) {
 	// Reset errno
 	errno = 0;
 
@@ -35,7 +51,7 @@ uint8_t wait_for_event(uint64_t timeout_ms, uint64_t const* fds, uint8_t* events
 		uint8_t event = events[i];
 
 		// Insert FD into sets
-		if (event & EVENT_READ ) FD_SET(fd, &read_set );
+		if (event & (EVENT_READ | EVENT_HANGUP)) FD_SET(fd, &read_set);
 		if (event & EVENT_WRITE) FD_SET(fd, &write_set);
 		if (event & EVENT_ERROR) FD_SET(fd, &error_set);
 
@@ -44,32 +60,300 @@ uint8_t wait_for_event(uint64_t timeout_ms, uint64_t const* fds, uint8_t* events
 	}
 
 	// Create timeval-struct and call select
-	struct timeval timeout = { timeout_ms / 1000, (timeout_ms % 1000) * 1000 };
+	struct timeval timeout = { timeout_us / 1000000, timeout_us % 1000000 };
 	if (select(highest_fd + 1, &read_set, &write_set, &error_set, &timeout) == -1) return errno;
 
 	// Check sets
 	for (size_t i = 0; fds[i] != INVALID_FD; i++) {
-		// Capture FD and set the event to `EVENT_NONE`
+		// Capture FD and requested event and set the event to `EVENT_NONE`
 		int fd = (int)fds[i];
+		uint8_t event = events[i];
 		events[i] = EVENT_NONE;
 
-		// Check FDs for events
-		if (FD_ISSET(fd, &read_set )) events[i] |= EVENT_READ;
-		if (FD_ISSET(fd, &write_set)) events[i] |= EVENT_WRITE;
-		if (FD_ISSET(fd, &error_set)) events[i] |= EVENT_ERROR;
+		// Check FDs for requested events (the same FD may be registered multiple times)
+		if ((event & EVENT_READ ) && FD_ISSET(fd, &read_set )) events[i] |= EVENT_READ;
+		if ((event & EVENT_WRITE) && FD_ISSET(fd, &write_set)) events[i] |= EVENT_WRITE;
+		if ((event & EVENT_ERROR) && FD_ISSET(fd, &error_set)) events[i] |= EVENT_ERROR;
+
+		// `select` cannot express hangups, so we approximate them: a readable FD where a peek returns
+		// no data has been shut down by the peer
+		if ((event & EVENT_HANGUP) && FD_ISSET(fd, &read_set)) {
+			char byte = 0;
+			if (recv(fd, &byte, 1, MSG_PEEK | MSG_DONTWAIT) == 0) events[i] |= EVENT_HANGUP;
+		}
+	}
+	return 0;
+}
+
+int wait_for_event(uint64_t timeout_ms, uint64_t const* fds, uint8_t* events) {
+	uint64_t timeout_us = timeout_ms > UINT64_MAX / 1000 ? UINT64_MAX : timeout_ms * 1000;
+	return wait_for_event_us(timeout_us, fds, events);
+}
+
+int check_fd(uint64_t fd) {
+	// Reset errno
+	errno = 0;
+
+	// Query the FD-flags (this fails with `EBADF` if the FD is not open)
+	return (fcntl((int)fd, F_GETFD) == -1) ? errno : 0;
+}
+
+// Creates a socket address from a raw `family`/`ip`/`port`-triple and returns it's length (or 0 if
+// `family` is invalid)
+static socklen_t address_from_raw(uint8_t family, uint8_t const* ip, uint16_t port, struct sockaddr_storage* address) {
+	memset(address, 0, sizeof(*address));
+	if (family == 4) {
+		struct sockaddr_in* address_v4 = (struct sockaddr_in*)address;
+		address_v4->sin_family = AF_INET;
+		address_v4->sin_port = htons(port);
+		memcpy(&address_v4->sin_addr, ip, 4);
+		return sizeof(struct sockaddr_in);
+	} else if (family == 6) {
+		struct sockaddr_in6* address_v6 = (struct sockaddr_in6*)address;
+		address_v6->sin6_family = AF_INET6;
+		address_v6->sin6_port = htons(port);
+		memcpy(&address_v6->sin6_addr, ip, 16);
+		return sizeof(struct sockaddr_in6);
+	} else return 0;
+}
+
+// Converts a socket address into a raw `family`/`ip`/`port`-triple
+static void address_to_raw(struct sockaddr_storage const* address, uint8_t* family, uint8_t* ip, uint16_t* port) {
+	if (address->ss_family == AF_INET) {
+		struct sockaddr_in const* address_v4 = (struct sockaddr_in const*)address;
+		*family = 4;
+		*port = ntohs(address_v4->sin_port);
+		memcpy(ip, &address_v4->sin_addr, 4);
+	} else if (address->ss_family == AF_INET6) {
+		struct sockaddr_in6 const* address_v6 = (struct sockaddr_in6 const*)address;
+		*family = 6;
+		*port = ntohs(address_v6->sin6_port);
+		memcpy(ip, &address_v6->sin6_addr, 16);
+	} else *family = 0;
+}
+
+int set_linger(uint64_t fd, uint8_t enabled, uint32_t seconds) {
+	// Reset errno
+	errno = 0;
+
+	// Set the linger option
+	struct linger linger = { enabled ? 1 : 0, (int)seconds };
+	return (setsockopt((int)fd, SOL_SOCKET, SO_LINGER, &linger, sizeof(linger)) == -1) ? errno : 0;
+}
+
+int set_keepalive(uint64_t fd, uint8_t enabled) {
+	// Reset errno
+	errno = 0;
+
+	// Set the keepalive option
+	int enable = enabled ? 1 : 0;
+	return (setsockopt((int)fd, SOL_SOCKET, SO_KEEPALIVE, &enable, sizeof(enable)) == -1) ? errno : 0;
+}
+
+int set_cork(uint64_t fd, uint8_t enabled) {
+	// Reset errno
+	errno = 0;
+
+	// Set the cork option (`TCP_CORK` on Linux, `TCP_NOPUSH` on BSDs/macOS; a no-op elsewhere)
+	int enable = enabled ? 1 : 0;
+#if defined(TCP_CORK)
+	return (setsockopt((int)fd, IPPROTO_TCP, TCP_CORK, &enable, sizeof(enable)) == -1) ? errno : 0;
+#elif defined(TCP_NOPUSH)
+	return (setsockopt((int)fd, IPPROTO_TCP, TCP_NOPUSH, &enable, sizeof(enable)) == -1) ? errno : 0;
+#else
+	(void)fd;
+	(void)enable;
+	return 0;
+#endif
+}
+
+int set_tos(uint64_t fd, uint8_t tos) {
+	// Reset errno
+	errno = 0;
+
+	// Set the IPv4 type-of-service
+	int value = tos;
+	return (setsockopt((int)fd, IPPROTO_IP, IP_TOS, &value, sizeof(value)) == -1) ? errno : 0;
+}
+
+int set_traffic_class(uint64_t fd, uint8_t traffic_class) {
+	// Reset errno
+	errno = 0;
+
+	// Set the IPv6 traffic class (`ENOPROTOOPT` if the platform does not support it)
+#if defined(IPV6_TCLASS)
+	int value = traffic_class;
+	return (setsockopt((int)fd, IPPROTO_IPV6, IPV6_TCLASS, &value, sizeof(value)) == -1) ? errno : 0;
+#else
+	(void)fd;
+	(void)traffic_class;
+	return ENOPROTOOPT;
+#endif
+}
+
+int send_file(uint64_t fd, uint64_t file_fd, uint64_t* offset, uint64_t count, uint64_t* sent) {
+	// Reset errno
+	errno = 0;
+	*sent = 0;
+
+	// Send the file (`sendfile` has a different signature on every platform; `ENOSYS` elsewhere)
+#if defined(__linux__)
+	off_t position = (off_t)*offset;
+	ssize_t result = sendfile((int)fd, (int)file_fd, &position, (size_t)count);
+	if (result == -1) return errno;
+	*sent = (uint64_t)result;
+#elif defined(__APPLE__)
+	off_t length = (off_t)count;
+	int result = sendfile((int)file_fd, (int)fd, (off_t)*offset, &length, NULL, 0);
+	*sent = (uint64_t)length;
+	if (result == -1 && (length == 0 || (errno != EAGAIN && errno != EINTR))) return errno;
+#elif defined(__FreeBSD__) || defined(__DragonFly__)
+	off_t length = 0;
+	int result = sendfile((int)file_fd, (int)fd, (off_t)*offset, (size_t)count, NULL, &length, 0);
+	*sent = (uint64_t)length;
+	if (result == -1 && (length == 0 || (errno != EAGAIN && errno != EINTR))) return errno;
+#else
+	(void)fd;
+	(void)file_fd;
+	(void)count;
+	return ENOSYS;
+#endif
+
+	// Advance the offset
+	*offset += *sent;
+	return 0;
+}
+
+int bind_reusable(uint8_t family, uint8_t const* ip, uint16_t port, uint8_t reuse_port, uint64_t* fd) {
+	// Reset errno
+	errno = 0;
+
+	// Create the address
+	struct sockaddr_storage address;
+	socklen_t address_len = address_from_raw(family, ip, port, &address);
+	if (address_len == 0) return EINVAL;
+
+	// Create the socket
+	int descriptor = socket(address.ss_family, SOCK_STREAM, 0);
+	if (descriptor == -1) return errno;
+	if (fcntl(descriptor, F_SETFD, FD_CLOEXEC) == -1) goto fail;
+
+	// Set the socket options
+	int enable = 1;
+	if (setsockopt(descriptor, SOL_SOCKET, SO_REUSEADDR, &enable, sizeof(enable)) == -1) goto fail;
+	if (reuse_port) {
+#ifdef SO_REUSEPORT
+		if (setsockopt(descriptor, SOL_SOCKET, SO_REUSEPORT, &enable, sizeof(enable)) == -1) goto fail;
+#else
+		errno = ENOPROTOOPT;
+		goto fail;
+#endif
+	}
+
+	// Bind and listen
+	if (bind(descriptor, (struct sockaddr*)&address, address_len) == -1) goto fail;
+	if (listen(descriptor, 128) == -1) goto fail;
+
+	*fd = (uint64_t)descriptor;
+	return 0;
+
+	fail: {
+		int error = errno;
+		close(descriptor);
+		return error;
+	}
+}
+
+#ifdef __linux__
+int receive_batch(uint64_t fd, uint8_t* const* bufs, size_t const* lens, size_t count, size_t* received_lens,
+	uint8_t* families, uint8_t* ips, uint16_t* ports, size_t* received)
+{
+	// Reset errno
+	errno = 0;
+
+	// Prepare the message headers
+	struct mmsghdr headers[BATCH_SIZE_MAX];
+	struct iovec iovecs[BATCH_SIZE_MAX];
+	struct sockaddr_storage addresses[BATCH_SIZE_MAX];
+	count = count < BATCH_SIZE_MAX ? count : BATCH_SIZE_MAX;
+	memset(headers, 0, sizeof(headers));
+	for (size_t i = 0; i < count; i++) {
+		iovecs[i].iov_base = bufs[i];
+		iovecs[i].iov_len = lens[i];
+		headers[i].msg_hdr.msg_iov = &iovecs[i];
+		headers[i].msg_hdr.msg_iovlen = 1;
+		headers[i].msg_hdr.msg_name = &addresses[i];
+		headers[i].msg_hdr.msg_namelen = sizeof(addresses[i]);
+	}
+
+	// Receive the messages
+	int result = recvmmsg((int)fd, headers, (unsigned int)count, MSG_DONTWAIT, NULL);
+	if (result == -1) return errno;
+
+	// Collect the results
+	for (size_t i = 0; i < (size_t)result; i++) {
+		received_lens[i] = headers[i].msg_len;
+		address_to_raw(&addresses[i], &families[i], &ips[i * 16], &ports[i]);
+	}
+	*received = (size_t)result;
+	return 0;
+}
+
+int send_batch(uint64_t fd, uint8_t const* const* datas, size_t const* lens, size_t count,
+	uint8_t const* families, uint8_t const* ips, uint16_t const* ports, size_t* sent)
+{
+	// Reset errno
+	errno = 0;
+
+	// Prepare the message headers
+	struct mmsghdr headers[BATCH_SIZE_MAX];
+	struct iovec iovecs[BATCH_SIZE_MAX];
+	struct sockaddr_storage addresses[BATCH_SIZE_MAX];
+	count = count < BATCH_SIZE_MAX ? count : BATCH_SIZE_MAX;
+	memset(headers, 0, sizeof(headers));
+	for (size_t i = 0; i < count; i++) {
+		iovecs[i].iov_base = (void*)datas[i];
+		iovecs[i].iov_len = lens[i];
+		headers[i].msg_hdr.msg_iov = &iovecs[i];
+		headers[i].msg_hdr.msg_iovlen = 1;
+		headers[i].msg_hdr.msg_name = &addresses[i];
+		headers[i].msg_hdr.msg_namelen = address_from_raw(families[i], &ips[i * 16], ports[i], &addresses[i]);
+		if (headers[i].msg_hdr.msg_namelen == 0) return EINVAL;
 	}
+
+	// Send the messages
+	int result = sendmmsg((int)fd, headers, (unsigned int)count, MSG_DONTWAIT);
+	if (result == -1) return errno;
+
+	*sent = (size_t)result;
+	return 0;
+}
+
+int datagram_size(uint64_t fd, size_t* size) {
+	// Reset errno
+	errno = 0;
+
+	// Peek the next datagram (`MSG_TRUNC` returns the real size even if it exceeds the buffer)
+	uint8_t byte = 0;
+	ssize_t result = recv((int)fd, &byte, 1, MSG_PEEK | MSG_TRUNC | MSG_DONTWAIT);
+	if (result == -1) return errno;
+
+	*size = (size_t)result;
 	return 0;
 }
 
-int set_blocking_mode(uint64_t fd, uint8_t blocking) {
+int set_receive_timeout(uint64_t fd, uint64_t timeout_us, uint64_t* previous_us) {
 	// Reset errno
 	errno = 0;
 
-	// Get current flags
-	int flags = fcntl((int)fd, F_GETFL, 0);
-	if (flags == -1) return errno;
+	// Get the previous timeout
+	struct timeval previous;
+	socklen_t previous_len = sizeof(previous);
+	if (getsockopt((int)fd, SOL_SOCKET, SO_RCVTIMEO, &previous, &previous_len) == -1) return errno;
+	*previous_us = (uint64_t)previous.tv_sec * 1000000 + (uint64_t)previous.tv_usec;
 
-	// Add new flag
-	flags = blocking ? (flags & ~O_NONBLOCK) : (flags | O_NONBLOCK);
-	return (fcntl((int)fd, F_SETFL, flags) == -1) ? 0 : errno;
+	// Set the new timeout (which is also honored by `accept` on Linux)
+	struct timeval timeout = { timeout_us / 1000000, timeout_us % 1000000 };
+	return (setsockopt((int)fd, SOL_SOCKET, SO_RCVTIMEO, &timeout, sizeof(timeout)) == -1) ? errno : 0;
 }
+#endif
@@ -1,6 +1,9 @@
 // Includes
 #include <stdint.h>
+#include <limits.h>
 #include <Winsock2.h>
+#include <Ws2tcpip.h>
+#include <string.h>
 #include <fcntl.h>
 
 
@@ -9,11 +12,12 @@ const uint8_t EVENT_NONE  = 0;
 const uint8_t EVENT_READ  = 1 << 1;
 const uint8_t EVENT_WRITE = 1 << 2;
 const uint8_t EVENT_ERROR = 1 << 3;
+const uint8_t EVENT_HANGUP = 1 << 4;
 
 const uint64_t INVALID_FD = ~0ULL;
 
 
-int wait_for_event(uint64_t timeout_ms, uint64_t const* fds, uint8_t* events) {
+int wait_for_event_us(uint64_t timeout_us, uint64_t const* fds, uint8_t* events) {
 	// Reset last error
 	WSASetLastError(0);
 
@@ -31,7 +35,7 @@ int wait_for_event(uint64_t timeout_ms, uint64_t const* fds, uint8_t* events) {
 		uint8_t event = events[i];
 
 		// Insert FD into sets
-		if (event & EVENT_READ ) FD_SET(fd, &read_set );
+		if (event & (EVENT_READ | EVENT_HANGUP)) FD_SET(fd, &read_set);
 		if (event & EVENT_WRITE) FD_SET(fd, &write_set);
 		if (event & EVENT_ERROR) FD_SET(fd, &error_set);
 
@@ -39,28 +43,48 @@ int wait_for_event(uint64_t timeout_ms, uint64_t const* fds, uint8_t* events) {
 		highest_fd = highest_fd < fd ? fd : highest_fd;
 	}
 
-	// Create timeval-struct
+	// Create timeval-struct (`long` is 32 bit on windows, so we clamp huge timeouts to `LONG_MAX`
+	// seconds instead of wrapping them)
 	struct timeval timeout;
-	timeout.tv_sec = (long)timeout_ms / 1000;
-	timeout.tv_usec = ((long)timeout_ms % 1000) * 1000;
+	uint64_t timeout_s = timeout_us / 1000000;
+	if (timeout_s > (uint64_t)LONG_MAX) {
+		timeout.tv_sec = LONG_MAX;
+		timeout.tv_usec = 0;
+	} else {
+		timeout.tv_sec = (long)timeout_s;
+		timeout.tv_usec = (long)(timeout_us % 1000000);
+	}
 
 	// Call select
 	if (select((int)highest_fd + 1, &read_set, &write_set, &error_set, &timeout) == -1) return WSAGetLastError();
 
 	// Check sets
 	for (size_t i = 0; fds[i] != INVALID_FD; i++) {
-		// Capture FD and set the event to `EVENT_NONE`
+		// Capture FD and requested event and set the event to `EVENT_NONE`
 		SOCKET fd = (SOCKET)fds[i];
+		uint8_t event = events[i];
 		events[i] = EVENT_NONE;
 
-		// Check FDs for events
-		if (FD_ISSET(fd, &read_set )) events[i] |= EVENT_READ;
-		if (FD_ISSET(fd, &write_set)) events[i] |= EVENT_WRITE;
-		if (FD_ISSET(fd, &error_set)) events[i] |= EVENT_ERROR;
+		// Check FDs for requested events (the same FD may be registered multiple times)
+		if ((event & EVENT_READ ) && FD_ISSET(fd, &read_set )) events[i] |= EVENT_READ;
+		if ((event & EVENT_WRITE) && FD_ISSET(fd, &write_set)) events[i] |= EVENT_WRITE;
+		if ((event & EVENT_ERROR) && FD_ISSET(fd, &error_set)) events[i] |= EVENT_ERROR;
+
+		// `select` cannot express hangups, so we approximate them: a readable FD where a peek returns
+		// no data has been shut down by the peer
+		if ((event & EVENT_HANGUP) && FD_ISSET(fd, &read_set)) {
+			char byte = 0;
+			if (recv(fd, &byte, 1, MSG_PEEK) == 0) events[i] |= EVENT_HANGUP;
+		}
 	}
 	return 0;
 }
 
+int wait_for_event(uint64_t timeout_ms, uint64_t const* fds, uint8_t* events) {
+	uint64_t timeout_us = timeout_ms > UINT64_MAX / 1000 ? UINT64_MAX : timeout_ms * 1000;
+	return wait_for_event_us(timeout_us, fds, events);
+}
+
 int set_blocking_mode(uint64_t fd, uint8_t blocking) {
 	// Reset last error
 	WSASetLastError(0);
@@ -69,3 +93,98 @@ int set_blocking_mode(uint64_t fd, uint8_t blocking) {
 	unsigned long mode = blocking ? 0 : 1;
 	return (ioctlsocket((SOCKET)fd, FIONBIO, &mode) == 0) ? 0 : WSAGetLastError();
 }
+
+int check_fd(uint64_t fd) {
+	// Reset last error
+	WSASetLastError(0);
+
+	// Query the socket type (this fails with `WSAENOTSOCK` if the handle is not an open socket)
+	int type = 0, type_len = sizeof(type);
+	return (getsockopt((SOCKET)fd, SOL_SOCKET, SO_TYPE, (char*)&type, &type_len) == 0) ? 0 : WSAGetLastError();
+}
+
+int set_linger(uint64_t fd, uint8_t enabled, uint32_t seconds) {
+	// Reset last error
+	WSASetLastError(0);
+
+	// Set the linger option
+	struct linger linger = { enabled ? 1 : 0, (u_short)(seconds > 0xFFFF ? 0xFFFF : seconds) };
+	return (setsockopt((SOCKET)fd, SOL_SOCKET, SO_LINGER, (char const*)&linger, sizeof(linger)) == 0) ? 0 : WSAGetLastError();
+}
+
+int set_keepalive(uint64_t fd, uint8_t enabled) {
+	// Reset last error
+	WSASetLastError(0);
+
+	// Set the keepalive option
+	BOOL enable = enabled ? TRUE : FALSE;
+	return (setsockopt((SOCKET)fd, SOL_SOCKET, SO_KEEPALIVE, (char const*)&enable, sizeof(enable)) == 0) ? 0 : WSAGetLastError();
+}
+
+int set_tos(uint64_t fd, uint8_t tos) {
+	// Reset last error
+	WSASetLastError(0);
+
+	// Set the IPv4 type-of-service
+	DWORD value = tos;
+	return (setsockopt((SOCKET)fd, IPPROTO_IP, IP_TOS, (char const*)&value, sizeof(value)) == 0) ? 0 : WSAGetLastError();
+}
+
+// Winsock is initialized once and stays initialized for the lifetime of the process (like `std`
+// does it, `WSACleanup` is never called since the created sockets may outlive any caller)
+static INIT_ONCE winsock_once = INIT_ONCE_STATIC_INIT;
+static int winsock_startup = 0;
+
+static BOOL CALLBACK startup_winsock(PINIT_ONCE once, PVOID parameter, PVOID* context) {
+	WSADATA wsa_data;
+	winsock_startup = WSAStartup(MAKEWORD(2, 2), &wsa_data);
+	return TRUE;
+}
+
+int bind_reusable(uint8_t family, uint8_t const* ip, uint16_t port, uint8_t reuse_port, uint64_t* fd) {
+	// Initialize winsock (once) and reset last error
+	InitOnceExecuteOnce(&winsock_once, startup_winsock, NULL, NULL);
+	if (winsock_startup != 0) return winsock_startup;
+	WSASetLastError(0);
+
+	// `SO_REUSEPORT` is not available on windows
+	if (reuse_port) return WSAENOPROTOOPT;
+
+	// Create the address
+	struct sockaddr_storage address;
+	int address_len = 0;
+	memset(&address, 0, sizeof(address));
+	if (family == 4) {
+		struct sockaddr_in* address_v4 = (struct sockaddr_in*)&address;
+		address_v4->sin_family = AF_INET;
+		address_v4->sin_port = htons(port);
+		memcpy(&address_v4->sin_addr, ip, 4);
+		address_len = sizeof(struct sockaddr_in);
+	} else if (family == 6) {
+		struct sockaddr_in6* address_v6 = (struct sockaddr_in6*)&address;
+		address_v6->sin6_family = AF_INET6;
+		address_v6->sin6_port = htons(port);
+		memcpy(&address_v6->sin6_addr, ip, 16);
+		address_len = sizeof(struct sockaddr_in6);
+	} else return WSAEINVAL;
+
+	// Create the socket
+	SOCKET descriptor = WSASocketW(address.ss_family, SOCK_STREAM, 0, NULL, 0, WSA_FLAG_NO_HANDLE_INHERIT);
+	if (descriptor == INVALID_SOCKET) return WSAGetLastError();
+
+	// Set the socket options, bind and listen (on windows `SO_REUSEADDR` would allow other sockets to
+	// hijack the port; windows allows rebinding despite `TIME_WAIT` anyway, so we bind exclusively)
+	BOOL enable = TRUE;
+	if (
+		setsockopt(descriptor, SOL_SOCKET, SO_EXCLUSIVEADDRUSE, (char const*)&enable, sizeof(enable)) != 0
+		|| bind(descriptor, (struct sockaddr*)&address, address_len) != 0
+		|| listen(descriptor, 128) != 0
+	) {
+		int error = WSAGetLastError();
+		closesocket(descriptor);
+		return error;
+	}
+
+	*fd = (uint64_t)descriptor;
+	return 0;
+}
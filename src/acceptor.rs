@@ -1,8 +1,12 @@
-use crate::{ TimeoutIoError, InstantExt, WaitForEvent, EventMask };
+use crate::{
+	TimeoutIoError, InstantExt, with_deadline, TIMED_OUT, WaitForEvent, EventMask, RawFd,
+	SocketOptions, event::libselect
+};
 use std::{
-	io,
-	time::{ Duration, Instant },
-	net::{ TcpListener, TcpStream }
+	io, convert::TryInto,
+	sync::Mutex,
+	time::Duration,
+	net::{ TcpListener, TcpStream, SocketAddr }
 };
 
 
@@ -22,35 +26,421 @@ impl StdAcceptor<::std::os::unix::net::UnixStream> for ::std::os::unix::net::Uni
 		Ok(::std::os::unix::net::UnixListener::accept(self)?.0)
 	}
 }
+#[cfg(all(feature = "vsock", target_os = "linux"))]
+impl StdAcceptor<vsock::VsockStream> for vsock::VsockListener {
+	fn accept(&self) -> Result<vsock::VsockStream, io::Error> {
+		Ok(vsock::VsockListener::accept(self)?.0)
+	}
+}
+
+
+/// The options that `Acceptor::accept_with_options` applies to an accepted connection
+///
+/// _Info: The defaults (see `Default`) are a non-blocking connection (as required by this crate)
+/// and the OS-defaults for all other options (i.e. no `TCP_NODELAY`, no `SO_KEEPALIVE` and no
+/// `SO_LINGER`)._
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct AcceptOptions {
+	/// Whether the connection is made non-blocking (`true`) or blocking (`false`)
+	pub nonblocking: bool,
+	/// Whether `TCP_NODELAY` is enabled (see `SocketOptions::set_nodelay_t`)
+	pub nodelay: bool,
+	/// Whether `SO_KEEPALIVE` is enabled (see `SocketOptions::set_keepalive_t`)
+	pub keepalive: bool,
+	/// The `SO_LINGER`-time (see `SocketOptions::set_linger_t`)
+	pub linger: Option<Duration>
+}
+impl AcceptOptions {
+	/// Applies the options to `connection`
+	pub fn apply<T>(&self, connection: &T) -> Result<(), TimeoutIoError>
+		where T: SocketOptions + WaitForEvent
+	{
+		connection.set_blocking_mode(!self.nonblocking)?;
+		connection.set_nodelay_t(self.nodelay)?;
+		connection.set_keepalive_t(self.keepalive)?;
+		connection.set_linger_t(self.linger)
+	}
+}
+impl Default for AcceptOptions {
+	fn default() -> Self {
+		Self{ nonblocking: true, nodelay: false, keepalive: false, linger: None }
+	}
+}
 
 
 /// A trait for accepting elements, e.g. a TCP-listener
+///
+/// _Info: The trait works for every `TcpListener` – including listeners that were created and
+/// configured elsewhere (e.g. via `socket2` with custom options like `IP_TRANSPARENT`, buffer sizes
+/// or bind-to-device and then converted via `socket2::Socket::into()`); the options are not
+/// touched except for the blocking mode._
 pub trait Acceptor<T> {
 	/// Tries to accept a type-`T`-connection until `timeout` expires
 	///
+	/// _Info: `timeout` bounds the entire call – including the setup (i.e. making `self`
+	/// non-blocking); so the call returns within `timeout` of entry._
+	///
+	/// _Note: A zero `timeout` performs exactly one non-blocking `accept`-attempt and returns
+	/// `TimedOut` immediately if there is no pending connection._
+	///
+	/// _Note: A pending connection that was aborted by the peer before it could be accepted
+	/// (`ConnectionAborted`) is skipped and the call keeps waiting for the next connection (this
+	/// is like `try_accept_bounded` with an unlimited retry budget)._
+	///
 	/// __Warning: This function makes `self` non-blocking. It's up to you to restore the previous
 	/// state if necessary.__
 	fn try_accept(&self, timeout: Duration) -> Result<T, TimeoutIoError>;
+	
+	/// Tries to accept a type-`T`-connection until `timeout` expires or `accept` failed
+	/// `max_retries + 1` times with a transient error (like an interrupt or an aborted pending
+	/// connection) – whichever happens first. If the retry budget is exhausted, the last error is
+	/// returned.
+	///
+	/// This protects against CPU-spikes caused by "accept-storms" where every `accept` fails with
+	/// a transient error.
+	///
+	/// _Info: Only failed `accept`s count against `max_retries`; spurious wakeups (i.e. the
+	/// listener was readable, but there was no pending connection anymore, e.g. because another
+	/// thread accepted it) don't count._
+	///
+	/// _Note: A zero `timeout` performs exactly one non-blocking `accept`-attempt and returns
+	/// `TimedOut` immediately if there is no pending connection._
+	///
+	/// __Warning: This function makes `self` non-blocking. It's up to you to restore the previous
+	/// state if necessary.__
+	fn try_accept_bounded(&self, timeout: Duration, max_retries: usize) -> Result<T, TimeoutIoError>;
+	
+	/// Performs exactly one non-blocking `accept`-attempt _without_ waiting and returns `None` if
+	/// there is no pending connection (e.g. to accept connections from your own `SelectSet`-loop)
+	///
+	/// _Note: Interrupts and aborted pending connections are reported as `None`, too._
+	///
+	/// __Warning: Unlike `try_accept`, this function does not change the blocking mode; so `self`
+	/// must be non-blocking or the call may block until a connection is pending.__
+	fn accept_nb(&self) -> Result<Option<T>, TimeoutIoError>;
+	
+	/// Tries to accept a type-`T`-connection until `timeout` expires _without_ making `self`
+	/// non-blocking (e.g. if `self` is shared with code that relies on a blocking listener)
+	///
+	/// _Info: On Linux, `accept` honors `SO_RCVTIMEO`; so the timeout is set as socket-timeout for
+	/// the duration of the call (and the previous socket-timeout is restored afterwards). On other
+	/// platforms (e.g. BSDs, macOS or windows), `SO_RCVTIMEO` is not (reliably) honored by
+	/// `accept`; so we fall back to `try_accept` and make `self` blocking again afterwards._
+	///
+	/// _Note: A zero `timeout` is rounded up to the smallest possible socket-timeout because a zero
+	/// `SO_RCVTIMEO` disables the timeout._
+	///
+	/// __Warning: `self` must be blocking; on Linux, a non-blocking `self` returns `TimedOut`
+	/// immediately if there is no pending connection.__
+	fn accept_blocking_timeout(&self, timeout: Duration) -> Result<T, TimeoutIoError>
+		where Self: RawFd;
+	
+	/// Like `try_accept`, but applies `options` to the accepted connection before returning it
+	/// (e.g. to centralize the per-connection setup)
+	///
+	/// __Warning: This function makes `self` non-blocking. It's up to you to restore the previous
+	/// state if necessary.__
+	fn accept_with_options(&self, timeout: Duration, options: &AcceptOptions)
+		-> Result<T, TimeoutIoError> where T: SocketOptions + WaitForEvent
+	{
+		let connection = self.try_accept(timeout)?;
+		options.apply(&connection)?;
+		Ok(connection)
+	}
+	/// Like `try_accept`, but returns a connection that is ready to be registered in a
+	/// `SelectSet` (i.e. the per-connection setup of a reactor-based server)
+	///
+	/// _Info: The connection is made non-blocking and `TCP_NODELAY` is enabled (so that small
+	/// responses are not delayed by Nagle's algorithm); all other options keep their OS-defaults.
+	/// This is a shorthand for `accept_with_options` with `nodelay: true`._
+	///
+	/// __Warning: This function makes `self` non-blocking. It's up to you to restore the previous
+	/// state if necessary.__
+	fn accept_for_reactor(&self, timeout: Duration) -> Result<T, TimeoutIoError>
+		where T: SocketOptions + WaitForEvent
+	{
+		let options = AcceptOptions{ nodelay: true, ..AcceptOptions::default() };
+		self.accept_with_options(timeout, &options)
+	}
 }
 impl<U, T: StdAcceptor<U> + WaitForEvent> Acceptor<U> for T {
 	fn try_accept(&self, timeout: Duration) -> Result<U, TimeoutIoError> {
-		// Make the socket non-blocking
-		self.set_blocking_mode(false)?;
-		
-		// Compute deadline and try to accept once until the timeout occurred
-		let deadline = Instant::now() + timeout;
-		loop {
-			// Wait for read-event
-			self.wait_for_event(EventMask::new_r(), deadline.remaining())?;
+		self.try_accept_bounded(timeout, usize::MAX)
+	}
+	
+	fn accept_nb(&self) -> Result<Option<U>, TimeoutIoError> {
+		accept_nb_with(|| StdAcceptor::accept(self))
+	}
+	
+	#[cfg(target_os = "linux")]
+	fn accept_blocking_timeout(&self, timeout: Duration) -> Result<U, TimeoutIoError>
+		where Self: RawFd
+	{
+		// Compute deadline and try to accept until the timeout occurred
+		with_deadline(timeout, |deadline| {
+			loop {
+				// Set the remaining time as socket-timeout, accept and restore the previous
+				// socket-timeout
+				let remaining = deadline.remaining().max(Duration::from_micros(1));
+				let remaining_us = remaining.as_micros().try_into().unwrap_or(u64::MAX);
+				let previous = set_receive_timeout(self, remaining_us)?;
+				let result = StdAcceptor::accept(self);
+				set_receive_timeout(self, previous)?;
+				
+				// Check the result (a pending connection that was aborted before we could accept it
+				// is transient)
+				let error = match result {
+					Ok(connection) => return Ok(connection),
+					Err(error) if error.kind() == io::ErrorKind::ConnectionAborted => continue,
+					Err(error) => TimeoutIoError::from(error)
+				};
+				if !error.should_retry() || error.is_timeout() { return Err(error) }
+				if deadline.remaining().is_zero() { return Err(TIMED_OUT) }
+			}
+		})
+	}
+	#[cfg(not(target_os = "linux"))]
+	fn accept_blocking_timeout(&self, timeout: Duration) -> Result<U, TimeoutIoError>
+		where Self: RawFd
+	{
+		// Accept via select and make the socket blocking again
+		let result = self.try_accept(timeout);
+		self.set_blocking_mode(true)?;
+		result
+	}
+	
+	fn try_accept_bounded(&self, timeout: Duration, max_retries: usize) -> Result<U, TimeoutIoError> {
+		// Compute deadline (before the setup so that it counts against `timeout`) and make the
+		// socket non-blocking
+		with_deadline(timeout, |deadline| {
+			let mut retries = 0;
+			self.set_blocking_mode(false)?;
 			
-			// Accept connection
-			match StdAcceptor::accept(self) {
-				Ok(connection) => return Ok(connection),
-				Err(error) => {
-					let error = TimeoutIoError::from(error);
-					if !error.should_retry() { return Err(error) }
+			// Try to accept once until the timeout occurred
+			loop {
+				// Wait for read-event (unless we only perform a non-blocking attempt)
+				if !timeout.is_zero() {
+					self.wait_for_event(EventMask::new_r(), deadline.remaining())?;
+				}
+				
+				// Accept connection
+				match StdAcceptor::accept(self) {
+					Ok(connection) => return Ok(connection),
+					Err(error) => {
+						// A pending connection that was aborted before we could accept it is
+						// transient
+						let aborted = error.kind() == io::ErrorKind::ConnectionAborted;
+						let error = TimeoutIoError::from(error);
+						
+						// Check if the error is fatal or a spurious wakeup or the retry budget is
+						// exhausted
+						if !(aborted || error.should_retry()) { return Err(error) }
+						if error.is_timeout() {
+							match timeout.is_zero() {
+								true => return Err(error),
+								false => continue
+							}
+						}
+						if retries >= max_retries { return Err(error) }
+						retries += 1;
+					}
 				}
 			}
+		})
+	}
+}
+
+/// Sets the `SO_RCVTIMEO`-socket-timeout of `socket` to `timeout_us` and returns the previous
+/// socket-timeout
+#[cfg(target_os = "linux")]
+fn set_receive_timeout(socket: &impl RawFd, timeout_us: u64) -> Result<u64, TimeoutIoError> {
+	let mut previous_us = 0;
+	match unsafe{ libselect::set_receive_timeout(socket.raw_fd(), timeout_us, &mut previous_us) } {
+		0 => Ok(previous_us),
+		e => Err(io::Error::from_raw_os_error(e).into())
+	}
+}
+/// Performs one `accept`-attempt and maps transient errors (would-block, interrupts and aborted
+/// pending connections) to `None`
+fn accept_nb_with<T>(accept: impl FnOnce() -> Result<T, io::Error>)
+	-> Result<Option<T>, TimeoutIoError>
+{
+	match accept() {
+		Ok(connection) => Ok(Some(connection)),
+		Err(ref error) if error.kind() == io::ErrorKind::ConnectionAborted => Ok(None),
+		Err(error) => match TimeoutIoError::from(error) {
+			error if error.should_retry() => Ok(None),
+			error => Err(error)
 		}
 	}
-}
\ No newline at end of file
+}
+/// Like `Acceptor::accept_nb`, but also returns the peer's address
+///
+/// __Warning: This function does not change the blocking mode; so `listener` must be non-blocking
+/// or the call may block until a connection is pending.__
+pub fn accept_from_nb(listener: &TcpListener)
+	-> Result<Option<(TcpStream, SocketAddr)>, TimeoutIoError>
+{
+	accept_nb_with(|| listener.accept())
+}
+
+
+/// A listener that can be shared between threads (e.g. via `Arc`) so that multiple workers can
+/// accept connections on it concurrently
+///
+/// _Info: The listener is made non-blocking once on construction and is never switched back; so
+/// concurrent calls cannot race on the blocking mode. The `accept`-calls are serialized by an
+/// internal mutex, but the waits for a pending connection are not (so that a waiting thread never
+/// blocks the others beyond its timeout). If multiple threads are woken for the same connection,
+/// only one of them gets it and the others continue to wait._
+#[derive(Debug)]
+pub struct SharedAcceptor<L> {
+	listener: L,
+	lock: Mutex<()>
+}
+impl<L: WaitForEvent> SharedAcceptor<L> {
+	/// Creates a new shared acceptor and makes `listener` non-blocking
+	pub fn new(listener: L) -> Result<Self, TimeoutIoError> {
+		listener.set_blocking_mode(false)?;
+		Ok(Self{ listener, lock: Mutex::new(()) })
+	}
+	
+	/// Tries to accept a type-`T`-connection until `timeout` expires
+	///
+	/// _Note: A zero `timeout` performs exactly one non-blocking `accept`-attempt and returns
+	/// `TimedOut` immediately if there is no pending connection._
+	pub fn try_accept<T>(&self, timeout: Duration) -> Result<T, TimeoutIoError>
+		where L: StdAcceptor<T>
+	{
+		// Compute deadline and try to accept until the timeout occurred
+		with_deadline(timeout, |deadline| {
+			loop {
+				// Accept a pending connection (serialized) and wait only if there is none
+				let accepted = {
+					let _lock = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+					accept_nb_with(|| StdAcceptor::accept(&self.listener))?
+				};
+				if let Some(connection) = accepted { return Ok(connection) }
+				
+				match deadline.remaining() {
+					remaining if remaining.is_zero() => return Err(TIMED_OUT),
+					remaining => self.listener.wait_for_event(EventMask::new_r(), remaining)?
+				};
+			}
+		})
+	}
+	
+	/// A reference to the underlying listener
+	pub fn get_ref(&self) -> &L {
+		&self.listener
+	}
+	/// Returns the underlying listener (which remains non-blocking)
+	pub fn into_inner(self) -> L {
+		self.listener
+	}
+}
+
+
+/// Binds a `TcpListener` to `address` and passes every accepted connection to `handler` until
+/// `handler` returns `false`; if `accept` fails with a non-retryable error, the listener is closed
+/// and rebound (up to `max_rebinds` times) so that a transient failure does not kill the server
+///
+/// `handler` is called with the current listener and the accepted connection, or with `None` if no
+/// connection was accepted within `accept_timeout` (e.g. so that you can check a shutdown-flag
+/// while the server is idle). Before each rebind, `on_rebind` is called with the `accept`-error
+/// and the number of the rebind (e.g. so that you can log it).
+///
+/// _Info: The listener is bound via `bind_reuseaddr`, so that rebinding succeeds even if there are
+/// connections in `TIME_WAIT`. If `address` has port `0`, the rebound listener keeps the port that
+/// was assigned initially._
+///
+/// _Note: Errors that cannot be fixed by rebinding (`AddrInUse`, `AddrNotAvailable` and
+/// `PermissionDenied`) are returned immediately; this includes errors of a failed rebind. If
+/// `accept` fails after `max_rebinds` rebinds, the error is returned, too._
+pub fn serve_resilient(address: &SocketAddr, accept_timeout: Duration,
+	mut handler: impl FnMut(&TcpListener, Option<TcpStream>) -> bool,
+	mut on_rebind: impl FnMut(&TimeoutIoError, usize), max_rebinds: usize)
+	-> Result<(), TimeoutIoError>
+{
+	// Bind the listener and pin the address (so that a rebound listener keeps an assigned port)
+	let mut listener = bind_reuseaddr(address)?;
+	let address = listener.local_addr()?;
+	let mut rebinds = 0;
+	loop {
+		// Accept a connection and pass it to the handler
+		let error = match listener.try_accept(accept_timeout) {
+			Ok(connection) => match handler(&listener, Some(connection)) {
+				true => continue,
+				false => return Ok(())
+			},
+			Err(TimeoutIoError::TimedOut{ .. }) => match handler(&listener, None) {
+				true => continue,
+				false => return Ok(())
+			},
+			Err(error) => error
+		};
+		
+		// Check if the error is fatal or the rebind budget is exhausted
+		match error {
+			TimeoutIoError::AddrInUse{ .. } | TimeoutIoError::AddrNotAvailable{ .. }
+				| TimeoutIoError::PermissionDenied{ .. } => return Err(error),
+			_ if rebinds >= max_rebinds => return Err(error),
+			_ => rebinds += 1
+		}
+		
+		// Report the error and close and rebind the listener
+		on_rebind(&error, rebinds);
+		drop(listener);
+		listener = bind_reuseaddr(&address)?;
+	}
+}
+
+
+/// Binds a new listening socket to `address` and sets `SO_REUSEADDR` (and `SO_REUSEPORT` if
+/// `reuse_port` is set) _before_ binding; on windows `SO_EXCLUSIVEADDRUSE` is set instead
+fn bind_reusable(address: &SocketAddr, reuse_port: bool) -> Result<TcpListener, TimeoutIoError> {
+	// Serialize the address
+	let (family, ip, port) = libselect::address_to_raw(address);
+	
+	// Create the socket
+	let mut fd = 0u64;
+	let result = unsafe{ libselect::bind_reusable(
+		family, ip.as_ptr(), port,
+		if reuse_port { 1 } else { 0 }, &mut fd
+	) };
+	if result != 0 { Err(io::Error::from_raw_os_error(result))? }
+	
+	// Wrap the socket
+	#[cfg(unix)]
+	let listener = unsafe{
+		<TcpListener as std::os::unix::io::FromRawFd>::from_raw_fd(fd as _)
+	};
+	#[cfg(windows)]
+	let listener = unsafe{
+		<TcpListener as std::os::windows::io::FromRawSocket>::from_raw_socket(fd as _)
+	};
+	Ok(listener)
+}
+/// Binds a new `TcpListener` to `address` with `SO_REUSEADDR` set, so that the address can be
+/// rebound immediately (e.g. after a restart) even if there are still connections in `TIME_WAIT`
+///
+/// _Info: `std::net::TcpListener::bind` does not allow you to set socket options before binding._
+///
+/// _Note: On Windows, `SO_REUSEADDR` would allow other sockets to bind (and hijack) the same port,
+/// so `SO_EXCLUSIVEADDRUSE` is set instead. Windows already allows rebinding while connections are
+/// in `TIME_WAIT`, but the port can only be rebound once the previous listener has been closed._
+pub fn bind_reuseaddr(address: &SocketAddr) -> Result<TcpListener, TimeoutIoError> {
+	bind_reusable(address, false)
+}
+/// Binds a new `TcpListener` to `address` with `SO_REUSEADDR` and `SO_REUSEPORT` set, so that
+/// multiple workers can accept on the same port
+///
+/// _Info: `std::net::TcpListener::bind` does not allow you to set socket options before binding._
+///
+/// __Warning: All listeners that share a port must set `SO_REUSEPORT` (on Linux they must also be
+/// owned by the same user). If the platform does not support `SO_REUSEPORT`, an error is
+/// returned.__
+#[cfg(unix)]
+pub fn bind_reuseport(address: &SocketAddr) -> Result<TcpListener, TimeoutIoError> {
+	bind_reusable(address, true)
+}
@@ -0,0 +1,39 @@
+use crate::{ TimeoutIoError, InstantExt, with_deadline, TIMED_OUT };
+use std::{
+	cmp::min, thread,
+	time::Duration
+};
+
+
+/// The initial delay between two retries
+const BACKOFF_INITIAL: Duration = Duration::from_millis(10);
+/// The maximum delay between two retries
+const BACKOFF_MAX: Duration = Duration::from_secs(1);
+
+
+/// Calls `f` until it either succeeds or returns a non-retryable error or `timeout` is exceeded
+///
+/// _Info: Between two retries we sleep for 10ms initially; the delay doubles with each retry up to
+/// 1s but never exceeds the remaining time. If `timeout` is exceeded, `TimedOut` is returned._
+pub(crate) fn retry_with_backoff<T>(timeout: Duration, mut f: impl FnMut() -> Result<T, TimeoutIoError>)
+	-> Result<T, TimeoutIoError>
+{
+	// Compute deadline
+	with_deadline(timeout, |deadline| {
+		let mut delay = BACKOFF_INITIAL;
+		loop {
+			// Call `f`
+			match f() {
+				Err(error) if error.should_retry() => (),
+				result => return result
+			}
+			
+			// Sleep and increase the delay
+			match deadline.remaining_checked() {
+				Some(remaining) if !remaining.is_zero() => thread::sleep(min(delay, remaining)),
+				_ => return Err(TIMED_OUT)
+			}
+			delay = min(delay * 2, BACKOFF_MAX);
+		}
+	})
+}
@@ -0,0 +1,147 @@
+use crate::{ TimeoutIoError, InstantExt, with_deadline, Reader, reader::find_pattern };
+use std::{
+	cmp::min,
+	time::Duration
+};
+
+
+/// The default capacity of the internal buffer
+const DEFAULT_CAPACITY: usize = 8192;
+
+
+/// A buffered reader (like `std::io::BufReader`) with timeouts
+///
+/// Data is read in large chunks into an internal buffer so that peeking (`try_fill_buf`) and
+/// delimiter scanning (`try_read_until`) do not need a syscall per byte.
+///
+/// _Note: Since data may be buffered, readiness-events on the underlying stream do not reflect
+/// whether data is available; so always check `buffer` before waiting on the underlying stream._
+#[derive(Debug)]
+pub struct TimeoutBufReader<S> {
+	inner: S,
+	buf: Vec<u8>,
+	pos: usize,
+	filled: usize
+}
+impl<S: Reader> TimeoutBufReader<S> {
+	/// Creates a new buffered reader with a default capacity (currently 8 KiB)
+	pub fn new(inner: S) -> Self {
+		Self::with_capacity(DEFAULT_CAPACITY, inner)
+	}
+	/// Creates a new buffered reader with a buffer of `capacity` bytes
+	///
+	/// _Note: `capacity` must not be zero._
+	pub fn with_capacity(capacity: usize, inner: S) -> Self {
+		assert!(capacity > 0, "The capacity must not be zero");
+		Self{ inner, buf: vec![0; capacity], pos: 0, filled: 0 }
+	}
+	
+	/// The capacity of the internal buffer
+	pub fn capacity(&self) -> usize {
+		self.buf.len()
+	}
+	/// The currently buffered (but not yet consumed) data
+	pub fn buffer(&self) -> &[u8] {
+		&self.buf[self.pos..self.filled]
+	}
+	
+	/// Returns the buffered data or executes _one_ `read`-operation to refill the buffer if it is
+	/// empty
+	///
+	/// The returned data is not consumed; call `consume` to mark bytes as read.
+	///
+	/// _Note: This function catches all internal timeouts/interrupts and returns only if there is
+	/// either buffered data or the `timeout` was hit or a non-recoverable error occurred. If the
+	/// buffer is empty and the peer closed the connection gracefully, `GracefulEof` is returned._
+	///
+	/// __Warning: the underlying stream must be non-blocking or the function won't work as
+	/// expected__
+	pub fn try_fill_buf(&mut self, timeout: Duration) -> Result<&[u8], TimeoutIoError> {
+		// Refill the buffer if it is empty
+		if self.pos >= self.filled {
+			let mut filled = 0;
+			let result = self.inner.try_read(&mut self.buf, &mut filled, timeout);
+			self.pos = 0;
+			self.filled = filled;
+			result?;
+		}
+		Ok(self.buffer())
+	}
+	/// Marks `len` bytes of the buffered data as consumed
+	///
+	/// _Note: `len` is capped at the amount of buffered data._
+	pub fn consume(&mut self, len: usize) {
+		self.pos = min(self.pos + len, self.filled);
+	}
+	
+	/// Appends data to `out` until either `pat` is matched or `out` has reached `limit` bytes.
+	/// Returns `true` if `pat` was matched and `false` otherwise.
+	///
+	/// Unlike `Reader::try_read_until`, this function scans the internal buffer and reads
+	/// chunk-wise; bytes behind the match remain buffered for the next call.
+	///
+	/// _Note: All bytes appended to `out` are consumed; so you can continue seamlessly on
+	/// `TimedOut`-errors etc. (the matching restarts at the last `pat.len() - 1` bytes of `out`)._
+	///
+	/// _Note: This function catches all internal timeouts/interrupts and returns only if either
+	/// `pat` has been matched or `limit` has been reached or the `timeout` was hit or a
+	/// non-recoverable error occurred._
+	///
+	/// __Warning: the underlying stream must be non-blocking or the function won't work as
+	/// expected__
+	pub fn try_read_until(&mut self, out: &mut Vec<u8>, pat: &[u8], limit: usize,
+		timeout: Duration) -> Result<bool, TimeoutIoError>
+	{
+		// Compute deadline and the start of the overlap window
+		with_deadline(timeout, |deadline| {
+			let overlap = pat.len().saturating_sub(1);
+			let mut scan_from = out.len().saturating_sub(overlap);
+			
+			loop {
+				// Check for pattern
+				if find_pattern(&out[scan_from..], pat).is_some() { return Ok(true) }
+				if out.len() >= limit { return Ok(false) }
+				
+				// Get the buffered data
+				let available = match self.try_fill_buf(deadline.remaining()) {
+					Err(TimeoutIoError::GracefulEof) => return Err(TimeoutIoError::UnexpectedEof),
+					result => result?
+				};
+				
+				// Append the data and move the scan window
+				let to_append = min(available.len(), limit - out.len());
+				let old_len = out.len();
+				scan_from = old_len.saturating_sub(overlap);
+				out.extend_from_slice(&available[..to_append]);
+				
+				// Consume only the bytes up to the match
+				match find_pattern(&out[scan_from..], pat) {
+					Some(end) => {
+						let end = scan_from + end;
+						self.consume(end - old_len);
+						out.truncate(end);
+						return Ok(true)
+					},
+					None => self.consume(to_append)
+				}
+			}
+		})
+	}
+	
+	/// Gets a reference to the underlying stream
+	pub fn get_ref(&self) -> &S {
+		&self.inner
+	}
+	/// Gets a mutable reference to the underlying stream
+	///
+	/// __Warning: reading directly from the underlying stream bypasses the buffer__
+	pub fn get_mut(&mut self) -> &mut S {
+		&mut self.inner
+	}
+	/// Returns the underlying stream
+	///
+	/// __Warning: any buffered data is lost__
+	pub fn into_inner(self) -> S {
+		self.inner
+	}
+}
@@ -0,0 +1,57 @@
+use crate::{ TimeoutIoError, Endianness };
+use std::convert::TryInto;
+
+
+/// A trait for decoding items (e.g. protocol frames) from a byte stream (see
+/// `Reader::try_read_frame`)
+pub trait Decoder {
+	/// The decoded item
+	type Item;
+	
+	/// Tries to decode an item from the beginning of `src` and returns the amount of consumed bytes
+	/// together with the item or `None` if `src` does not contain a complete item yet
+	///
+	/// _Info: Return an error (e.g. `LimitExceeded` or `InvalidInput`) to reject invalid data;
+	/// since `src` grows until an item is complete, the decoder is responsible for enforcing a size
+	/// limit._
+	fn decode(&mut self, src: &[u8]) -> Result<Option<(usize, Self::Item)>, TimeoutIoError>;
+}
+
+
+/// A decoder for frames that are prefixed with their length as 32-bit-integer (the prefix is not
+/// part of the decoded item)
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct LengthPrefixedDecoder {
+	endianness: Endianness,
+	max_len: usize
+}
+impl LengthPrefixedDecoder {
+	/// Creates a new decoder for length prefixes in `endianness` that rejects frames longer than
+	/// `max_len` with `LimitExceeded`
+	pub fn new(endianness: Endianness, max_len: usize) -> Self {
+		Self{ endianness, max_len }
+	}
+}
+impl Decoder for LengthPrefixedDecoder {
+	type Item = Vec<u8>;
+	
+	fn decode(&mut self, src: &[u8]) -> Result<Option<(usize, Self::Item)>, TimeoutIoError> {
+		// Decode the length prefix
+		let prefix: [u8; 4] = match src.get(..4) {
+			Some(prefix) => prefix.try_into().expect("The prefix has a fixed length"),
+			None => return Ok(None)
+		};
+		let len = match self.endianness {
+			Endianness::Big => u32::from_be_bytes(prefix),
+			Endianness::Little => u32::from_le_bytes(prefix)
+		};
+		let len: usize = len.try_into().map_err(|_| TimeoutIoError::LimitExceeded)?;
+		if len > self.max_len { return Err(TimeoutIoError::LimitExceeded) }
+		
+		// Decode the frame
+		match src[4..].get(..len) {
+			Some(frame) => Ok(Some((4 + len, frame.to_vec()))),
+			None => Ok(None)
+		}
+	}
+}
@@ -0,0 +1,60 @@
+use std::{
+	sync::Mutex,
+	time::Duration
+};
+
+
+/// The process-wide default timeouts
+static DEFAULT: Mutex<TimeoutConfig> = Mutex::new(TimeoutConfig::new(Duration::from_secs(30)));
+
+
+/// A timeout policy that is used by the `*_default`-methods (e.g. `Reader::try_read_default`)
+///
+/// _Info: The explicit-`Duration`-methods remain the primary API and are never affected by the
+/// default; the `*_default`-methods are only sugar for apps that use one consistent policy. The
+/// initial default is 30s for reads and writes._
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TimeoutConfig {
+	read: Duration,
+	write: Duration
+}
+impl TimeoutConfig {
+	/// Creates a new config that uses `timeout` for reads and writes
+	pub const fn new(timeout: Duration) -> Self {
+		Self{ read: timeout, write: timeout }
+	}
+	/// Sets the timeout for reads
+	pub const fn read(mut self, timeout: Duration) -> Self {
+		self.read = timeout;
+		self
+	}
+	/// Sets the timeout for writes
+	pub const fn write(mut self, timeout: Duration) -> Self {
+		self.write = timeout;
+		self
+	}
+	
+	/// The timeout for reads
+	pub const fn read_timeout(&self) -> Duration {
+		self.read
+	}
+	/// The timeout for writes
+	pub const fn write_timeout(&self) -> Duration {
+		self.write
+	}
+	
+	/// Installs `self` as process-wide default (affects all subsequent `*_default`-calls on all
+	/// threads)
+	pub fn install(self) {
+		*DEFAULT.lock().unwrap_or_else(|e| e.into_inner()) = self;
+	}
+	/// Returns the current process-wide default
+	pub fn current() -> Self {
+		*DEFAULT.lock().unwrap_or_else(|e| e.into_inner())
+	}
+}
+impl Default for TimeoutConfig {
+	fn default() -> Self {
+		Self::current()
+	}
+}
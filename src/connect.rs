@@ -0,0 +1,92 @@
+use crate::{ TimeoutIoError, InstantExt, deadline_after };
+#[cfg(unix)]
+use crate::WaitForEvent;
+use std::{
+	cmp::min,
+	time::{ Duration, Instant },
+	net::{ TcpStream, SocketAddr }
+};
+#[cfg(unix)]
+use std::{
+	io,
+	os::unix::{ io::FromRawFd, net::UnixStream }
+};
+
+
+/// Tries to connect to each address in `addrs` in order (with a timeout of `per_addr` for each
+/// attempt) until either a connection is established or the `total` budget is exhausted and
+/// returns the first connection
+///
+/// Returns
+///  - `InvalidInput` if `addrs` is empty,
+///  - `TimedOut` if the `total` budget was exhausted (or every attempt timed out), or
+///  - the error of the last failed attempt if all addresses refused/were unreachable.
+///
+/// _Info: This is the classic "try all A-records"-fallback; the attempts are sequential (not
+/// raced in parallel)._
+pub fn connect_any(addrs: &[SocketAddr], per_addr: Duration, total: Duration)
+	-> Result<TcpStream, TimeoutIoError>
+{
+	// Compute deadline
+	let (start, deadline) = (Instant::now(), deadline_after(total));
+	if addrs.is_empty() { return Err(TimeoutIoError::InvalidInput) }
+	
+	// Try each address
+	let mut last_error = None;
+	for address in addrs {
+		// Compute the timeout for this attempt
+		let timeout = min(per_addr, deadline.remaining());
+		if timeout.is_zero() { return Err(TimeoutIoError::timed_out(start, total)) }
+		
+		// Connect
+		match TcpStream::connect_timeout(address, timeout) {
+			Ok(stream) => return Ok(stream),
+			Err(error) => match TimeoutIoError::from(error) {
+				TimeoutIoError::TimedOut{ .. } => (),
+				error => last_error = Some(error)
+			}
+		}
+	}
+	Err(last_error.unwrap_or_else(|| TimeoutIoError::timed_out(start, total)))
+}
+
+
+/// Creates a pair of connected unix domain sockets via `socketpair(2)` (e.g. for IPC with a child
+/// process or to test timeout-IO without a TCP-listener)
+///
+/// _Info: Both ends are non-blocking and ready for use with `Reader`/`Writer`; each end owns it's
+/// socket and closes it on drop. The sockets are created with close-on-exec (atomically on Linux
+/// and the BSDs; on other unix-targets, e.g. macOS, it is set right after creation)._
+#[cfg(unix)]
+pub fn socket_pair() -> Result<(UnixStream, UnixStream), TimeoutIoError> {
+	// Create the sockets (atomically with close-on-exec where supported, so that a concurrent
+	// `fork`/`exec` cannot leak them)
+	#[cfg(any(
+		target_os = "linux", target_os = "android", target_os = "freebsd", target_os = "dragonfly",
+		target_os = "openbsd", target_os = "netbsd"
+	))]
+	let kind = libc::SOCK_STREAM | libc::SOCK_CLOEXEC;
+	#[cfg(not(any(
+		target_os = "linux", target_os = "android", target_os = "freebsd", target_os = "dragonfly",
+		target_os = "openbsd", target_os = "netbsd"
+	)))]
+	let kind = libc::SOCK_STREAM;
+	
+	let mut fds: [libc::c_int; 2] = [-1, -1];
+	if unsafe{ libc::socketpair(libc::AF_UNIX, kind, 0, fds.as_mut_ptr()) } == -1 {
+		return Err(io::Error::last_os_error().into())
+	}
+	let (s0, s1) = unsafe{ (UnixStream::from_raw_fd(fds[0]), UnixStream::from_raw_fd(fds[1])) };
+	
+	// Set close-on-exec afterwards on the other targets
+	#[cfg(not(any(
+		target_os = "linux", target_os = "android", target_os = "freebsd", target_os = "dragonfly",
+		target_os = "openbsd", target_os = "netbsd"
+	)))]
+	for fd in fds {
+		if unsafe{ libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) } == -1 {
+			return Err(io::Error::last_os_error().into())
+		}
+	}
+	Ok((s0.into_nonblocking()?, s1.into_nonblocking()?))
+}
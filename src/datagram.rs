@@ -0,0 +1,263 @@
+use crate::{ TimeoutIoError, InstantExt, with_deadline, WaitForEvent, EventMask };
+use std::{
+	io,
+	time::{ Duration, Instant },
+	net::{ UdpSocket, SocketAddr }
+};
+
+
+/// Receives as many datagrams as immediately available into `bufs` using `recvmmsg`
+#[cfg(target_os = "linux")]
+#[cfg_attr(libselect_fallback, allow(unused_unsafe))]
+fn receive_batch(socket: &UdpSocket, bufs: &mut[&mut[u8]])
+	-> Result<Vec<(usize, SocketAddr)>, io::Error>
+{
+	use crate::{ RawFd, event::libselect };
+	
+	// Prepare the buffers
+	let count = std::cmp::min(bufs.len(), unsafe{ libselect::BATCH_SIZE });
+	let ptrs: Vec<*mut u8> = bufs[..count].iter_mut().map(|b| b.as_mut_ptr()).collect();
+	let lens: Vec<usize> = bufs[..count].iter().map(|b| b.len()).collect();
+	let (mut received_lens, mut families) = (vec![0usize; count], vec![0u8; count]);
+	let (mut ips, mut ports) = (vec![0u8; count * 16], vec![0u16; count]);
+	
+	// Receive the datagrams
+	let mut received = 0;
+	let result = unsafe{ libselect::receive_batch(
+		socket.raw_fd(), ptrs.as_ptr(), lens.as_ptr(), count,
+		received_lens.as_mut_ptr(), families.as_mut_ptr(), ips.as_mut_ptr(), ports.as_mut_ptr(),
+		&mut received
+	) };
+	if result != 0 { return Err(io::Error::from_raw_os_error(result)) }
+	
+	// Collect the lengths and senders
+	(0..received).map(|i| {
+		let address = libselect::address_from_raw(families[i], &ips[i * 16..], ports[i])
+			.ok_or_else(|| io::Error::from(io::ErrorKind::InvalidData))?;
+		Ok((received_lens[i], address))
+	}).collect()
+}
+/// Receives as many datagrams as immediately available into `bufs` using `recv_from`
+#[cfg(not(target_os = "linux"))]
+fn receive_batch(socket: &UdpSocket, bufs: &mut[&mut[u8]])
+	-> Result<Vec<(usize, SocketAddr)>, io::Error>
+{
+	let mut received = Vec::new();
+	for buf in bufs.iter_mut() {
+		match socket.recv_from(buf) {
+			Ok(result) => received.push(result),
+			Err(ref e) if e.kind() == io::ErrorKind::WouldBlock && !received.is_empty() => break,
+			Err(e) => return Err(e)
+		}
+	}
+	Ok(received)
+}
+
+/// Sends as many datagrams as immediately possible using `sendmmsg`
+#[cfg(target_os = "linux")]
+#[cfg_attr(libselect_fallback, allow(unused_unsafe))]
+fn send_batch(socket: &UdpSocket, msgs: &[(&[u8], SocketAddr)]) -> Result<usize, io::Error> {
+	use crate::{ RawFd, event::libselect };
+	
+	// Prepare the messages
+	let count = std::cmp::min(msgs.len(), unsafe{ libselect::BATCH_SIZE });
+	let ptrs: Vec<*const u8> = msgs[..count].iter().map(|(d, _)| d.as_ptr()).collect();
+	let lens: Vec<usize> = msgs[..count].iter().map(|(d, _)| d.len()).collect();
+	let (mut families, mut ips, mut ports) = (Vec::new(), Vec::new(), Vec::new());
+	for (_, address) in &msgs[..count] {
+		let (family, ip, port) = libselect::address_to_raw(address);
+		families.push(family);
+		ips.extend_from_slice(&ip);
+		ports.push(port);
+	}
+	
+	// Send the datagrams
+	let mut sent = 0;
+	let result = unsafe{ libselect::send_batch(
+		socket.raw_fd(), ptrs.as_ptr(), lens.as_ptr(), count,
+		families.as_ptr(), ips.as_ptr(), ports.as_ptr(), &mut sent
+	) };
+	match result {
+		0 => Ok(sent),
+		e => Err(io::Error::from_raw_os_error(e))
+	}
+}
+/// Sends as many datagrams as immediately possible using `send_to`
+#[cfg(not(target_os = "linux"))]
+fn send_batch(socket: &UdpSocket, msgs: &[(&[u8], SocketAddr)]) -> Result<usize, io::Error> {
+	let mut sent = 0;
+	for (data, address) in msgs {
+		match socket.send_to(data, address) {
+			Ok(_) => sent += 1,
+			Err(ref e) if e.kind() == io::ErrorKind::WouldBlock && sent > 0 => break,
+			Err(e) => return Err(e)
+		}
+	}
+	Ok(sent)
+}
+
+/// Peeks the size of the next datagram using `MSG_PEEK | MSG_TRUNC`
+#[cfg(target_os = "linux")]
+fn datagram_size(socket: &UdpSocket, _max: usize) -> Result<usize, io::Error> {
+	use crate::{ RawFd, event::libselect };
+	
+	let mut size = 0;
+	match unsafe{ libselect::datagram_size(socket.raw_fd(), &mut size) } {
+		0 => Ok(size),
+		e => Err(io::Error::from_raw_os_error(e))
+	}
+}
+/// Returns `max` because the size of the next datagram cannot be peeked portably
+#[cfg(not(target_os = "linux"))]
+fn datagram_size(_socket: &UdpSocket, max: usize) -> Result<usize, io::Error> {
+	Ok(max)
+}
+
+
+/// A trait for batched datagram-IO with timeouts
+///
+/// _Info: On Linux, the batches are received/sent with a single `recvmmsg`/`sendmmsg`-syscall
+/// (up to 64 datagrams per call); on other platforms we fall back to a loop of
+/// `recv_from`/`send_to`-calls._
+pub trait Datagram {
+	/// Waits until at least one datagram is available and receives as many datagrams as
+	/// _immediately_ available (but at most `bufs.len()`) into `bufs`. Returns the length and the
+	/// sender of each received datagram in the order of `bufs`.
+	///
+	/// _Note: If fewer datagrams than buffers are available, only the first buffers are filled and
+	/// the returned vector is shorter than `bufs`; datagrams that are larger than their buffer are
+	/// truncated._
+	///
+	/// _Note: This function catches all interal timeouts/interrupts and returns only if there was
+	/// either one successful batch-receive or the `timeout` was hit or a non-recoverable error
+	/// occurred._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn try_receive_mmsg(&self, bufs: &mut[&mut[u8]], timeout: Duration)
+		-> Result<Vec<(usize, SocketAddr)>, TimeoutIoError>;
+	
+	/// Waits until `self` is writeable and sends as many `(data, address)`-datagrams from `msgs`
+	/// as _immediately_ possible. Returns the amount of datagrams sent.
+	///
+	/// _Note: If not all datagrams could be sent, you can continue with `msgs[sent..]`._
+	///
+	/// _Note: This function catches all interal timeouts/interrupts and returns only if there was
+	/// either one successful batch-send or the `timeout` was hit or a non-recoverable error
+	/// occurred._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn try_send_mmsg(&self, msgs: &[(&[u8], SocketAddr)], timeout: Duration)
+		-> Result<usize, TimeoutIoError>;
+	
+	/// "Accepts" a datagram-connection: waits for the first datagram, creates a new socket that is
+	/// connected to it's sender and returns the new socket, the sender's address and the initial
+	/// payload
+	///
+	/// _Info: The new socket is bound to the same local IP but to a new ephemeral port (like in
+	/// TFTP); so subsequent replies to the peer originate from that new port and the peer must
+	/// continue the conversation with the sender address of the replies. `self` is not modified and
+	/// keeps listening for new peers. The new socket is non-blocking._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn accept_udp(&self, timeout: Duration)
+		-> Result<(UdpSocket, SocketAddr, Vec<u8>), TimeoutIoError>;
+	
+	/// Receives exactly one datagram (but at most `max` bytes) into a right-sized `Vec` and
+	/// returns it together with it's sender
+	///
+	/// _Info: On Linux, the size of the datagram is peeked (`MSG_PEEK | MSG_TRUNC`) so that exactly
+	/// the required amount of memory is allocated; on other platforms, a `max`-sized buffer is
+	/// allocated and truncated to the datagram's length. In both cases, datagrams larger than `max`
+	/// are truncated._
+	///
+	/// _Note: This function catches all interal timeouts/interrupts and returns only if there was
+	/// either one successful receive or the `timeout` was hit or a non-recoverable error occurred._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn try_receive_vec(&self, max: usize, timeout: Duration)
+		-> Result<(Vec<u8>, SocketAddr), TimeoutIoError>;
+}
+impl Datagram for UdpSocket {
+	fn try_receive_mmsg(&self, bufs: &mut[&mut[u8]], timeout: Duration)
+		-> Result<Vec<(usize, SocketAddr)>, TimeoutIoError>
+	{
+		// Compute the deadline
+		with_deadline(timeout, |deadline| {
+			// Loop until we have *one* successful batch
+			if bufs.is_empty() { return Ok(Vec::new()) }
+			loop {
+				self.wait_for_event(EventMask::new_r(), deadline.remaining())?;
+				match receive_batch(self, bufs) {
+					Ok(received) => return Ok(received),
+					Err(error) => {
+						let error = TimeoutIoError::from(error);
+						if !error.should_retry() { return Err(error) }
+					}
+				}
+			}
+		})
+	}
+	fn try_send_mmsg(&self, msgs: &[(&[u8], SocketAddr)], timeout: Duration)
+		-> Result<usize, TimeoutIoError>
+	{
+		// Compute the deadline
+		with_deadline(timeout, |deadline| {
+			// Loop until we have *one* successful batch
+			if msgs.is_empty() { return Ok(0) }
+			loop {
+				self.wait_for_event(EventMask::new_w(), deadline.remaining())?;
+				match send_batch(self, msgs) {
+					Ok(sent) => return Ok(sent),
+					Err(error) => {
+						let error = TimeoutIoError::from(error);
+						if !error.should_retry() { return Err(error) }
+					}
+				}
+			}
+		})
+	}
+	fn accept_udp(&self, timeout: Duration)
+		-> Result<(UdpSocket, SocketAddr, Vec<u8>), TimeoutIoError>
+	{
+		// Receive the first datagram (up to the maximum datagram size)
+		let (start, mut payload) = (Instant::now(), vec![0; 65536]);
+		let (len, peer) = {
+			let mut bufs = [payload.as_mut_slice()];
+			let received = self.try_receive_mmsg(&mut bufs[..], timeout)?;
+			received.first().copied().ok_or_else(|| TimeoutIoError::timed_out(start, timeout))?
+		};
+		payload.truncate(len);
+		
+		// Create a new socket that is connected to the peer
+		let mut local = self.local_addr()?;
+		local.set_port(0);
+		let socket = UdpSocket::bind(local)?;
+		socket.connect(peer)?;
+		socket.set_blocking_mode(false)?;
+		Ok((socket, peer, payload))
+	}
+	fn try_receive_vec(&self, max: usize, timeout: Duration)
+		-> Result<(Vec<u8>, SocketAddr), TimeoutIoError>
+	{
+		// Compute the deadline
+		with_deadline(timeout, |deadline| {
+			// Loop until we have *one* successful receive
+			loop {
+				self.wait_for_event(EventMask::new_r(), deadline.remaining())?;
+				let result = datagram_size(self, max).and_then(|size| {
+					let mut buf = vec![0; std::cmp::min(size, max)];
+					let (received, from) = self.recv_from(&mut buf)?;
+					buf.truncate(received);
+					Ok((buf, from))
+				});
+				match result {
+					Ok(received) => return Ok(received),
+					Err(error) => {
+						let error = TimeoutIoError::from(error);
+						if !error.should_retry() { return Err(error) }
+					}
+				}
+			}
+		})
+	}
+}
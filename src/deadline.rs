@@ -0,0 +1,49 @@
+use std::{
+	convert::TryInto,
+	time::{ Duration, Instant },
+	sync::{ Arc, atomic::{ AtomicU64, Ordering } }
+};
+
+
+/// A deadline that can be shared across threads and moved while an operation is in progress
+///
+/// _Info: Operations that use a shared deadline re-read it at least every
+/// `SharedDeadline::GRANULARITY`; so moving the deadline (e.g. via `expire`) takes effect within
+/// that granularity even if the operation is currently waiting._
+///
+/// _Info: The deadline is stored as milliseconds relative to an internal base `Instant` in an
+/// `Arc<AtomicU64>`; so all clones refer to the same deadline._
+#[derive(Debug, Clone)]
+pub struct SharedDeadline {
+	base: Instant,
+	millis: Arc<AtomicU64>
+}
+impl SharedDeadline {
+	/// The maximum duration of a single wait before the deadline is re-read
+	pub const GRANULARITY: Duration = Duration::from_millis(100);
+	
+	/// Creates a new shared deadline that expires after `timeout`
+	pub fn new(timeout: Duration) -> Self {
+		let this = Self{ base: Instant::now(), millis: Arc::new(AtomicU64::new(0)) };
+		this.set(timeout);
+		this
+	}
+	
+	/// Moves the deadline so that it expires after `timeout` (counted from now)
+	pub fn set(&self, timeout: Duration) {
+		let millis = self.base.elapsed().saturating_add(timeout).as_millis().try_into().unwrap_or(u64::MAX);
+		self.millis.store(millis, Ordering::SeqCst);
+	}
+	
+	/// Expires the deadline immediately (e.g. to wind down all operations that share this deadline
+	/// during a coordinated shutdown)
+	pub fn expire(&self) {
+		self.set(Duration::from_secs(0));
+	}
+	
+	/// Computes the remaining time underflow-safe
+	pub fn remaining(&self) -> Duration {
+		let deadline = Duration::from_millis(self.millis.load(Ordering::SeqCst));
+		deadline.checked_sub(self.base.elapsed()).unwrap_or_default()
+	}
+}
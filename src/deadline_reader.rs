@@ -0,0 +1,73 @@
+use crate::{ TimeoutIoError, InstantExt, deadline_after, TIMED_OUT, Reader };
+use std::time::{ Duration, Instant };
+
+
+/// A reader that enforces a fixed overall deadline across _all_ read-calls (e.g. to prevent a
+/// never-idle peer from keeping you reading forever)
+///
+/// _Info: The deadline is set once at construction; every read uses the remaining time and returns
+/// `TimedOut` once the deadline has passed – regardless of how many calls you make. If you need a
+/// deadline that can be moved while an operation is in progress, use `SharedDeadline`._
+#[derive(Debug)]
+pub struct DeadlineReader<S> {
+	inner: S,
+	deadline: Instant
+}
+impl<S: Reader> DeadlineReader<S> {
+	/// Creates a new reader whose deadline expires after `timeout` (counted from now)
+	pub fn new(inner: S, timeout: Duration) -> Self {
+		Self::with_deadline(inner, deadline_after(timeout))
+	}
+	/// Creates a new reader with the absolute `deadline`
+	pub fn with_deadline(inner: S, deadline: Instant) -> Self {
+		Self{ inner, deadline }
+	}
+	
+	/// The absolute deadline
+	pub fn deadline(&self) -> Instant {
+		self.deadline
+	}
+	/// The remaining time until the deadline is reached
+	pub fn remaining(&self) -> Duration {
+		self.deadline.remaining()
+	}
+	
+	/// Like `Reader::try_read`, but uses the remaining time until the deadline as timeout
+	///
+	/// __Warning: the underlying stream must be non-blocking or the function won't work as
+	/// expected__
+	pub fn try_read(&mut self, buf: &mut[u8], pos: &mut usize) -> Result<(), TimeoutIoError> {
+		let remaining = self.remaining_checked()?;
+		self.inner.try_read(buf, pos, remaining)
+	}
+	/// Like `Reader::try_read_exact`, but uses the remaining time until the deadline as timeout
+	///
+	/// __Warning: the underlying stream must be non-blocking or the function won't work as
+	/// expected__
+	pub fn try_read_exact(&mut self, buf: &mut[u8], pos: &mut usize) -> Result<(), TimeoutIoError> {
+		let remaining = self.remaining_checked()?;
+		self.inner.try_read_exact(buf, pos, remaining)
+	}
+	
+	/// A reference to the underlying stream
+	pub fn get_ref(&self) -> &S {
+		&self.inner
+	}
+	/// A mutable reference to the underlying stream
+	pub fn get_mut(&mut self) -> &mut S {
+		&mut self.inner
+	}
+	/// Returns the underlying stream
+	pub fn into_inner(self) -> S {
+		self.inner
+	}
+	
+	/// The remaining time or `TimedOut` if the deadline has passed (so that an expired reader does
+	/// not perform a non-blocking attempt)
+	fn remaining_checked(&self) -> Result<Duration, TimeoutIoError> {
+		match self.deadline.remaining() {
+			remaining if remaining.is_zero() => Err(TIMED_OUT),
+			remaining => Ok(remaining)
+		}
+	}
+}
@@ -0,0 +1,80 @@
+use crate::RawFd;
+use std::{
+	fmt::{ self, Debug, Formatter },
+	io::{ self, Read, Write, IoSlice, IoSliceMut }
+};
+
+
+/// A combination of `Read` and `Write` that can be used as trait object (see `DynStream`)
+pub trait ReadWrite: Read + Write {}
+impl<T: Read + Write + ?Sized> ReadWrite for T {}
+
+
+/// A type-erased stream that retains the raw fd/socket of the erased stream; this makes `Reader`
+/// and `Writer` available for `&mut dyn ReadWrite` (the blanket implementations require a concrete
+/// type that exposes a raw fd/socket)
+///
+/// _Info: This is the non-generic counterpart to `Box<dyn Reader>`/`Box<dyn Writer>`: the erased
+/// stream keeps it's concrete `Read`/`Write`-implementation, and all waits are performed on the
+/// retained raw fd/socket._
+pub struct DynStream<'a> {
+	stream: &'a mut dyn ReadWrite,
+	fd: u64
+}
+impl<'a> DynStream<'a> {
+	/// Erases `stream` and retains it's raw fd/socket
+	pub fn new<S: ReadWrite + RawFd>(stream: &'a mut S) -> Self {
+		Self{ fd: stream.raw_fd(), stream }
+	}
+	/// Creates a new type-erased stream from an already erased `stream` and it's raw `fd`/socket
+	///
+	/// # Safety
+	/// `fd` must be the raw fd/socket that `stream` performs it's IO on, and it must remain open
+	/// for `'a`. If `fd` does not match `stream`, all waits are performed on the wrong handle
+	/// (which results in spurious timeouts or busy waiting); if `fd` is closed while `self` exists,
+	/// the waits may operate on an unrelated handle that reuses the number.
+	pub unsafe fn from_raw_parts(stream: &'a mut dyn ReadWrite, fd: u64) -> Self {
+		Self{ stream, fd }
+	}
+	
+	/// The erased stream
+	pub fn get_mut(&mut self) -> &mut dyn ReadWrite {
+		self.stream
+	}
+}
+impl Debug for DynStream<'_> {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		f.debug_struct("DynStream").field("fd", &self.fd).finish()
+	}
+}
+impl Read for DynStream<'_> {
+	fn read(&mut self, buf: &mut[u8]) -> io::Result<usize> {
+		self.stream.read(buf)
+	}
+	fn read_vectored(&mut self, bufs: &mut[IoSliceMut]) -> io::Result<usize> {
+		self.stream.read_vectored(bufs)
+	}
+}
+impl Write for DynStream<'_> {
+	fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+		self.stream.write(data)
+	}
+	fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+		self.stream.write_vectored(bufs)
+	}
+	fn flush(&mut self) -> io::Result<()> {
+		self.stream.flush()
+	}
+}
+#[cfg(unix)]
+impl std::os::unix::io::AsRawFd for DynStream<'_> {
+	fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+		self.fd as std::os::unix::io::RawFd
+	}
+}
+#[cfg(windows)]
+impl std::os::windows::io::AsRawSocket for DynStream<'_> {
+	fn as_raw_socket(&self) -> std::os::windows::io::RawSocket {
+		self.fd as std::os::windows::io::RawSocket
+	}
+}
@@ -1,24 +1,114 @@
-use crate::TimeoutIoError;
-use std::{ self, io, convert::TryInto, time::Duration };
+use crate::{ TimeoutIoError, InstantExt, deadline_after, Waker, WokenOr, Timer, observer };
+use std::{
+	self, io, cmp::min, convert::TryInto,
+	time::{ Duration, Instant }
+};
+
+
+/// The WSA-error for "not a socket"
+#[cfg(windows)]
+const WSAENOTSOCK: std::os::raw::c_int = 10038;
+/// The WSA-error for "winsock was not initialized"
+#[cfg(windows)]
+const WSANOTINITIALISED: std::os::raw::c_int = 10093;
 
 
 /// Interface to `libselect`
-mod libselect {
+pub(crate) mod libselect {
+	use std::net::{ SocketAddr, SocketAddrV4, SocketAddrV6, Ipv4Addr, Ipv6Addr };
+	#[cfg(not(libselect_fallback))]
 	use std::os::raw::c_int;
+	
+	/// Converts `address` into a raw `(family, ip, port)`-triple
+	pub fn address_to_raw(address: &SocketAddr) -> (u8, [u8; 16], u16) {
+		match address {
+			SocketAddr::V4(address) => {
+				let mut ip = [0u8; 16];
+				ip[..4].copy_from_slice(&address.ip().octets());
+				(4, ip, address.port())
+			},
+			SocketAddr::V6(address) => (6, address.ip().octets(), address.port())
+		}
+	}
+	/// Converts a raw `(family, ip, port)`-triple into an address
+	#[cfg_attr(not(target_os = "linux"), allow(unused))]
+	pub fn address_from_raw(family: u8, ip: &[u8], port: u16) -> Option<SocketAddr> {
+		match family {
+			4 => {
+				let ip = Ipv4Addr::new(ip[0], ip[1], ip[2], ip[3]);
+				Some(SocketAddr::V4(SocketAddrV4::new(ip, port)))
+			},
+			6 => {
+				let mut octets = [0u8; 16];
+				octets.copy_from_slice(&ip[..16]);
+				Some(SocketAddr::V6(SocketAddrV6::new(Ipv6Addr::from(octets), port, 0, 0)))
+			},
+			_ => None
+		}
+	}
+	
+	// The fallback's constants are plain statics; so the `unsafe`-blocks around them are unused
+	// (which is allowed where necessary)
+	#[cfg(libselect_fallback)]
+	pub use crate::fallback::*;
+	
+	#[cfg(not(libselect_fallback))]
 	extern "C" {
 		pub static EVENT_READ:  u8;
 		pub static EVENT_WRITE: u8;
 		pub static EVENT_ERROR: u8;
+		pub static EVENT_HANGUP: u8;
 		pub static INVALID_FD:  u64;
 		
 		pub fn wait_for_event(timeout_ms: u64, fds: *const u64, events: *mut u8) -> c_int;
+		pub fn wait_for_event_us(timeout_us: u64, fds: *const u64, events: *mut u8) -> c_int;
+		#[cfg(feature = "checked")]
+		pub fn check_fd(fd: u64) -> c_int;
+		pub fn set_linger(fd: u64, enabled: u8, seconds: u32) -> c_int;
+		pub fn set_keepalive(fd: u64, enabled: u8) -> c_int;
+		pub fn set_tos(fd: u64, tos: u8) -> c_int;
+		pub fn bind_reusable(family: u8, ip: *const u8, port: u16, reuse_port: u8, fd: *mut u64)
+			-> c_int;
+	}
+	#[cfg(windows)]
+	extern "C" {
 		pub fn set_blocking_mode(descriptor: u64, blocking: u8) -> c_int;
 	}
+	#[cfg(all(unix, not(libselect_fallback)))]
+	extern "C" {
+		pub fn set_cork(fd: u64, enabled: u8) -> c_int;
+		pub fn set_traffic_class(fd: u64, traffic_class: u8) -> c_int;
+		pub fn send_file(fd: u64, file_fd: u64, offset: *mut u64, count: u64, sent: *mut u64)
+			-> c_int;
+	}
+	#[cfg(all(target_os = "linux", not(libselect_fallback)))]
+	extern "C" {
+		pub static BATCH_SIZE: usize;
+		
+		pub fn receive_batch(fd: u64, bufs: *const *mut u8, lens: *const usize, count: usize,
+			received_lens: *mut usize, families: *mut u8, ips: *mut u8, ports: *mut u16,
+			received: *mut usize) -> c_int;
+		pub fn send_batch(fd: u64, datas: *const *const u8, lens: *const usize, count: usize,
+			families: *const u8, ips: *const u8, ports: *const u16, sent: *mut usize) -> c_int;
+		pub fn datagram_size(fd: u64, size: *mut usize) -> c_int;
+		pub fn set_receive_timeout(fd: u64, timeout_us: u64, previous_us: *mut u64) -> c_int;
+	}
 }
 
 
 /// A wrapper-trait that unifies the `std::os::unix::io::AsRawFd` and
 /// `std::os::windows::io::AsRawSocket` traits
+///
+/// _Info: To pass a stream on to helper functions, make them generic over `&mut R where R: Reader`
+/// (or `Writer`) and pass `&mut stream`._
+///
+/// _Note: In-memory types like `std::io::Cursor` or `&[u8]` don't expose a raw fd/socket and thus
+/// cannot be used with this crate._
+#[diagnostic::on_unimplemented(
+	message = "`{Self}` does not expose a raw file descriptor/socket",
+	label = "`{Self}` is not backed by a raw fd/socket",
+	note = "`timeout_io` waits for events on raw fds/sockets; in-memory types like `Cursor` or `&[u8]` are not supported"
+)]
 pub trait RawFd {
 	/// The underlying raw file descriptor
 	fn raw_fd(&self) -> u64;
@@ -36,6 +126,7 @@ impl<T> RawFd for T where T: std::os::windows::io::AsRawSocket {
 /// A struct describing null or more IO-events
 #[repr(transparent)] #[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
 pub struct EventMask{ raw: u8 }
+#[cfg_attr(libselect_fallback, allow(unused_unsafe))]
 impl EventMask {
 	/// Creates a new read/error event mask
 	pub fn new_r() -> Self {
@@ -51,6 +142,37 @@ impl EventMask {
 		Self{ raw: unsafe{ EVENT_READ | EVENT_WRITE | EVENT_ERROR } }
 	}
 	
+	/// Creates a new read/error/hangup event mask to detect if the peer shut down the connection
+	/// without reading
+	///
+	/// _Info: With the pure-Rust fallback on Linux, hangups are detected via `POLLRDHUP`/`POLLHUP`.
+	/// `select` cannot express hangups; so in the C-shim (and the fallback on other unix-targets),
+	/// they are approximated: if the handle is readable, we peek for data and report a hangup if
+	/// the peek returns zero bytes (i.e. the peer sent a FIN)._
+	pub fn new_rh() -> Self {
+		use self::libselect::{ EVENT_READ, EVENT_ERROR, EVENT_HANGUP };
+		Self{ raw: unsafe{ EVENT_READ | EVENT_ERROR | EVENT_HANGUP } }
+	}
+	
+	/// Creates a new event mask from the `read`/`write`/`error`-flags
+	pub fn from_rwe(read: bool, write: bool, error: bool) -> Self {
+		use self::libselect::{ EVENT_READ, EVENT_WRITE, EVENT_ERROR };
+		let mut raw = 0;
+		if read { raw |= unsafe{ EVENT_READ } }
+		if write { raw |= unsafe{ EVENT_WRITE } }
+		if error { raw |= unsafe{ EVENT_ERROR } }
+		Self{ raw }
+	}
+	
+	/// Checks if the mask contains a hangup-event
+	pub fn contains_hangup(&self) -> bool {
+		self.raw & unsafe{ libselect::EVENT_HANGUP } != 0
+	}
+	/// Checks if the mask contains no event at all
+	pub fn is_empty(&self) -> bool {
+		self.raw == 0
+	}
+	
 	/// Checks if the mask contains read/write/error
 	pub fn rwe(&self) -> (bool, bool, bool) {
 		(
@@ -62,76 +184,576 @@ impl EventMask {
 }
 
 
+/// Ensures that all `fds` are open (e.g. to detect stale FDs that were inherited by a forked child
+/// but closed by the parent) and returns `InvalidInput` otherwise
+///
+/// _Info: This is only enabled with the `checked`-feature. Note that a stale FD number that has
+/// already been reused by a new FD cannot be detected._
+#[cfg(feature = "checked")]
+fn check_fds(fds: &[u64]) -> Result<(), TimeoutIoError> {
+	for fd in fds {
+		match unsafe{ libselect::check_fd(*fd) } {
+			0 => (),
+			#[cfg(unix)]
+			libc::EBADF => return Err(TimeoutIoError::InvalidInput),
+			#[cfg(windows)]
+			WSAENOTSOCK => return Err(TimeoutIoError::InvalidInput),
+			e => return Err(io::Error::from_raw_os_error(e).into())
+		}
+	}
+	Ok(())
+}
+/// The maximum amount of distinct raw FDs that `select_raw` waits on without allocating
+const STACK_FDS: usize = 8;
+/// Waits on the raw `fds` until an event from the according `events` occurrs or `timeout` was
+/// reached and replaces `events` with the events that occurred
+///
+/// _Info: If `precise` is set, the timeout is passed with microsecond-precision; otherwise it is
+/// truncated to whole milliseconds._
+#[cfg_attr(libselect_fallback, allow(unused_unsafe))]
+fn select_raw(fds: impl IntoIterator<Item = u64>, events: &mut[EventMask], timeout: Duration,
+	precise: bool) -> Result<(), TimeoutIoError>
+{
+	// Collect up to `STACK_FDS` raw FDs (plus one to detect larger sets) on the stack
+	let (mut fds, mut raw) = (fds.into_iter(), [unsafe{ libselect::INVALID_FD }; STACK_FDS + 1]);
+	let mut count = 0;
+	while count < raw.len() {
+		match fds.next() {
+			Some(fd) => raw[count] = fd,
+			None => break
+		}
+		count += 1;
+	}
+	
+	// Wait on small sets of distinct FDs directly; only larger sets or sets with duplicate FDs need
+	// to be merged
+	let distinct = (1..count).all(|i| !raw[..i].contains(&raw[i]));
+	match count <= STACK_FDS && distinct {
+		true => select_terminated(&raw[..=count], events, timeout, precise),
+		false => RawSelect::default().select(raw[..count].iter().copied().chain(fds), events,
+			timeout, precise)
+	}
+}
+/// The buffers to merge duplicate raw FDs for `select_terminated` (keep them to wait repeatedly
+/// without allocating)
+#[derive(Debug, Default)]
+struct RawSelect {
+	/// The raw FD of each registration
+	fds: Vec<u64>,
+	/// The registrations sorted by their raw FD
+	order: Vec<usize>,
+	/// The position of each registration's raw FD in `unique`
+	indices: Vec<usize>,
+	/// The distinct raw FDs (terminated by `INVALID_FD`) and their merged event masks
+	unique: Vec<u64>,
+	merged: Vec<EventMask>
+}
+impl RawSelect {
+	/// Like `select_raw`, but reuses the buffers
+	#[cfg_attr(libselect_fallback, allow(unused_unsafe))]
+	fn select(&mut self, fds: impl IntoIterator<Item = u64>, events: &mut[EventMask],
+		timeout: Duration, precise: bool) -> Result<(), TimeoutIoError>
+	{
+		// Collect the raw FDs and sort the registrations by FD
+		let Self{ fds: raw_fds, order, indices, unique, merged } = self;
+		raw_fds.clear();
+		raw_fds.extend(fds);
+		assert_eq!(raw_fds.len(), events.len(), "Each FD needs exactly one event mask");
+		order.clear();
+		order.extend(0..raw_fds.len());
+		order.sort_unstable_by_key(|registration| raw_fds[*registration]);
+		
+		// Merge the masks of duplicate FDs so that every FD is passed to libselect only once
+		unique.clear();
+		merged.clear();
+		indices.clear();
+		indices.resize(raw_fds.len(), 0);
+		for registration in order.iter().copied() {
+			if unique.last() != Some(&raw_fds[registration]) {
+				unique.push(raw_fds[registration]);
+				merged.push(EventMask::default());
+			}
+			merged[unique.len() - 1].raw |= events[registration].raw;
+			indices[registration] = unique.len() - 1;
+		}
+		
+		// Terminate the raw FDs and map the events back to the registrations
+		unique.push(unsafe{ libselect::INVALID_FD });
+		select_terminated(unique, merged, timeout, precise)?;
+		for (event, index) in events.iter_mut().zip(indices.iter()) {
+			event.raw &= merged[*index].raw;
+		}
+		Ok(())
+	}
+}
+/// Waits on a single raw `fd` like `select_raw`, but without allocating (this is the hot path of
+/// `WaitForEvent`)
+#[cfg_attr(libselect_fallback, allow(unused_unsafe))]
+fn select_single(fd: u64, event: EventMask, timeout: Duration, precise: bool)
+	-> Result<EventMask, TimeoutIoError>
+{
+	let (fds, mut events) = ([fd, unsafe{ libselect::INVALID_FD }], [event]);
+	select_terminated(&fds, &mut events, timeout, precise)?;
+	Ok(events[0])
+}
+/// Waits on the `INVALID_FD`-terminated raw `fds` (see `select_raw`)
+#[cfg_attr(libselect_fallback, allow(unused_unsafe))]
+fn select_terminated(fds: &[u64], events: &mut[EventMask], timeout: Duration, precise: bool)
+	-> Result<(), TimeoutIoError>
+{
+	// Validate the raw FDs
+	assert_eq!(fds.last(), Some(unsafe{ &libselect::INVALID_FD }), "The FDs must be terminated");
+	assert_eq!(fds.len() - 1, events.len(), "Each FD needs exactly one event mask");
+	#[cfg(feature = "checked")]
+	check_fds(&fds[..events.len()])?;
+	
+	// Round too small timeouts up so that only a zero `timeout` performs a non-waiting poll and clamp
+	// too large timeouts (which are effectively infinite anyway)
+	let result = match precise {
+		true => {
+			let mut timeout_us: u64 = timeout.as_micros().try_into().unwrap_or(u64::MAX);
+			if timeout_us == 0 && !timeout.is_zero() { timeout_us = 1 }
+			unsafe{ libselect::wait_for_event_us(timeout_us, fds.as_ptr(), events.as_mut_ptr() as *mut u8) }
+		},
+		false => {
+			let mut timeout_ms: u64 = timeout.as_millis().try_into().unwrap_or(u64::MAX);
+			if timeout_ms == 0 && !timeout.is_zero() { timeout_ms = 1 }
+			unsafe{ libselect::wait_for_event(timeout_ms, fds.as_ptr(), events.as_mut_ptr() as *mut u8) }
+		}
+	};
+	match result {
+		0 => Ok(()),
+		e => Err(io::Error::from_raw_os_error(e).into())
+	}
+}
+
+
+/// Waits on the raw `(fd, event)`-pairs until an event occurrs or `timeout` was reached and returns
+/// the events that occurred for each pair (in the order of `fds`; use `EventMask::rwe` to
+/// decompose them)
+///
+/// This is the lowest-level primitive atop `libselect` that allows you to poll raw FDs without the
+/// `SelectSet`-lifetimes. If the timeout is reached, all returned event masks are empty.
+///
+/// _Info: The same FD may be passed multiple times with different event masks; each pair is
+/// reported only with the events of it's own mask._
+pub fn poll_many(fds: &[(u64, EventMask)], timeout: Duration)
+	-> Result<Vec<EventMask>, TimeoutIoError>
+{
+	let mut events: Vec<EventMask> = fds.iter().map(|(_, event)| *event).collect();
+	select_raw(fds.iter().map(|(fd, _)| *fd), &mut events, timeout, false)?;
+	Ok(events)
+}
+
+
+/// The handles where an event occurred, grouped by the event type
+#[derive(Debug)]
+pub struct SelectGroups<'a, T> {
+	/// The handles that are ready for reading
+	pub readable: Vec<&'a T>,
+	/// The handles that are ready for writing
+	pub writable: Vec<&'a T>,
+	/// The handles where an error occurred
+	pub errored: Vec<&'a T>
+}
+
+
+/// The events returned by `SelectSet::select_with_timers`
+#[derive(Debug)]
+pub struct SelectEvents<'a, T> {
+	/// The `(handle, event_that_occurred)`-pairs for the handles where an event occurred
+	pub handles: Vec<(&'a T, EventMask)>,
+	/// The timers that expired
+	pub timers: Vec<&'a Timer>
+}
+
+
 /// A set of multiple `(handle: event)`-pairs that allows you to call `select` on all pairs at the
 /// same time
 pub struct SelectSet<'a, T: RawFd> {
 	handles: Vec<&'a T>,
-	events: Vec<EventMask>
+	events: Vec<EventMask>,
+	/// The events that were already reported by `select_edge` and not yet re-armed
+	fired: Vec<EventMask>,
+	timers: Vec<&'a Timer>,
+	/// The buffers that are reused by `dispatch`
+	raw: RawSelect,
+	occurred: Vec<EventMask>
 }
 impl<'a, T: RawFd> SelectSet<'a, T> {
 	/// Creates a new select set
+	#[allow(clippy::new_without_default)]
 	pub fn new() -> Self {
-		Self{ handles: Vec::new(), events: Vec::new() }
+		Self{
+			handles: Vec::new(), events: Vec::new(), fired: Vec::new(), timers: Vec::new(),
+			raw: RawSelect::default(), occurred: Vec::new()
+		}
 	}
 	
 	/// Pushes a new `handle` and the according `event` mask wait for to the set
+	///
+	/// _Info: The same handle may be pushed multiple times with different event masks; each
+	/// registration is reported separately and only with the events of it's own mask. At the
+	/// syscall level, duplicate handles are merged (i.e. each raw fd/socket is passed only once with
+	/// the union of all masks), so duplicate pushes don't cause redundant work._
 	pub fn push(&mut self, handle: &'a T, event: EventMask) {
 		self.handles.push(handle);
 		self.events.push(event);
+		self.fired.push(EventMask::default());
+	}
+	/// The amount of distinct raw fds/sockets in the set (i.e. the amount of handles that are
+	/// passed to the syscall; see `push`)
+	pub fn fd_count(&self) -> usize {
+		let mut fds: Vec<u64> = self.handles.iter().map(|h| h.raw_fd()).collect();
+		fds.sort_unstable();
+		fds.dedup();
+		fds.len()
+	}
+	/// Pushes a new `timer` to the set (see `select_with_timers`)
+	///
+	/// _Note: Timers are only waited on by `select_with_timers`; the other select-functions ignore
+	/// them._
+	pub fn push_timer(&mut self, timer: &'a Timer) {
+		self.timers.push(timer);
 	}
 	
 	/// Waits on all handles in the set until an event occurrs or `timeout` was reached. Returns
 	/// only the `(handle, event_that_occurred)`-pairs for the handles where an event occurred.
+	///
+	/// _Note: A zero `timeout` performs exactly one non-blocking poll over the whole set (i.e. a
+	/// snapshot of the current readiness of every handle in one syscall). Like any other timeout, it
+	/// does not result in `TimedOut`; if no handle is ready, the returned `Vec` is empty._
 	pub fn select(mut self, timeout: Duration) -> Result<Vec<(&'a T, EventMask)>, TimeoutIoError> {
-		// Create raw event masks and raw FDs
-		let mut fds: Vec<u64> = self.handles.iter().map(|h| h.raw_fd()).collect();
-		fds.push(unsafe{ libselect::INVALID_FD });
-		
-		// Call libselect
-		let result = unsafe{ libselect::wait_for_event(
-			timeout.as_millis().try_into().expect("`timeout.as_millis()` > `u64`"),
-			fds.as_ptr(), self.events.as_mut_ptr() as *mut u8
-		) };
-		if result != 0 { Err(io::Error::from_raw_os_error(result))? }
+		// Call libselect with the raw FDs
+		let fds = self.handles.iter().map(|h| h.raw_fd());
+		select_raw(fds, &mut self.events, timeout, false)?;
 		
 		// Yield the handles where an event occurred
 		let yielded = self.handles.into_iter().zip(self.events)
-			.filter(|(_, e)| e.rwe() != (false, false, false))
+			.filter(|(_, e)| !e.is_empty())
+			.collect();
+		Ok(yielded)
+	}
+	
+	/// Like `select`, but also waits for the timers in the set and returns the expired timers as
+	/// distinct events (e.g. to wait for "this socket is readable OR this timer fired" in one call)
+	///
+	/// _Info: `timeout` still limits the entire wait; if neither a handle nor a timer fired within
+	/// `timeout`, both lists are empty._
+	pub fn select_with_timers(self, timeout: Duration)
+		-> Result<SelectEvents<'a, T>, TimeoutIoError>
+	{
+		// Create raw FDs and events for the handles and the FD-backed timers and cap the timeout at
+		// the deadlines of the other timers (rounded up so that we don't wake up too early)
+		let mut fds: Vec<u64> = self.handles.iter().map(|h| h.raw_fd()).collect();
+		let (mut events, mut timeout) = (self.events, timeout);
+		for timer in self.timers.iter() {
+			match (timer.raw_fd(), timer.remaining()) {
+				(Some(fd), _) => {
+					fds.push(fd);
+					events.push(EventMask::new_r());
+				},
+				(None, Some(remaining)) => {
+					timeout = min(timeout, remaining + Duration::from_micros(1));
+				},
+				(None, None) => ()
+			}
+		}
+		select_raw(fds, &mut events, timeout, true)?;
+		
+		// Yield the handles where an event occurred and the expired timers
+		let timer_events = events.split_off(self.handles.len());
+		let handles = self.handles.into_iter().zip(events)
+			.filter(|(_, e)| !e.is_empty())
+			.collect();
+		let mut timer_events = timer_events.into_iter();
+		let timers = self.timers.into_iter()
+			.filter(|timer| match timer.raw_fd() {
+				Some(_) => timer_events.next().is_some_and(|event| !event.is_empty()),
+				None => timer.is_expired()
+			})
+			.collect();
+		Ok(SelectEvents{ handles, timers })
+	}
+	
+	/// Like `select`, but edge-triggered: each event of a registration is reported only _once_
+	/// until the handle is re-armed using `rearm` (e.g. after you've drained it). Unlike `select`,
+	/// this does not consume the set, so that it can be reused in a loop.
+	///
+	/// _Info: `select` is level-triggered, i.e. a handle is reported as long as it is ready (e.g.
+	/// as long as there is unread data). With `select_edge`, events that were already reported are
+	/// excluded from the next waits; so a handle you haven't drained yet does not cause redundant
+	/// wakeups. Events that were not reported yet remain armed._
+	///
+	/// __Warning: Since readiness is tracked per registration, an event is never reported again if
+	/// you forget to call `rearm` for the handle.__
+	pub fn select_edge(&mut self, timeout: Duration)
+		-> Result<Vec<(&'a T, EventMask)>, TimeoutIoError>
+	{
+		// Wait only for the events that were not reported yet
+		let fds = self.handles.iter().map(|h| h.raw_fd());
+		let mut armed: Vec<EventMask> = self.events.iter().zip(self.fired.iter())
+			.map(|(event, fired)| EventMask{ raw: event.raw & !fired.raw })
 			.collect();
+		select_raw(fds, &mut armed, timeout, false)?;
+		
+		// Record and yield the handles where an event occurred
+		let mut yielded = Vec::new();
+		for ((handle, event), fired) in self.handles.iter().zip(armed).zip(self.fired.iter_mut()) {
+			if event.is_empty() { continue }
+			fired.raw |= event.raw;
+			yielded.push((*handle, event));
+		}
 		Ok(yielded)
 	}
+	/// Like `select`, but does not consume the set and invokes `on_ready` with each
+	/// `(handle, event_that_occurred)`-pair instead of collecting them; returns the amount of
+	/// handles where an event occurred (i.e. the amount of `on_ready`-calls)
+	///
+	/// _Info: This is level-triggered like `select`. The internal buffers are kept in the set and
+	/// reused; so once the set has been dispatched, subsequent `dispatch`-calls don't allocate and
+	/// you can call it in a loop as a minimal reactor (with the pure-Rust fallback, this only holds
+	/// for up to 8 distinct fds)._
+	///
+	/// _Note: `on_ready` is called synchronously (in push-order) during `dispatch`, after the wait
+	/// has completed; so a slow callback delays the next `dispatch`._
+	pub fn dispatch(&mut self, timeout: Duration, mut on_ready: impl FnMut(&T, EventMask))
+		-> Result<usize, TimeoutIoError>
+	{
+		// Copy the events and call libselect with the reused buffers
+		let Self{ handles, events, raw, occurred, .. } = self;
+		occurred.clear();
+		occurred.extend_from_slice(events);
+		raw.select(handles.iter().map(|h| h.raw_fd()), occurred, timeout, false)?;
+		
+		// Invoke the callback for the handles where an event occurred
+		let mut fired = 0;
+		for (handle, event) in handles.iter().zip(occurred.iter().copied()) {
+			if event.is_empty() { continue }
+			on_ready(handle, event);
+			fired += 1;
+		}
+		Ok(fired)
+	}
+	
+	/// Re-arms all registrations of `handle` so that `select_edge` reports their events again
+	pub fn rearm(&mut self, handle: &T) {
+		let fd = handle.raw_fd();
+		for (registered, fired) in self.handles.iter().zip(self.fired.iter_mut()) {
+			if registered.raw_fd() == fd { *fired = EventMask::default() }
+		}
+	}
+	
+	/// Like `select`, but groups the handles where an event occurred by the event type
+	///
+	/// _Note: A handle appears in every group of the events that occurred on it (e.g. a handle that
+	/// is ready for reading and writing appears in `readable` and `writable`); within each group, the
+	/// handles are in push-order._
+	pub fn select_grouped(self, timeout: Duration) -> Result<SelectGroups<'a, T>, TimeoutIoError> {
+		let mut groups = SelectGroups{
+			readable: Vec::new(), writable: Vec::new(), errored: Vec::new()
+		};
+		for (handle, event) in self.select(timeout)? {
+			let (r, w, e) = event.rwe();
+			if r { groups.readable.push(handle) }
+			if w { groups.writable.push(handle) }
+			if e { groups.errored.push(handle) }
+		}
+		Ok(groups)
+	}
 }
-/// Creates a new `SelectSet` for
-macro_rules! select_set {
-	($($handle:expr => $event:expr),*) => ({
-		let mut select_set = $crate::SelectSet::new();
-		$(select_set.push($handle, $event);)*
-		select_set
-	});
+
+
+/// Waits until `a_event` occurs on `a` and/or `b_event` occurs on `b` or `timeout` is exceeded and
+/// returns the events that occurred on `a` and `b`
+///
+/// This is a cheap specialization of `SelectSet` for the common "socket + wakeup-pipe"-pattern
+/// (where `b` is used to interrupt a wait on `a`) that also works with two different handle types.
+#[cfg_attr(libselect_fallback, allow(unused_unsafe))]
+pub fn wait_either(a: &impl RawFd, a_event: EventMask, b: &impl RawFd, b_event: EventMask,
+	timeout: Duration) -> Result<(Option<EventMask>, Option<EventMask>), TimeoutIoError>
+{
+	// Wait for the events (directly from the stack; libselect handles identical FDs itself)
+	let start = Instant::now();
+	let fds = [a.raw_fd(), b.raw_fd(), unsafe{ libselect::INVALID_FD }];
+	let mut events = [a_event, b_event];
+	select_terminated(&fds, &mut events, timeout, false)?;
+	
+	// Filter the events that occurred
+	let occurred = |event: EventMask| match event.is_empty() {
+		true => None,
+		false => Some(event)
+	};
+	match (occurred(events[0]), occurred(events[1])) {
+		(None, None) => Err(TimeoutIoError::timed_out(start, timeout)),
+		events => Ok(events)
+	}
 }
 
 
 /// This trait defines an API to wait for an event
+///
+/// _Note: This trait is implemented for all types that implement `RawFd`._
+#[diagnostic::on_unimplemented(
+	message = "`{Self}` cannot wait for IO-events",
+	label = "`{Self}` does not implement `WaitForEvent`",
+	note = "`WaitForEvent` is implemented for all types that expose a raw fd/socket via `RawFd`"
+)]
 pub trait WaitForEvent {
 	/// Waits until `event` occurs or `timeout` is exceeded and returns the event that occurred
+	///
+	/// _Info: A zero `timeout` polls the current state without waiting; other timeouts are rounded
+	/// up to whole milliseconds._
 	fn wait_for_event(&self, event: EventMask, timeout: Duration)
 		-> Result<EventMask, TimeoutIoError>;
 	
+	/// Like `wait_for_event`, but preserves the sub-millisecond precision of `timeout` (e.g. for
+	/// a 250µs-wait)
+	///
+	/// _Info: The precision depends on the platform: the C-shim uses `select` with a microsecond
+	/// `timeval` on unix and windows (however windows' timer resolution is usually much coarser);
+	/// the pure-Rust fallback uses `ppoll` with a `timespec` on Linux and rounds up to whole
+	/// milliseconds on other unix-targets. Sub-microsecond timeouts are rounded up to 1µs._
+	fn wait_for_event_precise(&self, event: EventMask, timeout: Duration)
+		-> Result<EventMask, TimeoutIoError>;
+	
+	/// Waits until any event from `events` occurs or `timeout` is exceeded and returns the events
+	/// that occurred for each mask in `events` (in the same order) – all in a single call
+	///
+	/// This is useful if you want to know about e.g. read- and write-readiness separately; note that
+	/// the mask returned by `wait_for_event` already contains all events that occurred and can be
+	/// decomposed using `EventMask::rwe`.
+	fn wait_for_event_multi(&self, events: &[EventMask], timeout: Duration)
+		-> Result<Vec<EventMask>, TimeoutIoError>;
+	
+	/// Like `wait_for_event`, but also returns the remaining time of `timeout` after the wait (e.g.
+	/// to pass the remaining budget to the next operation of a sequence)
+	///
+	/// _Info: If the event occurred early, the remaining time is `timeout` minus the time waited; if
+	/// the full `timeout` was consumed, the remaining time is zero. If the event did not occur at
+	/// all, `TimedOut` is returned as usual._
+	fn wait_for_event_timed(&self, event: EventMask, timeout: Duration)
+		-> Result<(EventMask, Duration), TimeoutIoError>
+	{
+		let deadline = deadline_after(timeout);
+		let event = self.wait_for_event(event, timeout)?;
+		Ok((event, deadline.remaining()))
+	}
+	
+	/// Waits until either data arrives (returns `Ok(false)`) or the peer closes the connection
+	/// (returns `Ok(true)`) or `timeout` is exceeded (returns `TimedOut`) – without consuming any
+	/// data (e.g. to reap dead idle connections of a long-lived server promptly)
+	///
+	/// _Info: This waits for `EventMask::new_rh()`; if the handle becomes readable, a peek
+	/// distinguishes pending data from a closed connection (see `EventMask::new_rh` for the
+	/// platform details)._
+	///
+	/// _Note: If data is pending, `Ok(false)` is returned even if the peer has closed the
+	/// connection after sending it; read the data and call this function again. A failed
+	/// connection (e.g. a reset) is not reported as hangup; the next read returns the error._
+	fn wait_until_closed(&self, timeout: Duration) -> Result<bool, TimeoutIoError> {
+		let event = self.wait_for_event(EventMask::new_rh(), timeout)?;
+		Ok(event.contains_hangup())
+	}
+	
+	/// Waits until `event` occurs or `waker` is signaled from another thread or `timeout` is
+	/// exceeded
+	///
+	/// _Info: If `waker` is signaled, `WokenOr::Woken` is returned even if `event` occurred, too.
+	/// The waker is not reset by this call (see `Waker`)._
+	fn wait_for_event_or_wake(&self, event: EventMask, waker: &Waker, timeout: Duration)
+		-> Result<WokenOr<EventMask>, TimeoutIoError>;
+	
 	/// Makes `self` blocking or non-blocking
+	///
+	/// _Info: On unix, this works for all file descriptors (sockets, pipes, ttys etc.) and is
+	/// implemented in Rust via `fcntl` (i.e. it does not use `libselect` – regardless of the
+	/// `pure-rust`-feature). On windows, the blocking mode is set by `libselect` via
+	/// `ioctlsocket(FIONBIO)` which only supports sockets; for other handles (like pipes or the
+	/// console) `InvalidInput` is returned._
 	fn set_blocking_mode(&self, make_blocking: bool) -> Result<(), TimeoutIoError>;
+	
+	/// Makes `self` blocking and returns it (e.g. to hand it over to blocking code)
+	fn into_blocking(self) -> Result<Self, TimeoutIoError> where Self: Sized {
+		self.set_blocking_mode(true)?;
+		Ok(self)
+	}
+	/// Makes `self` non-blocking and returns it (e.g. to use it with this crate)
+	fn into_nonblocking(self) -> Result<Self, TimeoutIoError> where Self: Sized {
+		self.set_blocking_mode(false)?;
+		Ok(self)
+	}
 }
 impl<T: RawFd> WaitForEvent for T {
 	fn wait_for_event(&self, event: EventMask, timeout: Duration)
 		-> Result<EventMask, TimeoutIoError>
 	{
-		// Wait for `r | e`
-		let events: Vec<(&Self, EventMask)> = select_set!(self => event).select(timeout)?;
-		match events.first() {
-			Some((_, event)) => Ok(*event),
-			None => Err(TimeoutIoError::TimedOut)
+		// Wait for the event (without a `SelectSet` to avoid the allocations on this hot path)
+		let (start, fd) = (Instant::now(), self.raw_fd());
+		observer::observe(fd, event, || match select_single(fd, event, timeout, false)? {
+			event if event.is_empty() => Err(TimeoutIoError::timed_out(start, timeout)),
+			event => Ok(event)
+		})
+	}
+	
+	fn wait_for_event_precise(&self, event: EventMask, timeout: Duration)
+		-> Result<EventMask, TimeoutIoError>
+	{
+		// Wait for the event
+		let (start, fd) = (Instant::now(), self.raw_fd());
+		observer::observe(fd, event, || match select_single(fd, event, timeout, true)? {
+			event if event.is_empty() => Err(TimeoutIoError::timed_out(start, timeout)),
+			event => Ok(event)
+		})
+	}
+	
+	fn wait_for_event_multi(&self, events: &[EventMask], timeout: Duration)
+		-> Result<Vec<EventMask>, TimeoutIoError>
+	{
+		// Wait for the union of all events and split the occurred events by mask
+		let (start, fd) = (Instant::now(), self.raw_fd());
+		let merged = EventMask{ raw: events.iter().fold(0, |merged, event| merged | event.raw) };
+		observer::observe(fd, merged, || {
+			let occurred = select_single(fd, merged, timeout, false)?;
+			match occurred.is_empty() {
+				true => Err(TimeoutIoError::timed_out(start, timeout)),
+				false => {
+					let split = |event: &EventMask| EventMask{ raw: event.raw & occurred.raw };
+					Ok(events.iter().map(split).collect())
+				}
+			}
+		})
+	}
+	
+	fn wait_for_event_or_wake(&self, event: EventMask, waker: &Waker, timeout: Duration)
+		-> Result<WokenOr<EventMask>, TimeoutIoError>
+	{
+		// Wait for the event or the waker (a signaled waker takes precedence; `wait_either` returns
+		// `TimedOut` if neither occurred)
+		match wait_either(self, event, waker, EventMask::new_r(), timeout)? {
+			(Some(event), None) => Ok(WokenOr::Event(event)),
+			_ => Ok(WokenOr::Woken)
 		}
 	}
 	
+	#[cfg(unix)]
+	fn set_blocking_mode(&self, make_blocking: bool) -> Result<(), TimeoutIoError> {
+		// Get the current flags
+		let fd = self.raw_fd() as libc::c_int;
+		let flags = unsafe{ libc::fcntl(fd, libc::F_GETFL, 0) };
+		if flags == -1 { return Err(io::Error::last_os_error().into()) }
+		
+		// Set the new flags
+		let flags = match make_blocking {
+			true => flags & !libc::O_NONBLOCK,
+			false => flags | libc::O_NONBLOCK
+		};
+		match unsafe{ libc::fcntl(fd, libc::F_SETFL, flags) } {
+			-1 => Err(io::Error::last_os_error().into()),
+			_ => Ok(())
+		}
+	}
+	#[cfg(windows)]
 	fn set_blocking_mode(&self, make_blocking: bool) -> Result<(), TimeoutIoError> {
 		// Set the blocking mode
 		let result = unsafe{ libselect::set_blocking_mode(
@@ -139,9 +761,11 @@ impl<T: RawFd> WaitForEvent for T {
 			if make_blocking { 1 } else { 0 }
 		) };
 		
-		// Check the result
+		// Check the result (`FIONBIO` fails with `WSAENOTSOCK` for non-socket handles or with
+		// `WSANOTINITIALISED` if there is no socket in this process at all)
 		match result {
 			0 => Ok(()),
+			WSAENOTSOCK | WSANOTINITIALISED => Err(TimeoutIoError::InvalidInput),
 			e => Err(io::Error::from_raw_os_error(e).into())
 		}
 	}
@@ -0,0 +1,479 @@
+//! A pure-Rust implementation of `libselect` for unix-targets based on `poll` that is used if the
+//! `pure-rust`-feature is enabled or if the C-shim could not be built
+//!
+//! _Info: The functions mirror the C-API (including the raw pointers and the errno-return-values)
+//! so that they are drop-in replacements for the `extern "C"`-declarations._
+use std::{
+	io, mem, ptr, slice, cmp::min,
+	os::raw::c_int
+};
+
+
+pub static EVENT_READ:  u8 = 1 << 1;
+pub static EVENT_WRITE: u8 = 1 << 2;
+pub static EVENT_ERROR: u8 = 1 << 3;
+pub static EVENT_HANGUP: u8 = 1 << 4;
+pub static INVALID_FD:  u64 = !0;
+#[cfg(target_os = "linux")]
+pub static BATCH_SIZE: usize = 64;
+
+
+/// The poll-events to request for a hangup-event (`POLLHUP` is always reported)
+#[cfg(target_os = "linux")]
+const HANGUP_REQUEST: libc::c_short = libc::POLLRDHUP;
+/// The poll-events to request for a hangup-event (without `POLLRDHUP`, we detect a half-close by
+/// peeking on readable FDs)
+#[cfg(not(target_os = "linux"))]
+const HANGUP_REQUEST: libc::c_short = libc::POLLIN;
+/// The maximum amount of poll-FDs that are passed to `poll` without allocating
+const STACK_POLL_FDS: usize = 8;
+
+
+/// Checks if `revents` signal a hangup on `fd`
+unsafe fn is_hangup(fd: u64, revents: libc::c_short) -> bool {
+	#[cfg(target_os = "linux")]
+	if revents & libc::POLLRDHUP != 0 { return true }
+	if revents & libc::POLLHUP != 0 { return true }
+	
+	// Peek if the FD is readable but there is no data
+	let mut byte = 0u8;
+	let flags = libc::MSG_PEEK | libc::MSG_DONTWAIT;
+	revents & libc::POLLIN != 0
+		&& libc::recv(fd as c_int, &mut byte as *mut _ as *mut libc::c_void, 1, flags) == 0
+}
+
+/// Returns the current `errno`
+fn errno() -> c_int {
+	io::Error::last_os_error().raw_os_error().unwrap_or(0)
+}
+
+/// Creates a socket address from a raw `family`/`ip`/`port`-triple and returns it's length (or 0
+/// if `family` is invalid)
+unsafe fn address_from_raw(family: u8, ip: *const u8, port: u16,
+	address: &mut libc::sockaddr_storage) -> libc::socklen_t
+{
+	*address = mem::zeroed();
+	match family {
+		4 => {
+			let address_v4 = &mut *(address as *mut _ as *mut libc::sockaddr_in);
+			address_v4.sin_family = libc::AF_INET as _;
+			address_v4.sin_port = port.to_be();
+			ptr::copy_nonoverlapping(ip, &mut address_v4.sin_addr as *mut _ as *mut u8, 4);
+			mem::size_of::<libc::sockaddr_in>() as _
+		},
+		6 => {
+			let address_v6 = &mut *(address as *mut _ as *mut libc::sockaddr_in6);
+			address_v6.sin6_family = libc::AF_INET6 as _;
+			address_v6.sin6_port = port.to_be();
+			ptr::copy_nonoverlapping(ip, &mut address_v6.sin6_addr as *mut _ as *mut u8, 16);
+			mem::size_of::<libc::sockaddr_in6>() as _
+		},
+		_ => 0
+	}
+}
+/// Converts a socket address into a raw `family`/`ip`/`port`-triple
+#[cfg(target_os = "linux")]
+unsafe fn address_to_raw(address: &libc::sockaddr_storage, family: *mut u8, ip: *mut u8,
+	port: *mut u16)
+{
+	match address.ss_family as c_int {
+		libc::AF_INET => {
+			let address_v4 = &*(address as *const _ as *const libc::sockaddr_in);
+			*family = 4;
+			*port = u16::from_be(address_v4.sin_port);
+			ptr::copy_nonoverlapping(&address_v4.sin_addr as *const _ as *const u8, ip, 4);
+		},
+		libc::AF_INET6 => {
+			let address_v6 = &*(address as *const _ as *const libc::sockaddr_in6);
+			*family = 6;
+			*port = u16::from_be(address_v6.sin6_port);
+			ptr::copy_nonoverlapping(&address_v6.sin6_addr as *const _ as *const u8, ip, 16);
+		},
+		_ => *family = 0
+	}
+}
+
+
+pub unsafe fn wait_for_event(timeout_ms: u64, fds: *const u64, events: *mut u8) -> c_int {
+	let timeout = min(timeout_ms, c_int::MAX as u64) as c_int;
+	poll_events(fds, events, |poll_fds| libc::poll(poll_fds.as_mut_ptr(), poll_fds.len() as _, timeout))
+}
+
+#[cfg(target_os = "linux")]
+pub unsafe fn wait_for_event_us(timeout_us: u64, fds: *const u64, events: *mut u8) -> c_int {
+	let timeout = libc::timespec {
+		tv_sec: min(timeout_us / 1_000_000, libc::time_t::MAX as u64) as _,
+		tv_nsec: ((timeout_us % 1_000_000) * 1000) as _
+	};
+	poll_events(fds, events, |poll_fds| {
+		libc::ppoll(poll_fds.as_mut_ptr(), poll_fds.len() as _, &timeout, ptr::null())
+	})
+}
+#[cfg(not(target_os = "linux"))]
+pub unsafe fn wait_for_event_us(timeout_us: u64, fds: *const u64, events: *mut u8) -> c_int {
+	// `ppoll` is not portable, so we round up to whole milliseconds
+	wait_for_event(timeout_us.saturating_add(999) / 1000, fds, events)
+}
+
+/// Translates `fds`/`events` into poll-FDs, calls `poll` with them and translates the results back
+unsafe fn poll_events(fds: *const u64, events: *mut u8,
+	poll: impl FnOnce(&mut [libc::pollfd]) -> c_int) -> c_int
+{
+	// Collect FDs and events
+	let mut count = 0;
+	while *fds.add(count) != INVALID_FD { count += 1 }
+	let fds = slice::from_raw_parts(fds, count);
+	let events = slice::from_raw_parts_mut(events, count);
+	
+	// Prepare the poll-FDs (a few FDs are the common case, so we avoid the allocation there)
+	let poll_fd = |(fd, event): (&u64, &u8)| {
+		let mut poll_events = 0;
+		if event & EVENT_READ   != 0 { poll_events |= libc::POLLIN }
+		if event & EVENT_WRITE  != 0 { poll_events |= libc::POLLOUT }
+		if event & EVENT_ERROR  != 0 { poll_events |= libc::POLLPRI }
+		if event & EVENT_HANGUP != 0 { poll_events |= HANGUP_REQUEST }
+		libc::pollfd{ fd: *fd as c_int, events: poll_events, revents: 0 }
+	};
+	let (mut few, mut multiple);
+	let poll_fds: &mut [libc::pollfd] = match count {
+		0..=STACK_POLL_FDS => {
+			few = [libc::pollfd{ fd: -1, events: 0, revents: 0 }; STACK_POLL_FDS];
+			few.iter_mut().zip(fds.iter().zip(events.iter())).for_each(|(slot, pair)| {
+				*slot = poll_fd(pair)
+			});
+			&mut few[..count]
+		},
+		_ => {
+			multiple = fds.iter().zip(events.iter()).map(poll_fd).collect::<Vec<_>>();
+			&mut multiple
+		}
+	};
+	
+	// Call poll
+	if poll(poll_fds) == -1 { return errno() }
+	
+	// Check the poll-FDs (like `select`, we report hangups/errors as read- and write-events)
+	let (readable, writeable) = (
+		libc::POLLIN | libc::POLLHUP | libc::POLLERR,
+		libc::POLLOUT | libc::POLLHUP | libc::POLLERR
+	);
+	for ((event, poll_fd), fd) in events.iter_mut().zip(poll_fds.iter()).zip(fds.iter()) {
+		if poll_fd.revents & libc::POLLNVAL != 0 { return libc::EBADF }
+		
+		let (requested, revents) = (*event, poll_fd.revents);
+		*event = 0;
+		if requested & EVENT_READ   != 0 && revents & readable      != 0 { *event |= EVENT_READ }
+		if requested & EVENT_WRITE  != 0 && revents & writeable     != 0 { *event |= EVENT_WRITE }
+		if requested & EVENT_ERROR  != 0 && revents & libc::POLLPRI != 0 { *event |= EVENT_ERROR }
+		if requested & EVENT_HANGUP != 0 && is_hangup(*fd, revents)      { *event |= EVENT_HANGUP }
+	}
+	0
+}
+
+#[cfg(feature = "checked")]
+pub unsafe fn check_fd(fd: u64) -> c_int {
+	match libc::fcntl(fd as c_int, libc::F_GETFD) {
+		-1 => errno(),
+		_ => 0
+	}
+}
+
+pub unsafe fn set_linger(fd: u64, enabled: u8, seconds: u32) -> c_int {
+	let linger = libc::linger{ l_onoff: enabled as c_int, l_linger: seconds as c_int };
+	let result = libc::setsockopt(
+		fd as c_int, libc::SOL_SOCKET, libc::SO_LINGER,
+		&linger as *const _ as *const libc::c_void, mem::size_of::<libc::linger>() as _
+	);
+	match result {
+		-1 => errno(),
+		_ => 0
+	}
+}
+
+pub unsafe fn set_keepalive(fd: u64, enabled: u8) -> c_int {
+	let enable: c_int = if enabled != 0 { 1 } else { 0 };
+	let result = libc::setsockopt(
+		fd as c_int, libc::SOL_SOCKET, libc::SO_KEEPALIVE,
+		&enable as *const _ as *const libc::c_void, mem::size_of::<c_int>() as _
+	);
+	match result {
+		-1 => errno(),
+		_ => 0
+	}
+}
+
+pub unsafe fn set_tos(fd: u64, tos: u8) -> c_int {
+	set_int_option(fd, libc::IPPROTO_IP, libc::IP_TOS, tos as c_int)
+}
+/// Sets `IPV6_TCLASS`
+#[cfg(any(
+	target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios",
+	target_os = "freebsd", target_os = "dragonfly", target_os = "openbsd", target_os = "netbsd"
+))]
+pub unsafe fn set_traffic_class(fd: u64, traffic_class: u8) -> c_int {
+	set_int_option(fd, libc::IPPROTO_IPV6, libc::IPV6_TCLASS, traffic_class as c_int)
+}
+/// The traffic class is not supported (returns `ENOPROTOOPT`)
+#[cfg(not(any(
+	target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios",
+	target_os = "freebsd", target_os = "dragonfly", target_os = "openbsd", target_os = "netbsd"
+)))]
+pub unsafe fn set_traffic_class(_fd: u64, _traffic_class: u8) -> c_int {
+	libc::ENOPROTOOPT
+}
+/// Sets the integer-option `option` on `level`
+unsafe fn set_int_option(fd: u64, level: c_int, option: c_int, value: c_int) -> c_int {
+	let result = libc::setsockopt(
+		fd as c_int, level, option, &value as *const _ as *const libc::c_void,
+		mem::size_of::<c_int>() as _
+	);
+	match result {
+		-1 => errno(),
+		_ => 0
+	}
+}
+
+/// Sets `TCP_CORK` on Linux
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub unsafe fn set_cork(fd: u64, enabled: u8) -> c_int {
+	set_tcp_option(fd, libc::TCP_CORK, enabled)
+}
+/// Sets `TCP_NOPUSH` on BSDs/macOS
+#[cfg(any(
+	target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "dragonfly",
+	target_os = "openbsd", target_os = "netbsd"
+))]
+pub unsafe fn set_cork(fd: u64, enabled: u8) -> c_int {
+	set_tcp_option(fd, libc::TCP_NOPUSH, enabled)
+}
+/// Corking is not supported (this is a no-op)
+#[cfg(not(any(
+	target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios",
+	target_os = "freebsd", target_os = "dragonfly", target_os = "openbsd", target_os = "netbsd"
+)))]
+pub unsafe fn set_cork(_fd: u64, _enabled: u8) -> c_int {
+	0
+}
+/// Sets the boolean `IPPROTO_TCP`-option `option`
+#[cfg(any(
+	target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios",
+	target_os = "freebsd", target_os = "dragonfly", target_os = "openbsd", target_os = "netbsd"
+))]
+unsafe fn set_tcp_option(fd: u64, option: c_int, enabled: u8) -> c_int {
+	let enable: c_int = if enabled != 0 { 1 } else { 0 };
+	let result = libc::setsockopt(
+		fd as c_int, libc::IPPROTO_TCP, option,
+		&enable as *const _ as *const libc::c_void, mem::size_of::<c_int>() as _
+	);
+	match result {
+		-1 => errno(),
+		_ => 0
+	}
+}
+
+/// Sends a file via `sendfile` on Linux
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub unsafe fn send_file(fd: u64, file_fd: u64, offset: *mut u64, count: u64, sent: *mut u64)
+	-> c_int
+{
+	*sent = 0;
+	let mut position = *offset as libc::off_t;
+	match libc::sendfile(fd as c_int, file_fd as c_int, &mut position, count as usize) {
+		-1 => return errno(),
+		result => *sent = result as u64
+	}
+	*offset += *sent;
+	0
+}
+/// Sends a file via `sendfile` on macOS
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub unsafe fn send_file(fd: u64, file_fd: u64, offset: *mut u64, count: u64, sent: *mut u64)
+	-> c_int
+{
+	let mut length = count as libc::off_t;
+	let result = libc::sendfile(
+		file_fd as c_int, fd as c_int, *offset as libc::off_t, &mut length, ptr::null_mut(), 0
+	);
+	finish_send_file(result, length, offset, sent)
+}
+/// Sends a file via `sendfile` on FreeBSD/DragonFly
+#[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+pub unsafe fn send_file(fd: u64, file_fd: u64, offset: *mut u64, count: u64, sent: *mut u64)
+	-> c_int
+{
+	let mut length: libc::off_t = 0;
+	let result = libc::sendfile(
+		file_fd as c_int, fd as c_int, *offset as libc::off_t, count as usize, ptr::null_mut(),
+		&mut length, 0
+	);
+	finish_send_file(result, length, offset, sent)
+}
+/// `sendfile` is not supported (returns `ENOSYS`)
+#[cfg(not(any(
+	target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios",
+	target_os = "freebsd", target_os = "dragonfly"
+)))]
+pub unsafe fn send_file(_fd: u64, _file_fd: u64, _offset: *mut u64, _count: u64, sent: *mut u64)
+	-> c_int
+{
+	*sent = 0;
+	libc::ENOSYS
+}
+/// Evaluates the result of a BSD-`sendfile` (which may report partially sent bytes on errors)
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "dragonfly"))]
+unsafe fn finish_send_file(result: c_int, length: libc::off_t, offset: *mut u64, sent: *mut u64)
+	-> c_int
+{
+	let error = errno();
+	*sent = length as u64;
+	if result == -1 && (length == 0 || (error != libc::EAGAIN && error != libc::EINTR)) {
+		return error
+	}
+	*offset += *sent;
+	0
+}
+
+pub unsafe fn bind_reusable(family: u8, ip: *const u8, port: u16, reuse_port: u8, fd: *mut u64)
+	-> c_int
+{
+	// Create the address
+	let mut address: libc::sockaddr_storage = mem::zeroed();
+	let address_len = address_from_raw(family, ip, port, &mut address);
+	if address_len == 0 { return libc::EINVAL }
+	
+	// Create the socket
+	let descriptor = libc::socket(address.ss_family as c_int, libc::SOCK_STREAM, 0);
+	if descriptor == -1 { return errno() }
+	
+	// Set the socket options, bind and listen
+	let enable: c_int = 1;
+	let set_option = |option| libc::setsockopt(
+		descriptor, libc::SOL_SOCKET, option,
+		&enable as *const _ as *const libc::c_void, mem::size_of::<c_int>() as _
+	);
+	let failed = libc::fcntl(descriptor, libc::F_SETFD, libc::FD_CLOEXEC) == -1
+		|| set_option(libc::SO_REUSEADDR) == -1
+		|| (reuse_port != 0 && set_option(libc::SO_REUSEPORT) == -1)
+		|| libc::bind(descriptor, &address as *const _ as *const libc::sockaddr, address_len) == -1
+		|| libc::listen(descriptor, 128) == -1;
+	if failed {
+		let error = errno();
+		libc::close(descriptor);
+		return error
+	}
+	
+	*fd = descriptor as u64;
+	0
+}
+
+#[cfg(target_os = "linux")]
+#[allow(clippy::too_many_arguments)]
+pub unsafe fn receive_batch(fd: u64, bufs: *const *mut u8, lens: *const usize, count: usize,
+	received_lens: *mut usize, families: *mut u8, ips: *mut u8, ports: *mut u16,
+	received: *mut usize) -> c_int
+{
+	// Prepare the message headers
+	let count = min(count, BATCH_SIZE);
+	let mut addresses: Vec<libc::sockaddr_storage> = vec![mem::zeroed(); count];
+	let mut iovecs: Vec<libc::iovec> = (0..count)
+		.map(|i| libc::iovec{ iov_base: *bufs.add(i) as *mut _, iov_len: *lens.add(i) })
+		.collect();
+	let mut headers: Vec<libc::mmsghdr> = (0..count).map(|i| {
+		let mut header: libc::mmsghdr = mem::zeroed();
+		header.msg_hdr.msg_iov = &mut iovecs[i];
+		header.msg_hdr.msg_iovlen = 1;
+		header.msg_hdr.msg_name = &mut addresses[i] as *mut _ as *mut libc::c_void;
+		header.msg_hdr.msg_namelen = mem::size_of::<libc::sockaddr_storage>() as _;
+		header
+	}).collect();
+	
+	// Receive the messages
+	let result = libc::recvmmsg(
+		fd as c_int, headers.as_mut_ptr(), count as _,
+		libc::MSG_DONTWAIT, ptr::null_mut()
+	);
+	if result == -1 { return errno() }
+	
+	// Collect the results
+	for i in 0..result as usize {
+		*received_lens.add(i) = headers[i].msg_len as usize;
+		address_to_raw(&addresses[i], families.add(i), ips.add(i * 16), ports.add(i));
+	}
+	*received = result as usize;
+	0
+}
+
+#[cfg(target_os = "linux")]
+#[allow(clippy::too_many_arguments)]
+pub unsafe fn send_batch(fd: u64, datas: *const *const u8, lens: *const usize, count: usize,
+	families: *const u8, ips: *const u8, ports: *const u16, sent: *mut usize) -> c_int
+{
+	// Prepare the message headers
+	let count = min(count, BATCH_SIZE);
+	let mut addresses: Vec<libc::sockaddr_storage> = vec![mem::zeroed(); count];
+	let mut iovecs: Vec<libc::iovec> = (0..count)
+		.map(|i| libc::iovec{ iov_base: *datas.add(i) as *mut _, iov_len: *lens.add(i) })
+		.collect();
+	let mut headers: Vec<libc::mmsghdr> = Vec::with_capacity(count);
+	for i in 0..count {
+		let address_len = address_from_raw(
+			*families.add(i), ips.add(i * 16), *ports.add(i),
+			&mut addresses[i]
+		);
+		if address_len == 0 { return libc::EINVAL }
+		
+		let mut header: libc::mmsghdr = mem::zeroed();
+		header.msg_hdr.msg_iov = &mut iovecs[i];
+		header.msg_hdr.msg_iovlen = 1;
+		header.msg_hdr.msg_name = &mut addresses[i] as *mut _ as *mut libc::c_void;
+		header.msg_hdr.msg_namelen = address_len;
+		headers.push(header);
+	}
+	
+	// Send the messages
+	let result = libc::sendmmsg(fd as c_int, headers.as_mut_ptr(), count as _, libc::MSG_DONTWAIT);
+	if result == -1 { return errno() }
+	
+	*sent = result as usize;
+	0
+}
+
+#[cfg(target_os = "linux")]
+pub unsafe fn datagram_size(fd: u64, size: *mut usize) -> c_int {
+	let mut byte = 0u8;
+	let flags = libc::MSG_PEEK | libc::MSG_TRUNC | libc::MSG_DONTWAIT;
+	match libc::recv(fd as c_int, &mut byte as *mut _ as *mut libc::c_void, 1, flags) {
+		-1 => errno(),
+		result => {
+			*size = result as usize;
+			0
+		}
+	}
+}
+
+#[cfg(target_os = "linux")]
+pub unsafe fn set_receive_timeout(fd: u64, timeout_us: u64, previous_us: *mut u64) -> c_int {
+	// Get the previous timeout
+	let mut previous: libc::timeval = mem::zeroed();
+	let mut previous_len = mem::size_of::<libc::timeval>() as libc::socklen_t;
+	let result = libc::getsockopt(
+		fd as c_int, libc::SOL_SOCKET, libc::SO_RCVTIMEO,
+		&mut previous as *mut _ as *mut libc::c_void, &mut previous_len
+	);
+	if result == -1 { return errno() }
+	*previous_us = (previous.tv_sec as u64) * 1_000_000 + previous.tv_usec as u64;
+	
+	// Set the new timeout (which is also honored by `accept` on Linux)
+	let timeout = libc::timeval {
+		tv_sec: min(timeout_us / 1_000_000, libc::time_t::MAX as u64) as _,
+		tv_usec: (timeout_us % 1_000_000) as _
+	};
+	let result = libc::setsockopt(
+		fd as c_int, libc::SOL_SOCKET, libc::SO_RCVTIMEO,
+		&timeout as *const _ as *const libc::c_void, mem::size_of::<libc::timeval>() as _
+	);
+	match result {
+		-1 => errno(),
+		_ => 0
+	}
+}
@@ -4,6 +4,7 @@
 //! It provides the following features:
 //!  - DNS-resolution (currently uses a background-thread)
 //!  - TCP-accept
+//!  - TCP-connect (to multiple addresses)
 //!  - TCP-read/read-until/write
 //!  - StdIOE-read/read-until/write
 //!  - UDP-receive/send
@@ -11,86 +12,456 @@
 //! All functions are defined as traits, so that you can easily wrap your own IO-channels without
 //! breaking compatibility.
 //!
-//! _Note: We currently do not provide a function for timeout-based `connect`-calls; use
-//! `std::net::TcpStream::connect_timeout` for TCP-connections or build sth. using `io::libselect`
-//! (and feel free to commit if you do so 😇)_
+//! _Info: Every handle that implements `AsRawFd`/`AsRawSocket` gets `Reader`/`Writer` for free –
+//! e.g. `AF_VSOCK`-streams for VM-to-host communication on Linux. However, `Acceptor` is only
+//! implemented for the standard library's listeners (and `vsock::VsockListener` if the
+//! `vsock`-feature is enabled)._
+//!
+//! # Pure-Rust fallback
+//! By default, the event-handling is implemented in a small C-shim (`libselect`) that is compiled
+//! by the build script. On unix-targets, there is also a pure-Rust fallback based on `poll` that
+//! is used
+//!  - if the `pure-rust`-feature is enabled (so that no C-compiler is required), or
+//!  - automatically if the C-shim could not be built (the build script emits a warning then).
+//!
+//! _Note: The fallback is unix-only; on windows, the `pure-rust`-feature is ignored and the C-shim
+//! is always built. Other targets (e.g. WASI) are not supported at all._
+//!
+//! _Info: On unix-targets, `WaitForEvent::set_blocking_mode` is always implemented in Rust (via
+//! `fcntl`) and does not use the C-shim._
+//!
+//! # FD-validation
+//! If the `checked`-feature is enabled, all FDs are validated before waiting on them and stale FDs
+//! (e.g. FDs inherited by a forked child but closed by the parent) result in `InvalidInput`. This
+//! costs one additional syscall per FD and wait, so it is disabled by default.
+//!
+//! # `bytes`-integration
+//! If the `bytes`-feature is enabled, `Reader::try_read_buf_bytes` reads directly into a
+//! `bytes::BytesMut`.
+//!
+//! # `vsock`-integration
+//! If the `vsock`-feature is enabled on Linux, `Acceptor` is also implemented for
+//! `vsock::VsockListener` (and `vsock::VsockStream` gets `Reader`/`Writer` like every other
+//! stream).
+//!
+//! # Observability
+//! Use `set_observer` to install a process-wide `IoObserver` that is notified after each wait for
+//! an IO-event on a single handle (e.g. to record latency histograms; see `IoObserver` for the
+//! reported waits); if no observer is installed, this costs a single atomic load per wait.
+//!
+//! _Note: For timeout-based `connect`-calls to a single address, use
+//! `std::net::TcpStream::connect_timeout`; to try multiple addresses in order, use `connect_any`._
+//!
+//! # Minimum supported Rust version
+//! This crate requires Rust 1.83 or newer (e.g. for `io::ErrorKind::HostUnreachable` and
+//! `IoSliceMut::advance_slices`).
 
 
 // Mods
+#[cfg(libselect_fallback)]
+mod fallback;
 mod event;
 mod reader;
 mod writer;
 mod acceptor;
 mod resolver;
+mod deadline;
+mod datagram;
+mod pump;
+mod pool;
+mod socket;
+mod backoff;
+mod peek;
+mod bufreader;
+mod config;
+mod connect;
+mod tracked;
+mod waker;
+mod deadline_reader;
+mod timer;
+mod reader_on;
+mod codec;
+mod dyn_stream;
+mod throttled;
+mod observer;
 
 
 // Create re-exports
+#[cfg(unix)]
+pub use crate::acceptor::bind_reuseport;
+#[cfg(unix)]
+pub use crate::connect::socket_pair;
+#[doc(hidden)]
+pub use crate::acceptor::StdAcceptor;
 pub use crate::{
-	acceptor::Acceptor, reader::Reader, writer::Writer,
-	event::{ RawFd, EventMask, SelectSet, WaitForEvent },
-	resolver::{ DnsResolvable, IpParseable }
+	acceptor::{
+		Acceptor, AcceptOptions, SharedAcceptor, bind_reuseaddr, accept_from_nb, serve_resilient
+	},
+	reader::{ Reader, read_exact_from_either }, reader_on::ReaderOn, writer::Writer,
+	dyn_stream::{ DynStream, ReadWrite },
+	codec::{ Decoder, LengthPrefixedDecoder },
+	event::{
+		RawFd, EventMask, SelectSet, SelectGroups, SelectEvents, WaitForEvent, wait_either, poll_many
+	},
+	timer::Timer,
+	resolver::{
+		DnsResolvable, DnsOptions, ResolvedAddrs, IpParseable, IpCidr, HostPort,
+		resolve_with_options
+	},
+	deadline::SharedDeadline, deadline_reader::DeadlineReader, throttled::ThrottledStream,
+	datagram::Datagram,
+	pump::{ pump, PumpTimeouts, PumpTimeout },
+	pool::{ ConnectionPool, PooledConnection },
+	socket::{ SocketOptions, IpOptions, Corked },
+	peek::{ Peeker, Endianness }, bufreader::TimeoutBufReader, config::TimeoutConfig,
+	connect::connect_any, tracked::{ TrackedStream, DropPolicy }, waker::{ Waker, WokenOr },
+	observer::{ IoObserver, Operation, set_observer, clear_observer }
 };
 use std::{
 	error::Error,
 	fmt::{ self, Display, Formatter },
 	time::{ Duration, Instant },
+	sync::{ Arc, mpsc::{ RecvTimeoutError, RecvError, TryRecvError } },
 	io::{
 		self,
 		ErrorKind::{
 			Interrupted, TimedOut, WouldBlock, UnexpectedEof,
-			BrokenPipe, ConnectionAborted, ConnectionReset
+			BrokenPipe, ConnectionAborted, ConnectionReset,
+			AddrInUse, AddrNotAvailable, PermissionDenied,
+			ConnectionRefused, HostUnreachable, NetworkUnreachable
 		}
 	}
 };
 
 
 /// An IO-error-wrapper
-#[derive(Debug, Clone, Eq, PartialEq)]
+///
+/// _Info: Two errors are equal if they are the same variant (and `Other` has the same
+/// description); the durations of `TimedOut` and the `source`s are ignored. To check for a timeout
+/// without knowing the durations, use `is_timeout`, `kind` or
+/// `matches!(error, TimeoutIoError::TimedOut{ .. })`._
+///
+/// _Info: The variants with a `source` retain the original `io::Error` if they were converted from
+/// one (see `as_io_error`); otherwise `source` is `None`._
+///
+/// _Note: New variants may be added in minor releases, so `match`es need a wildcard-arm._
+#[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum TimeoutIoError {
 	InterruptedSyscall,
-	TimedOut,
+	/// The operation did not complete within `limit` (its timeout) after it waited for `waited`
+	///
+	/// _Info: A `waited` close to `limit` means that the operation used its full budget; a tiny
+	/// `limit` means that the budget was almost exhausted before the operation even started (e.g.
+	/// because it was passed the remaining time of a previous operation). Both are zero if they are
+	/// unknown (e.g. if the error was converted from a `WouldBlock`-`io::Error`)._
+	TimedOut{ waited: Duration, limit: Duration },
+	/// The connection was closed gracefully (FIN) while data was still expected (e.g. during
+	/// `try_read_exact`)
 	UnexpectedEof,
-	ConnectionLost,
+	/// The connection was closed gracefully (FIN) before a one-shot `try_read` could read any data
+	/// (i.e. the end of the stream was reached)
+	GracefulEof,
+	/// The connection was aborted or reset (RST) by the peer
+	ConnectionLost{ source: Option<Arc<io::Error>> },
 	NotFound,
 	InvalidInput,
+	/// A size limit was reached
+	LimitExceeded,
+	/// The address is already in use (e.g. the port is taken by another listener)
+	AddrInUse{ source: Option<Arc<io::Error>> },
+	/// The address is not available (e.g. it does not belong to a local interface)
+	AddrNotAvailable{ source: Option<Arc<io::Error>> },
+	/// The operation is not permitted (e.g. binding to a privileged port)
+	PermissionDenied{ source: Option<Arc<io::Error>> },
+	/// The peer transferred data slower than the required minimum rate
+	TooSlow,
+	/// The connection was refused (i.e. nothing listens on the address)
+	Refused{ source: Option<Arc<io::Error>> },
+	/// The host or network is unreachable (e.g. there is no route to it)
+	Unreachable{ source: Option<Arc<io::Error>> },
 	Other{ desc: String }
 }
 impl TimeoutIoError {
+	/// Creates an `Other`-error with `desc` as description (e.g. for domain-specific failures)
+	pub fn other(desc: impl Into<String>) -> Self {
+		TimeoutIoError::Other{ desc: desc.into() }
+	}
+	/// Creates a `TimedOut`-error for an operation that was started at `start` with `limit` as
+	/// timeout
+	pub(crate) fn timed_out(start: Instant, limit: Duration) -> Self {
+		TimeoutIoError::TimedOut{ waited: start.elapsed(), limit }
+	}
+	
+	/// Whether the operation may be retried (i.e. the error is either transient or a timeout)
+	///
+	/// _Info: This is kept for backward compatibility; in retry loops, prefer `is_transient` and
+	/// `is_timeout` to decide _how_ to retry._
 	pub fn should_retry(&self) -> bool {
+		self.is_transient() || self.is_timeout()
+	}
+	/// Whether the error is transient and the operation should be retried _immediately_ (i.e. the
+	/// syscall was interrupted by a signal)
+	pub fn is_transient(&self) -> bool {
+		matches!(self, TimeoutIoError::InterruptedSyscall)
+	}
+	/// Whether the error is a timeout and the _caller_ should decide whether to give up or to retry
+	/// (e.g. after a delay or with a new deadline)
+	///
+	/// _Note: `WouldBlock` is mapped to `TimedOut`; so if you perform non-blocking operations
+	/// manually, a timeout may also mean "wait for an event and retry" (this is what all `try_*`
+	/// functions do internally until their deadline is exceeded)._
+	pub fn is_timeout(&self) -> bool {
+		matches!(self, TimeoutIoError::TimedOut{ .. })
+	}
+	
+	/// The kind of this error (without any payload; e.g. to aggregate errors in a map)
+	pub fn kind(&self) -> TimeoutIoErrorKind {
+		match self {
+			TimeoutIoError::InterruptedSyscall => TimeoutIoErrorKind::InterruptedSyscall,
+			TimeoutIoError::TimedOut{ .. } => TimeoutIoErrorKind::TimedOut,
+			TimeoutIoError::UnexpectedEof => TimeoutIoErrorKind::UnexpectedEof,
+			TimeoutIoError::GracefulEof => TimeoutIoErrorKind::GracefulEof,
+			TimeoutIoError::ConnectionLost{ .. } => TimeoutIoErrorKind::ConnectionLost,
+			TimeoutIoError::NotFound => TimeoutIoErrorKind::NotFound,
+			TimeoutIoError::InvalidInput => TimeoutIoErrorKind::InvalidInput,
+			TimeoutIoError::LimitExceeded => TimeoutIoErrorKind::LimitExceeded,
+			TimeoutIoError::AddrInUse{ .. } => TimeoutIoErrorKind::AddrInUse,
+			TimeoutIoError::AddrNotAvailable{ .. } => TimeoutIoErrorKind::AddrNotAvailable,
+			TimeoutIoError::PermissionDenied{ .. } => TimeoutIoErrorKind::PermissionDenied,
+			TimeoutIoError::TooSlow => TimeoutIoErrorKind::TooSlow,
+			TimeoutIoError::Refused{ .. } => TimeoutIoErrorKind::Refused,
+			TimeoutIoError::Unreachable{ .. } => TimeoutIoErrorKind::Unreachable,
+			TimeoutIoError::Other{ .. } => TimeoutIoErrorKind::Other
+		}
+	}
+	
+	/// The original `io::Error` if `self` was converted from one (i.e. the `source` of the variants
+	/// that originate from OS errors, like `ConnectionLost`)
+	///
+	/// _Info: This is `None` for all other variants and if the error was created by this crate
+	/// itself (e.g. a `ConnectionLost` because a channel was disconnected)._
+	pub fn as_io_error(&self) -> Option<&io::Error> {
 		match self {
-			TimeoutIoError::InterruptedSyscall | TimeoutIoError::TimedOut => true,
-			_ => false
+			TimeoutIoError::ConnectionLost{ source } | TimeoutIoError::AddrInUse{ source }
+				| TimeoutIoError::AddrNotAvailable{ source }
+				| TimeoutIoError::PermissionDenied{ source } | TimeoutIoError::Refused{ source }
+				| TimeoutIoError::Unreachable{ source } => source.as_deref(),
+			_ => None
+		}
+	}
+	
+	/// Converts `self` into an `io::Error` with the according `io::ErrorKind` (e.g. to return it
+	/// from a `std::io::Read`-implementation)
+	///
+	/// _Info: If `self` retains an original OS error (see `as_io_error`), an equivalent
+	/// `io::Error` with the same raw OS error code is returned. Otherwise, the `io::Error` is
+	/// synthesized and wraps `self`, so converting it back via `From<io::Error>` yields the same
+	/// variant (you can also use `io::Error::get_ref` and `downcast_ref`)._
+	pub fn into_io_error(self) -> io::Error {
+		use io::ErrorKind;
+		
+		// Recreate the original OS error
+		let os_error = self.as_io_error().and_then(|source| source.raw_os_error());
+		if let Some(code) = os_error { return io::Error::from_raw_os_error(code) }
+		
+		// Synthesize the error
+		let kind = match self {
+			TimeoutIoError::InterruptedSyscall => ErrorKind::Interrupted,
+			TimeoutIoError::TimedOut{ .. } => ErrorKind::TimedOut,
+			TimeoutIoError::UnexpectedEof | TimeoutIoError::GracefulEof => ErrorKind::UnexpectedEof,
+			TimeoutIoError::ConnectionLost{ .. } => ErrorKind::ConnectionReset,
+			TimeoutIoError::NotFound => ErrorKind::NotFound,
+			TimeoutIoError::InvalidInput => ErrorKind::InvalidInput,
+			TimeoutIoError::AddrInUse{ .. } => ErrorKind::AddrInUse,
+			TimeoutIoError::AddrNotAvailable{ .. } => ErrorKind::AddrNotAvailable,
+			TimeoutIoError::PermissionDenied{ .. } => ErrorKind::PermissionDenied,
+			TimeoutIoError::Refused{ .. } => ErrorKind::ConnectionRefused,
+			TimeoutIoError::Unreachable{ .. } => ErrorKind::HostUnreachable,
+			TimeoutIoError::LimitExceeded | TimeoutIoError::TooSlow | TimeoutIoError::Other{ .. } =>
+				ErrorKind::Other
+		};
+		io::Error::new(kind, self)
+	}
+}
+impl PartialEq for TimeoutIoError {
+	fn eq(&self, other: &Self) -> bool {
+		match (self, other) {
+			(TimeoutIoError::Other{ desc: a }, TimeoutIoError::Other{ desc: b }) => a == b,
+			(a, b) => a.kind() == b.kind()
 		}
 	}
 }
+impl Eq for TimeoutIoError {}
 impl Display for TimeoutIoError {
 	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
 		write!(f, "{:?}", self)
 	}
 }
-impl Error for TimeoutIoError {}
+impl Error for TimeoutIoError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		self.as_io_error().map(|source| source as &(dyn Error + 'static))
+	}
+}
 impl From<io::Error> for TimeoutIoError {
 	fn from(error: io::Error) -> Self {
+		// Unwrap errors that were created by `into_io_error`
+		if let Some(error) = error.get_ref().and_then(|e| e.downcast_ref::<TimeoutIoError>()) {
+			return error.clone()
+		}
+		// Map the error and retain the original where it originates from the OS
 		match error.kind() {
 			Interrupted => TimeoutIoError::InterruptedSyscall,
-			TimedOut | WouldBlock => TimeoutIoError::TimedOut,
+			TimedOut | WouldBlock => TIMED_OUT,
 			UnexpectedEof => TimeoutIoError::UnexpectedEof,
-			BrokenPipe | ConnectionAborted | ConnectionReset => TimeoutIoError::ConnectionLost,
+			BrokenPipe | ConnectionAborted | ConnectionReset =>
+				TimeoutIoError::ConnectionLost{ source: Some(Arc::new(error)) },
+			AddrInUse => TimeoutIoError::AddrInUse{ source: Some(Arc::new(error)) },
+			AddrNotAvailable => TimeoutIoError::AddrNotAvailable{ source: Some(Arc::new(error)) },
+			PermissionDenied => TimeoutIoError::PermissionDenied{ source: Some(Arc::new(error)) },
+			ConnectionRefused => TimeoutIoError::Refused{ source: Some(Arc::new(error)) },
+			HostUnreachable | NetworkUnreachable =>
+				TimeoutIoError::Unreachable{ source: Some(Arc::new(error)) },
 			_ => TimeoutIoError::Other{ desc: format!("{:#?}", error) }
 		}
 	}
 }
+impl From<String> for TimeoutIoError {
+	fn from(desc: String) -> Self {
+		TimeoutIoError::other(desc)
+	}
+}
+impl From<&str> for TimeoutIoError {
+	fn from(desc: &str) -> Self {
+		TimeoutIoError::other(desc)
+	}
+}
+impl From<RecvTimeoutError> for TimeoutIoError {
+	fn from(error: RecvTimeoutError) -> Self {
+		match error {
+			RecvTimeoutError::Timeout => TIMED_OUT,
+			RecvTimeoutError::Disconnected => TimeoutIoError::ConnectionLost{ source: None }
+		}
+	}
+}
+impl From<TryRecvError> for TimeoutIoError {
+	fn from(error: TryRecvError) -> Self {
+		match error {
+			TryRecvError::Empty => TIMED_OUT,
+			TryRecvError::Disconnected => TimeoutIoError::ConnectionLost{ source: None }
+		}
+	}
+}
+impl From<RecvError> for TimeoutIoError {
+	fn from(_: RecvError) -> Self {
+		TimeoutIoError::ConnectionLost{ source: None }
+	}
+}
+
+
+/// A `TimedOut`-error with unknown durations (e.g. for converted errors or within `with_deadline`,
+/// which replaces them)
+pub(crate) const TIMED_OUT: TimeoutIoError =
+	TimeoutIoError::TimedOut{ waited: Duration::from_secs(0), limit: Duration::from_secs(0) };
+
+
+/// The kind of a `TimeoutIoError` (see `TimeoutIoError::kind`)
+///
+/// _Info: Unlike `TimeoutIoError`, this does not carry any payload (e.g. the description of
+/// `Other`), so it can be used as key to aggregate errors (e.g. for metrics)._
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum TimeoutIoErrorKind {
+	/// The syscall was interrupted by a signal (see `TimeoutIoError::InterruptedSyscall`)
+	InterruptedSyscall,
+	/// The operation did not complete within its timeout (see `TimeoutIoError::TimedOut`)
+	TimedOut,
+	/// The connection was closed gracefully (FIN) while data was still expected
+	UnexpectedEof,
+	/// The connection was closed gracefully (FIN) before a one-shot `try_read` could read any data
+	GracefulEof,
+	/// The connection was aborted or reset (RST) by the peer
+	ConnectionLost,
+	/// The entity was not found (e.g. a hostname could not be resolved)
+	NotFound,
+	/// An argument was invalid (e.g. a stale FD or an empty address list)
+	InvalidInput,
+	/// A size limit was reached
+	LimitExceeded,
+	/// The address is already in use (e.g. the port is taken by another listener)
+	AddrInUse,
+	/// The address is not available (e.g. it does not belong to a local interface)
+	AddrNotAvailable,
+	/// The operation is not permitted (e.g. binding to a privileged port)
+	PermissionDenied,
+	/// The peer transferred data slower than the required minimum rate
+	TooSlow,
+	/// The connection was refused (i.e. nothing listens on the address)
+	Refused,
+	/// The host or network is unreachable (e.g. there is no route to it)
+	Unreachable,
+	/// Any other error (see `TimeoutIoError::Other` for the description)
+	Other
+}
+
+
+/// A shorthand for `Duration::from_secs(secs)` (e.g. `stream.try_read(&mut buf, &mut pos,
+/// secs(4))`)
+///
+/// _Info: `Duration` remains the canonical timeout type; this is just sugar to reduce boilerplate._
+pub const fn secs(secs: u64) -> Duration {
+	Duration::from_secs(secs)
+}
+/// A shorthand for `Duration::from_millis(millis)` (e.g. `stream.try_read(&mut buf, &mut pos,
+/// ms(250))`)
+///
+/// _Info: `Duration` remains the canonical timeout type; this is just sugar to reduce boilerplate._
+pub const fn ms(millis: u64) -> Duration {
+	Duration::from_millis(millis)
+}
+
+
+/// The deadline for huge timeouts that would overflow `Instant` (about 100 years from now; i.e.
+/// effectively infinite)
+const FAR_FUTURE: Duration = Duration::from_secs(100 * 365 * 24 * 60 * 60);
+/// Computes the deadline after `timeout` (counted from now) overflow-safe
+///
+/// _Info: If `timeout` is so large that the deadline cannot be represented (e.g. `Duration::MAX`),
+/// a far-future deadline is used instead (i.e. the timeout is treated as effectively infinite)._
+pub(crate) fn deadline_after(timeout: Duration) -> Instant {
+	let now = Instant::now();
+	now.checked_add(timeout)
+		.or_else(|| now.checked_add(FAR_FUTURE))
+		.expect("Failed to compute the deadline")
+}
+/// Calls `f` with the deadline after `timeout` and replaces the durations of a returned
+/// `TimedOut`-error with the time since the call and `timeout` (so that the error describes the
+/// whole operation instead of its last internal wait)
+pub(crate) fn with_deadline<T, F>(timeout: Duration, f: F) -> Result<T, TimeoutIoError>
+	where F: FnOnce(Instant) -> Result<T, TimeoutIoError>
+{
+	let start = Instant::now();
+	f(deadline_after(timeout)).map_err(|error| match error {
+		TimeoutIoError::TimedOut{ .. } => TimeoutIoError::timed_out(start, timeout),
+		error => error
+	})
+}
 
 
 /// Extends `std::time::Instant`
 pub trait InstantExt {
 	/// Computes the remaining time underflow-safe
 	fn remaining(self) -> Duration;
+	/// Computes the remaining time or returns `None` if `self` is already in the past
+	///
+	/// _Info: Unlike `remaining`, this allows you to distinguish "no time left" (`Some(0)`) from
+	/// "already expired" (`None`)._
+	fn remaining_checked(self) -> Option<Duration>;
 }
 impl InstantExt for Instant {
 	fn remaining(self) -> Duration {
-		let now = Instant::now();
-		if now > self { Duration::from_secs(0) }
-			else { self - now }
+		self.remaining_checked().unwrap_or_default()
+	}
+	fn remaining_checked(self) -> Option<Duration> {
+		self.checked_duration_since(Instant::now())
 	}
 }
\ No newline at end of file
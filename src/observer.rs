@@ -0,0 +1,87 @@
+use crate::{ TimeoutIoError, EventMask };
+use std::{
+	sync::{ Arc, RwLock, atomic::{ AtomicBool, Ordering::{ Acquire, Release } } },
+	time::{ Duration, Instant }
+};
+
+
+/// Whether an observer is installed (checked before taking the lock)
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+/// The process-wide observer
+static OBSERVER: RwLock<Option<Arc<dyn IoObserver>>> = RwLock::new(None);
+
+
+/// The operation a wait was performed for (derived from the awaited event mask)
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Operation {
+	/// A wait for read-readiness (this includes accepts and receives)
+	Read,
+	/// A wait for write-readiness (this includes sends)
+	Write,
+	/// A wait for read- or write-readiness
+	ReadWrite
+}
+impl Operation {
+	/// Derives the operation from the awaited `event` mask
+	fn from_event(event: EventMask) -> Self {
+		match event.rwe() {
+			(true, false, _) => Operation::Read,
+			(false, true, _) => Operation::Write,
+			_ => Operation::ReadWrite
+		}
+	}
+}
+
+
+/// An observer that is notified after each wait for an IO-event (e.g. to record histograms of the
+/// wait latencies without instrumenting every call)
+///
+/// _Info: The observer is invoked by the single-handle waits of `WaitForEvent` (`wait_for_event`,
+/// `wait_for_event_precise` and `wait_for_event_multi`) and thus by all `try_*`-loops of the
+/// blanket implementations; explicit calls of these functions are reported, too. Waits on multiple
+/// handles (`SelectSet`, `poll_many` and `wait_either`/`wait_for_event_or_wake`) are not
+/// reported._
+///
+/// _Note: The observer is process-wide and may be invoked concurrently from all threads that
+/// perform IO; so it must be `Send + Sync` and should return quickly (it runs on the IO path)._
+pub trait IoObserver: Send + Sync {
+	/// Called after a wait on the raw `fd` for `op` that took `waited` and completed with `result`
+	/// (`TimedOut` if the event did not occur in time)
+	fn on_wait(&self, op: Operation, fd: u64, waited: Duration,
+		result: &Result<(), TimeoutIoError>);
+}
+
+
+/// Installs `observer` as process-wide IO-observer (replaces the previous observer; see
+/// `IoObserver`)
+pub fn set_observer(observer: Arc<dyn IoObserver>) {
+	*OBSERVER.write().unwrap_or_else(|e| e.into_inner()) = Some(observer);
+	INSTALLED.store(true, Release);
+}
+/// Removes the process-wide IO-observer
+pub fn clear_observer() {
+	INSTALLED.store(false, Release);
+	*OBSERVER.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+
+/// Performs `wait` and reports it to the installed observer
+///
+/// _Info: If no observer is installed, this is a single atomic load._
+pub(crate) fn observe<T, F>(fd: u64, event: EventMask, wait: F) -> Result<T, TimeoutIoError>
+	where F: FnOnce() -> Result<T, TimeoutIoError>
+{
+	// Skip the measurement if there is no observer
+	if !INSTALLED.load(Acquire) { return wait() }
+	let start = Instant::now();
+	let result = wait();
+	let waited = start.elapsed();
+	
+	// Notify the observer
+	let observer = OBSERVER.read().unwrap_or_else(|e| e.into_inner()).clone();
+	if let Some(observer) = observer {
+		let reported = result.as_ref().map(|_| ()).map_err(|e| e.clone());
+		observer.on_wait(Operation::from_event(event), fd, waited, &reported);
+	}
+	result
+}
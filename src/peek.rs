@@ -0,0 +1,90 @@
+use crate::{ TimeoutIoError, InstantExt, with_deadline, TIMED_OUT, WaitForEvent, EventMask };
+use std::{
+	cmp::min, thread,
+	time::Duration,
+	net::TcpStream
+};
+
+
+/// The delay between two `peek`-attempts if not enough bytes are available yet
+const PEEK_DELAY: Duration = Duration::from_millis(1);
+
+
+/// The byte order of an integer prefix
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Endianness {
+	Big,
+	Little
+}
+
+
+/// A trait for peeking at pending data without consuming it (e.g. to inspect the length prefix of
+/// a frame before reading it)
+pub trait Peeker {
+	/// Peeks until `buf` is filled completely without consuming any data from the stream
+	///
+	/// _Note: If fewer bytes than `buf.len()` are available yet, the `peek` is retried (with a short
+	/// delay because the socket remains readable) until the `timeout` is hit. If the peer closed
+	/// the connection before any data was sent, `GracefulEof` is returned._
+	///
+	/// _Note: A zero `timeout` performs exactly one `peek`-attempt and returns `TimedOut`
+	/// immediately if not enough bytes are available._
+	fn try_peek_exact(&self, buf: &mut[u8], timeout: Duration) -> Result<(), TimeoutIoError>;
+	
+	/// Peeks an unsigned `width`-byte integer in the given byte order without consuming it
+	///
+	/// _Note: `width` must be in `1..=8`, otherwise `InvalidInput` is returned._
+	fn try_peek_uint(&self, width: usize, endianness: Endianness, timeout: Duration)
+		-> Result<u64, TimeoutIoError>;
+	
+	/// Peeks a big-endian `u32` (the common length prefix) without consuming it
+	fn try_peek_u32_be(&self, timeout: Duration) -> Result<u32, TimeoutIoError>;
+}
+impl Peeker for TcpStream {
+	fn try_peek_exact(&self, buf: &mut[u8], timeout: Duration) -> Result<(), TimeoutIoError> {
+		// Compute the deadline
+		with_deadline(timeout, |deadline| {
+			loop {
+				// Wait for read-event (unless we only perform a single attempt) and peek data
+				if !timeout.is_zero() {
+					self.wait_for_event(EventMask::new_r(), deadline.remaining())?;
+				}
+				match self.peek(buf) {
+					Ok(0) if !buf.is_empty() => return Err(TimeoutIoError::GracefulEof),
+					Ok(peeked) if peeked == buf.len() => return Ok(()),
+					Ok(_) => (),
+					Err(error) => {
+						let error = TimeoutIoError::from(error);
+						if !error.should_retry() { return Err(error) }
+					}
+				}
+				
+				// Sleep shortly because the socket is still readable
+				match deadline.remaining() {
+					remaining if remaining.is_zero() => return Err(TIMED_OUT),
+					remaining => thread::sleep(min(PEEK_DELAY, remaining))
+				}
+			}
+		})
+	}
+	
+	fn try_peek_uint(&self, width: usize, endianness: Endianness, timeout: Duration)
+		-> Result<u64, TimeoutIoError>
+	{
+		// Validate the width and peek the bytes
+		if !(1..=8).contains(&width) { return Err(TimeoutIoError::InvalidInput) }
+		let mut buf = [0; 8];
+		self.try_peek_exact(&mut buf[..width], timeout)?;
+		
+		// Decode the integer
+		let bytes = &buf[..width];
+		Ok(match endianness {
+			Endianness::Big => bytes.iter().fold(0, |value, byte| (value << 8) | *byte as u64),
+			Endianness::Little => bytes.iter().rev().fold(0, |value, byte| (value << 8) | *byte as u64)
+		})
+	}
+	
+	fn try_peek_u32_be(&self, timeout: Duration) -> Result<u32, TimeoutIoError> {
+		Ok(self.try_peek_uint(4, Endianness::Big, timeout)? as u32)
+	}
+}
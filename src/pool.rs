@@ -0,0 +1,118 @@
+use crate::{ TimeoutIoError, WaitForEvent, EventMask };
+use std::{
+	collections::HashMap,
+	ops::{ Deref, DerefMut },
+	net::{ SocketAddr, TcpStream },
+	sync::{ Arc, Mutex },
+	time::{ Duration, Instant }
+};
+
+
+/// The idle connections per address
+type IdleConnections = Arc<Mutex<HashMap<SocketAddr, Vec<(TcpStream, Instant)>>>>;
+
+
+/// A pool of keep-alive TCP-connections
+///
+/// _Info: Connections are checked out with `get` and returned to the pool if the `PooledConnection`
+/// is dropped (unless it was marked as errored). Connections that were idle for longer than the
+/// idle timeout are evicted._
+#[derive(Debug, Clone)]
+pub struct ConnectionPool {
+	idle_timeout: Duration,
+	idle: IdleConnections
+}
+impl ConnectionPool {
+	/// Creates a new connection pool that evicts connections which were idle for longer than
+	/// `idle_timeout`
+	pub fn new(idle_timeout: Duration) -> Self {
+		Self{ idle_timeout, idle: Arc::new(Mutex::new(HashMap::new())) }
+	}
+	
+	/// Checks out a live pooled connection to `address` or connects a new one if there is none
+	/// (`timeout` is the connect timeout)
+	///
+	/// _Info: Before a pooled connection is handed out, we check if it is readable; an idle
+	/// keep-alive connection that is readable has either been closed by the peer or contains
+	/// unexpected data and is discarded._
+	///
+	/// _Note: The returned connection is non-blocking._
+	pub fn get(&self, address: &SocketAddr, timeout: Duration)
+		-> Result<PooledConnection, TimeoutIoError>
+	{
+		// Evict idle connections and check out a live one
+		self.evict_idle();
+		let pooled = {
+			let mut idle = self.idle.lock().expect("Connection pool is poisoned");
+			let connections = idle.entry(*address).or_default();
+			std::iter::from_fn(|| connections.pop()).map(|(stream, _)| stream)
+				.find(|stream| matches!(
+					stream.wait_for_event(EventMask::new_r(), Duration::from_secs(0)),
+					Err(TimeoutIoError::TimedOut{ .. })
+				))
+		};
+		
+		// Connect a new stream if necessary
+		let stream = match pooled {
+			Some(stream) => stream,
+			None => TcpStream::connect_timeout(address, timeout)?.into_nonblocking()?
+		};
+		Ok(PooledConnection{
+			stream: Some(stream), address: *address,
+			errored: false, pool: self.clone()
+		})
+	}
+	
+	/// Evicts all connections that were idle for longer than the idle timeout
+	pub fn evict_idle(&self) {
+		let mut idle = self.idle.lock().expect("Connection pool is poisoned");
+		idle.values_mut().for_each(|connections| {
+			connections.retain(|(_, since)| since.elapsed() <= self.idle_timeout)
+		});
+		idle.retain(|_, connections| !connections.is_empty());
+	}
+	
+	/// The amount of idle connections in the pool
+	pub fn idle_count(&self) -> usize {
+		let idle = self.idle.lock().expect("Connection pool is poisoned");
+		idle.values().map(|connections| connections.len()).sum()
+	}
+}
+
+
+/// A connection checked out from a `ConnectionPool` that derefs to the underlying `TcpStream`
+///
+/// _Info: The connection is returned to the pool on drop unless it was marked as errored._
+#[derive(Debug)]
+pub struct PooledConnection {
+	stream: Option<TcpStream>,
+	address: SocketAddr,
+	errored: bool,
+	pool: ConnectionPool
+}
+impl PooledConnection {
+	/// Marks the connection as errored so that it is not returned to the pool
+	pub fn mark_errored(&mut self) {
+		self.errored = true
+	}
+}
+impl Deref for PooledConnection {
+	type Target = TcpStream;
+	fn deref(&self) -> &Self::Target {
+		self.stream.as_ref().expect("Connection has already been returned")
+	}
+}
+impl DerefMut for PooledConnection {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		self.stream.as_mut().expect("Connection has already been returned")
+	}
+}
+impl Drop for PooledConnection {
+	fn drop(&mut self) {
+		if let (Some(stream), false) = (self.stream.take(), self.errored) {
+			if let Ok(mut idle) = self.pool.idle.lock() {
+				idle.entry(self.address).or_default().push((stream, Instant::now()));
+			}
+		}
+	}
+}
@@ -0,0 +1,79 @@
+use crate::{ TimeoutIoError, InstantExt, deadline_after, Reader, Writer };
+use std::time::Duration;
+
+
+/// The buffer size used to pump data
+const BUFFER_SIZE: usize = 64 * 1024;
+
+
+/// The independent timeouts for `pump`
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct PumpTimeouts {
+	/// The maximum time to wait for the next chunk from the source
+	pub read_idle: Duration,
+	/// The maximum time to write a chunk to the destination
+	pub write: Duration,
+	/// The maximum time for the entire transfer
+	pub total: Duration
+}
+
+
+/// The timeout that fired during `pump`
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PumpTimeout {
+	/// The source did not yield data within `read_idle`
+	ReadIdle,
+	/// A chunk could not be written to the destination within `write`
+	Write,
+	/// The entire transfer did not complete within `total`
+	Total
+}
+
+
+/// Pumps data from `src` to `dst` until `src` reaches the end of the stream or a timeout fires and
+/// adjusts `moved` _on every successful `write`-call_. Returns the timeout that fired or `None`
+/// if `src` reached the end of the stream.
+///
+/// Each chunk is read from `src` within `read_idle` and then written completely to `dst` within
+/// `write`; so a slow source cannot consume the write timeout and vice versa. `total` limits the
+/// entire transfer.
+///
+/// _Note: If a timeout fires during a write, the unwritten rest of the current chunk is lost._
+///
+/// __Warning: `src` and `dst` must non-blocking or the function won't work as expected__
+pub fn pump(src: &mut impl Reader, dst: &mut impl Writer, moved: &mut usize,
+	timeouts: PumpTimeouts) -> Result<Option<PumpTimeout>, TimeoutIoError>
+{
+	// Compute deadline and create buffer
+	let deadline = deadline_after(timeouts.total);
+	let mut buf = vec![0; BUFFER_SIZE];
+	
+	// Selects the effective timeout and the timeout that fires if the operation times out
+	let effective = |timeout: Duration, kind: PumpTimeout| match deadline.remaining() {
+		remaining if remaining <= timeout => (remaining, PumpTimeout::Total),
+		_ => (timeout, kind)
+	};
+	
+	loop {
+		// Read the next chunk
+		let (timeout, kind) = effective(timeouts.read_idle, PumpTimeout::ReadIdle);
+		let mut read = 0;
+		match src.try_read(&mut buf, &mut read, timeout) {
+			Ok(_) => (),
+			Err(TimeoutIoError::GracefulEof) => return Ok(None),
+			Err(TimeoutIoError::TimedOut{ .. }) => return Ok(Some(kind)),
+			Err(e) => return Err(e)
+		}
+		
+		// Write the chunk
+		let (timeout, kind) = effective(timeouts.write, PumpTimeout::Write);
+		let mut written = 0;
+		let result = dst.try_write_exact(&buf[..read], &mut written, timeout);
+		*moved += written;
+		match result {
+			Ok(_) => (),
+			Err(TimeoutIoError::TimedOut{ .. }) => return Ok(Some(kind)),
+			Err(e) => return Err(e)
+		}
+	}
+}
@@ -1,11 +1,47 @@
-use crate::{ TimeoutIoError, InstantExt, WaitForEvent, EventMask };
+use crate::{
+	TimeoutIoError, InstantExt, with_deadline, TIMED_OUT, WaitForEvent, EventMask, SharedDeadline,
+	TimeoutConfig, Decoder, RawFd, SelectSet
+};
 use std::{
-	io::Read,
+	ptr, cmp::min,
+	collections::VecDeque,
+	io::{ Read, IoSliceMut },
 	time::{ Duration, Instant }
 };
 
 
+/// The chunk size used to extend growable buffers
+const CHUNK_SIZE: usize = 4096;
+/// The sliding window over which the transfer rate is measured
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+/// The maximum duration of a single wait before the transfer rate is checked again
+const RATE_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+/// The maximum length of an LEB128-encoded `u64`
+const VARINT_MAX_LEN: usize = 10;
+
+
+/// Finds the first occurrence of `pat` in `data` and returns the index _behind_ the match
+pub(crate) fn find_pattern(data: &[u8], pat: &[u8]) -> Option<usize> {
+	match pat.len() {
+		0 => Some(0),
+		len => data.windows(len).position(|window| window == pat).map(|i| i + len)
+	}
+}
+
+
 /// A trait for reading with timeouts
+///
+/// _Note: This trait is implemented for all types that implement `std::io::Read` and
+/// `WaitForEvent` – i.e. the type must expose a raw fd/socket. In-memory types like
+/// `std::io::Cursor` or `&[u8]` are not supported._
+///
+/// _Info: The trait is object safe, so that you can store heterogeneous streams (e.g. `TcpStream`
+/// and `UnixStream`) as `Box<dyn Reader>`; generic conveniences must be bounded by `Self: Sized`._
+#[diagnostic::on_unimplemented(
+	message = "`{Self}` cannot be used as a `Reader`",
+	label = "`{Self}` does not implement `Reader`",
+	note = "`Reader` is implemented for all `std::io::Read + WaitForEvent` types; the type must expose a raw fd/socket (see `RawFd`)"
+)]
 pub trait Reader {
 	/// Executes _one_ `read`-operation to read _as much bytes as possible_ into `buf[*pos..]` and
 	/// adjusts `pos` accordingly
@@ -17,29 +53,264 @@ pub trait Reader {
 	/// either one successful `read`-operation or the `timeout` was hit or a non-recoverable error
 	/// occurred._
 	///
+	/// _Note: A zero `timeout` performs exactly one non-blocking attempt and returns `TimedOut`
+	/// immediately if `self` is not ready._
+	///
+	/// _Note: If the peer closed the connection gracefully (FIN), `GracefulEof` is returned; if the
+	/// connection was reset (RST), `ConnectionLost` is returned. The multi-read functions like
+	/// `try_read_exact` return `UnexpectedEof` instead of `GracefulEof` because the end of the
+	/// stream is unexpected there._
+	///
+	/// _Note: If `buf` is empty or already full (i.e. `*pos >= buf.len()`), this is a no-op that
+	/// returns `Ok(())` without touching `self`._
+	///
 	/// __Warning: `self` must non-blocking or the function won't work as expected__
 	fn try_read(&mut self, buf: &mut[u8], pos: &mut usize, timeout: Duration)
 		-> Result<(), TimeoutIoError>;
 	
+	/// Like `try_read`, but uses the read-timeout of the process-wide `TimeoutConfig`
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn try_read_default(&mut self, buf: &mut[u8], pos: &mut usize) -> Result<(), TimeoutIoError>;
+	
+	/// Like `try_read`, but waits for `mask` instead of `EventMask::new_r()` before reading
+	///
+	/// _Note: The read is attempted as soon as _any_ event in `mask` occurs; so an unusual mask
+	/// (e.g. one that includes write-events) may result in `read`-attempts while there is no data
+	/// available. These are retried transparently until the `timeout` is hit, but can cause busy
+	/// waiting if the event in question persists. A mask without the error-event ignores
+	/// out-of-band/error-conditions._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn try_read_with_mask(&mut self, buf: &mut[u8], pos: &mut usize, mask: EventMask,
+		timeout: Duration) -> Result<(), TimeoutIoError>;
+	
+	/// Executes _one_ `read`-operation that appends _as much bytes as possible_ (but not more than
+	/// `max - buf.len()`) to `buf` and returns the amount of bytes read
+	///
+	/// This is the `Vec`-counterpart to `try_read`: `buf` grows only as needed and you can
+	/// accumulate data across multiple calls without tracking a separate `pos`. Returns
+	/// `LimitExceeded` if `buf` already contains `max` or more bytes.
+	///
+	/// _Info: Because `Read` requires an initialized buffer and a `Vec` cannot track initialized
+	/// spare capacity, the new space must be zeroed before reading. To keep this cheap even for
+	/// huge `max`-values or preallocated buffers, at most 4 KiB are read per call._
+	///
+	/// _Note: This function catches all internal timeouts/interrupts and returns only if there was
+	/// either one successful `read`-operation or the `timeout` was hit or a non-recoverable error
+	/// occurred._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn try_read_append(&mut self, buf: &mut Vec<u8>, max: usize, timeout: Duration)
+		-> Result<usize, TimeoutIoError>;
+	
+	/// Executes _one_ `read`-operation that appends _as much bytes as possible_ to the spare
+	/// capacity of `dst` and returns the amount of bytes read
+	///
+	/// This is the `bytes::BytesMut`-counterpart to `try_read_append`: if `dst` has no spare
+	/// capacity, some space is reserved; afterwards, the data is read directly into the tail of
+	/// `dst` (without an intermediate buffer) and the length is advanced accordingly.
+	///
+	/// _Info: Because `Read` requires an initialized buffer, the spare capacity is zeroed before
+	/// reading. To keep this cheap even for huge spare capacities, at most 4 KiB are zeroed and
+	/// read per call._
+	///
+	/// _Note: This function is only available if the `bytes`-feature is enabled._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	#[cfg(feature = "bytes")]
+	fn try_read_buf_bytes(&mut self, dst: &mut bytes::BytesMut, timeout: Duration)
+		-> Result<usize, TimeoutIoError>;
+	
 	/// Reads until `buf[*pos..]` is filled completely and adjusts `pos` _on every successful
 	/// `read`-call_ (so that you can continue seamlessly on `TimedOut`-errors etc.)
 	///
 	/// This is especially useful in stream-based contexts where partial-`read`-calls are common
 	/// (like in TCP) and you want to read a well-known amount of bytes
 	///
-	/// _Note: This function catches all internal timeouts/interrupts and returns only if either
-	/// `buf` has been filled completely or the `timeout` was exceeded or a non-recoverable error
-	/// occurred._
+	/// _Note: This function catches all internal timeouts/interrupts (including `Interrupted`-errors
+	/// returned by the underlying `read`-call itself) and returns only if either `buf` has been
+	/// filled completely or the `timeout` was exceeded or a non-recoverable error occurred._
+	///
+	/// _Info: The first `read` is attempted _before_ waiting for a read-event; so if data is already
+	/// available, this saves the wait-syscall. We only wait if `read` returns `WouldBlock`._
+	///
+	/// _Note: If `buf` is empty or already full (i.e. `*pos >= buf.len()`), this is a no-op that
+	/// returns `Ok(())` without touching `self`._
+	///
+	/// _Info: `buf` is passed to the underlying `read`-calls directly (no intermediate buffer); so
+	/// you can e.g. read into a memory-mapped file region without an extra copy – regardless of
+	/// how many `read`-calls are necessary to fill it._
 	///
 	/// __Warning: `self` must non-blocking or the function won't work as expected__
 	fn try_read_exact(&mut self, buf: &mut[u8], pos: &mut usize, timeout: Duration)
 		-> Result<(), TimeoutIoError>;
 	
+	/// Like `try_read_exact`, but tolerates a premature EOF and returns the amount of bytes read
+	/// by this call (i.e. the amount by which `pos` was advanced)
+	///
+	/// This is useful for best-effort reads where a short final record is acceptable: compare the
+	/// returned count (or `*pos`) against `buf.len()` to detect truncation.
+	///
+	/// _Info: Unlike `try_read_exact` (which returns `UnexpectedEof` if the connection is closed
+	/// before `buf` is filled), this function returns `Ok` with the partial count; all other errors
+	/// (e.g. `TimedOut`) are returned as usual and `pos` still reflects the progress._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn try_read_exact_partial(&mut self, buf: &mut[u8], pos: &mut usize, timeout: Duration)
+		-> Result<usize, TimeoutIoError>;
+	
+	/// Reads into `buf` until `decoder` decodes an item and returns it; the decoded bytes are
+	/// removed from `buf` while the remaining bytes are kept for the next call (so always pass the
+	/// same `buf` for a stream)
+	///
+	/// _Info: `buf` is decoded _before_ reading; so if a previous read received multiple items, the
+	/// following calls return them without reading. Use a `Vec` with some capacity to reduce
+	/// reallocations._
+	///
+	/// _Note: If the connection is closed gracefully while `buf` is empty (i.e. between two items),
+	/// `GracefulEof` is returned; if it is closed within an item, `UnexpectedEof` is returned.
+	/// `decoder` is responsible for limiting the size of an item._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn try_read_frame<D: Decoder>(&mut self, decoder: &mut D, buf: &mut Vec<u8>, timeout: Duration)
+		-> Result<D::Item, TimeoutIoError> where Self: Sized;
+	
+	/// Reads into `scratch` until `read` returns `WouldBlock` (i.e. until `self` is drained) and
+	/// calls `on_chunk` with every chunk read
+	///
+	/// Since `select`/`poll` are level-triggered, a handle that was reported as readable (e.g. by
+	/// `SelectSet::select` or `wait_for_event`) is reported again immediately until all available
+	/// data has been read; this function encapsulates this drain-pattern for event loops.
+	///
+	/// _Info: This function never waits; if no data is available, it returns `Ok(())` without
+	/// calling `on_chunk`._
+	///
+	/// _Note: If the connection is closed, all data read before is passed to `on_chunk` and
+	/// `GracefulEof` is returned. An empty `scratch` results in `InvalidInput`._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn drain_read(&mut self, on_chunk: impl FnMut(&[u8]), scratch: &mut[u8])
+		-> Result<(), TimeoutIoError> where Self: Sized;
+	
+	/// Reads until at least `min` bytes are in `buf` (i.e. `*pos >= min`) and adjusts `pos` _on
+	/// every successful `read`-call_ (so that you can continue seamlessly on `TimedOut`-errors
+	/// etc.)
+	///
+	/// This is useful if e.g. a protocol header has at least `min` bytes but may be followed by
+	/// more data in the same packet: every `read` fills as much of `buf[*pos..]` as available, so
+	/// additional data is grabbed opportunistically.
+	///
+	/// _Info: Unlike `try_read_exact` (which reads until `buf` is full), this function returns as
+	/// soon as `min` bytes are available; `buf.len()` only limits how much data may be read._
+	///
+	/// _Note: `min` is capped at `buf.len()`; so if `*pos >= min` already, this is a no-op that
+	/// returns `Ok(())` without touching `self`. If the connection is closed before `min` bytes
+	/// were read, `UnexpectedEof` is returned._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn try_read_at_least(&mut self, buf: &mut[u8], pos: &mut usize, min: usize, timeout: Duration)
+		-> Result<(), TimeoutIoError>;
+	
+	/// Fills each buffer in `targets` completely and in order (e.g. a length-field, then an ID,
+	/// then the payload) and adjusts `pos` _on every successful `read`-call_; `pos` is the total
+	/// amount of bytes read into _all_ targets (so that you can continue seamlessly on
+	/// `TimedOut`-errors etc.)
+	///
+	/// _Info: Unlike a vectored read, this function performs as many reads as necessary; all
+	/// reads share the same `timeout`._
+	///
+	/// _Note: This function catches all internal timeouts/interrupts and returns only if either
+	/// all targets have been filled completely or the `timeout` was exceeded or a non-recoverable
+	/// error occurred._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn try_read_exact_into(&mut self, targets: &mut[&mut[u8]], pos: &mut usize, timeout: Duration)
+		-> Result<(), TimeoutIoError>;
+	
+	/// Reads until every slice in `bufs` has been filled completely and increases `pos` by the
+	/// amount of bytes read _on every successful `read`-call_ (so that `pos` tracks the total
+	/// progress across calls)
+	///
+	/// This is the vectored counterpart to `try_read_exact` (e.g. to reassemble a message into
+	/// preallocated non-contiguous buffers).
+	///
+	/// _Info: The slices are advanced in place (via `IoSliceMut::advance`): after each read, all
+	/// completely filled slices are empty and a partially filled slice starts behind the bytes
+	/// read. So if an error occurs (e.g. `TimedOut`), `bufs` contains only the remaining space and
+	/// you can continue seamlessly by passing the same `bufs` again._
+	///
+	/// _Note: This function catches all internal timeouts/interrupts and returns only if either
+	/// all slices have been filled completely or the `timeout` was exceeded or a non-recoverable
+	/// error occurred._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn try_read_vectored_exact(&mut self, bufs: &mut[IoSliceMut], pos: &mut usize,
+		timeout: Duration) -> Result<(), TimeoutIoError>;
+	
+	/// Reads exactly `len` bytes and decodes them as UTF-8 string
+	///
+	/// Returns `InvalidInput` if the bytes are not valid UTF-8.
+	///
+	/// _Note: On an UTF-8-error, all `len` bytes have been consumed; so the stream is positioned
+	/// directly behind the field and you can continue with the next field. On other errors (e.g.
+	/// `TimedOut`), the bytes read so far are lost; use `try_read_exact` if you need to continue
+	/// seamlessly._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn try_read_string(&mut self, len: usize, timeout: Duration) -> Result<String, TimeoutIoError>;
+	/// Reads an unsigned LEB128-varint (e.g. a protobuf-style length prefix) byte by byte until
+	/// `timeout` expires
+	///
+	/// Returns `InvalidInput` if the varint is longer than 10 bytes (the maximum for a `u64`) or if
+	/// it does not fit into a `u64`; so a peer can't keep us reading an endless continuation.
+	///
+	/// _Note: Non-minimal encodings (e.g. `0x80 0x00` for `0`) are accepted. If the connection is
+	/// closed before the first byte, `GracefulEof` is returned; if it is closed within the varint,
+	/// `UnexpectedEof` is returned. On other errors (e.g. `TimedOut`), the bytes read so far are
+	/// lost._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn try_read_varint(&mut self, timeout: Duration) -> Result<u64, TimeoutIoError>;
+	
+	/// Like `try_read_exact`, but additionally returns `TooSlow` if the peer transfers less than
+	/// `min_bytes_per_sec` (e.g. to defend against "slowloris"-clients that trickle data to hold
+	/// connections open)
+	///
+	/// _Info: The rate is measured over a sliding window of the last second: once the first
+	/// second has elapsed, the amount of bytes received within the window is compared against
+	/// `min_bytes_per_sec` before every `read` and at least every 100ms while waiting._
+	///
+	/// _Note: `pos` is adjusted on every successful `read`-call; so you can continue seamlessly on
+	/// `TimedOut`-errors (the rate measurement restarts then)._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn try_read_exact_min_rate(&mut self, buf: &mut[u8], pos: &mut usize, min_bytes_per_sec: u64,
+		timeout: Duration) -> Result<(), TimeoutIoError>;
+	
+	/// Like `try_read_exact`, but uses a `deadline` that can be moved by other threads while the
+	/// operation is in progress
+	///
+	/// _Note: The deadline is re-read before every internal wait and every internal wait is capped
+	/// at `SharedDeadline::GRANULARITY`; so changes take effect within that granularity (e.g. if
+	/// you `expire` the deadline during shutdown, a stalled read returns `TimedOut` promptly). If
+	/// the deadline was extended while a wait timed out, the function continues to read._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn try_read_exact_shared(&mut self, buf: &mut[u8], pos: &mut usize, deadline: &SharedDeadline)
+		-> Result<(), TimeoutIoError>;
+	
 	/// Reads until either `pat` is matched or `buf` is filled completely and adjusts `pos`
 	/// accordingly. Returns `true` if `pat` was matched and `false` otherwise.
 	///
 	/// _Note: While the reading is continued at `*pos`, `pat` is matched against the entire `buf`_
 	///
+	/// _Info: After each byte, only the last `pat.len()` bytes of `buf` are compared against `pat`
+	/// (the rest of `buf` is never rescanned)._
+	///
+	/// _Note: If `buf` is empty or already full (i.e. `*pos >= buf.len()`), this is a no-op that
+	/// returns `Ok(false)` without touching `self` (even if `buf` ends with `pat`)._
+	///
 	/// _Note: This function catches all interal timeouts/interrupts and returns only if either
 	/// `pattern` has been matched or `buffer` has been filled completely or the `timeout` was hit
 	/// or a non-recoverable error occurred._
@@ -47,46 +318,363 @@ pub trait Reader {
 	/// __Warning: `self` must non-blocking or the function won't work as expected__
 	fn try_read_until(&mut self, buf: &mut[u8], pos: &mut usize, pat: &[u8], timeout: Duration)
 		-> Result<bool, TimeoutIoError>;
+	/// Like `try_read_until`, but stops only at an _unescaped_ `delim`
+	///
+	/// A `delim` that is preceded by an `escape` is a literal and does not terminate the message;
+	/// an `escape` that is preceded by another `escape` is a literal escape (i.e. `escape escape
+	/// delim` terminates, `escape escape escape delim` does not). In other words: a `delim` is
+	/// escaped if it is preceded by an odd amount of consecutive `escape`-bytes.
+	///
+	/// _Note: The bytes are stored as they were received (i.e. the escape sequences remain in
+	/// `buf`); this allows you to resume at `*pos` after `TimedOut`-errors etc. – the escape state
+	/// is derived from `buf` itself, so escapes before `*pos` are taken into account._
+	///
+	/// _Note: Returns `InvalidInput` if `delim == escape`._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn try_read_until_unescaped(&mut self, buf: &mut[u8], pos: &mut usize, delim: u8, escape: u8,
+		timeout: Duration) -> Result<bool, TimeoutIoError>;
+	
+	/// Reads chunk-wise and appends to `buf` until either `pat` is matched or `buf` has reached
+	/// `limit` bytes. Returns the index _behind_ the first match of `pat` or `None` if `limit` was
+	/// reached without a match.
+	///
+	/// Unlike `try_read_until`, this function does not read byte by byte; so `buf` may contain
+	/// additional bytes behind the match.
+	///
+	/// _Note: The bytes that are already in `buf` are scanned first; so if they contain a match
+	/// (e.g. pipelined data that was read behind a previous match), it is returned without reading.
+	/// Since all read bytes remain in `buf`, you can continue seamlessly on `TimedOut`-errors etc._
+	///
+	/// _Info: The matching is incremental – after each read, only the newly read bytes and the
+	/// last `pat.len() - 1` bytes before them are scanned (so that a match that straddles two reads
+	/// is still found). So within a call, the amount of scanned windows is linear in the amount of
+	/// bytes read – even for huge buffers and long patterns that never match._
+	///
+	/// _Note: This function catches all interal timeouts/interrupts and returns only if either
+	/// `pat` has been matched or `limit` has been reached or the `timeout` was hit or a
+	/// non-recoverable error occurred._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn try_read_until_limit(&mut self, buf: &mut Vec<u8>, pat: &[u8], limit: usize,
+		timeout: Duration) -> Result<Option<usize>, TimeoutIoError>;
 }
 impl<T: Read + WaitForEvent> Reader for T {
 	fn try_read(&mut self, buf: &mut[u8], pos: &mut usize, timeout: Duration)
 		-> Result<(), TimeoutIoError>
 	{
-		// Loop until we have *one* successful read
-		if *pos >= buf.len() { return Ok(()) }
+		self.try_read_with_mask(buf, pos, EventMask::new_r(), timeout)
+	}
+	fn try_read_default(&mut self, buf: &mut[u8], pos: &mut usize) -> Result<(), TimeoutIoError> {
+		self.try_read(buf, pos, TimeoutConfig::current().read_timeout())
+	}
+	fn try_read_with_mask(&mut self, buf: &mut[u8], pos: &mut usize, mask: EventMask,
+		timeout: Duration) -> Result<(), TimeoutIoError>
+	{
+		// Compute the deadline
+		with_deadline(timeout, |deadline| {
+			// Loop until we have *one* successful read
+			if *pos >= buf.len() { return Ok(()) }
+			loop {
+				// Wait for read-event (unless we only perform a non-blocking attempt) and read data
+				if !timeout.is_zero() { self.wait_for_event(mask, deadline.remaining())?; }
+				match self.read(&mut buf[*pos..]) {
+					Ok(0) => return Err(TimeoutIoError::GracefulEof),
+					Ok(read) => {
+						*pos += read;
+						return Ok(())
+					},
+					Err(error) => {
+						let error = TimeoutIoError::from(error);
+						if !error.should_retry() || (timeout.is_zero() && error.is_timeout()) {
+							return Err(error)
+						}
+					}
+				}
+			}
+		})
+	}
+	fn try_read_append(&mut self, buf: &mut Vec<u8>, max: usize, timeout: Duration)
+		-> Result<usize, TimeoutIoError>
+	{
+		// Compute the amount of bytes to read (limited to one chunk so that we don't zero a huge
+		// spare capacity on every call)
+		let len = buf.len();
+		if len >= max { return Err(TimeoutIoError::LimitExceeded) }
+		let to_read = min(max - len, CHUNK_SIZE);
+		
+		// Zero the new space and read into it
+		let mut pos = len;
+		buf.resize(len + to_read, 0);
+		let result = self.try_read(buf, &mut pos, timeout);
+		buf.truncate(pos);
+		result.map(|_| pos - len)
+	}
+	#[cfg(feature = "bytes")]
+	fn try_read_buf_bytes(&mut self, dst: &mut bytes::BytesMut, timeout: Duration)
+		-> Result<usize, TimeoutIoError>
+	{
+		use bytes::BufMut;
+		
+		// Reserve some space if there is no spare capacity and zero (at most) one chunk of the
+		// spare capacity
+		if dst.capacity() == dst.len() { dst.reserve(CHUNK_SIZE) }
+		let spare = dst.chunk_mut();
+		let to_read = min(spare.len(), CHUNK_SIZE);
+		let buf = unsafe{
+			std::ptr::write_bytes(spare.as_mut_ptr(), 0, to_read);
+			std::slice::from_raw_parts_mut(spare.as_mut_ptr(), to_read)
+		};
+		
+		// Read into the spare capacity and advance the length
+		let mut pos = 0;
+		self.try_read(buf, &mut pos, timeout)?;
+		unsafe{ dst.advance_mut(pos) };
+		Ok(pos)
+	}
+	fn try_read_exact(&mut self, buf: &mut[u8], pos: &mut usize, timeout: Duration)
+		-> Result<(), TimeoutIoError>
+	{
+		// Compute the deadline
+		with_deadline(timeout, |deadline| {
+			// Loop until buffer is filled completely
+			while *pos < buf.len() {
+				// Read data (opportunistically, because data is often already available) and wait
+				// for a read-event only if `self` is not ready
+				match self.read(&mut buf[*pos..]) {
+					Ok(0) => return Err(TimeoutIoError::UnexpectedEof),
+					Ok(read) => *pos += read,
+					Err(error) => match TimeoutIoError::from(error) {
+						TimeoutIoError::TimedOut{ .. } => {
+							self.wait_for_event(EventMask::new_r(), deadline.remaining())?;
+						},
+						error if error.should_retry() => (),
+						error => return Err(error)
+					}
+				}
+			}
+			Ok(())
+		})
+	}
+	fn try_read_exact_partial(&mut self, buf: &mut[u8], pos: &mut usize, timeout: Duration)
+		-> Result<usize, TimeoutIoError>
+	{
+		// Read until `buf` is filled or EOF is hit
+		let start = std::cmp::min(*pos, buf.len());
+		match self.try_read_exact(buf, pos, timeout) {
+			Ok(_) | Err(TimeoutIoError::UnexpectedEof) => Ok(*pos - start),
+			Err(error) => Err(error)
+		}
+	}
+	fn try_read_frame<D: Decoder>(&mut self, decoder: &mut D, buf: &mut Vec<u8>, timeout: Duration)
+		-> Result<D::Item, TimeoutIoError>
+	{
+		// Compute the deadline
+		with_deadline(timeout, |deadline| {
+			// Decode the buffered data and read more data until an item is complete
+			loop {
+				if let Some((consumed, item)) = decoder.decode(buf)? {
+					buf.drain(..consumed);
+					return Ok(item)
+				}
+				match self.try_read_append(buf, usize::MAX, deadline.remaining()) {
+					Err(TimeoutIoError::GracefulEof) if !buf.is_empty() => {
+						return Err(TimeoutIoError::UnexpectedEof)
+					},
+					result => result?
+				};
+			}
+		})
+	}
+	fn drain_read(&mut self, mut on_chunk: impl FnMut(&[u8]), scratch: &mut[u8])
+		-> Result<(), TimeoutIoError>
+	{
+		// Read until `self` would block
+		if scratch.is_empty() { return Err(TimeoutIoError::InvalidInput) }
 		loop {
-			// Wait for read-event and read data
-			self.wait_for_event(EventMask::new_r(), timeout)?;
-			match self.read(&mut buf[*pos..]) {
-				Ok(0) => return Err(TimeoutIoError::UnexpectedEof),
-				Ok(read) => {
-					*pos += read;
-					return Ok(())
-				},
-				Err(error) => {
-					let error = TimeoutIoError::from(error);
-					if !error.should_retry() { return Err(error) }
+			match self.read(scratch) {
+				Ok(0) => return Err(TimeoutIoError::GracefulEof),
+				Ok(read) => on_chunk(&scratch[..read]),
+				Err(error) => match TimeoutIoError::from(error) {
+					TimeoutIoError::TimedOut{ .. } => return Ok(()),
+					TimeoutIoError::InterruptedSyscall => (),
+					error => return Err(error)
 				}
 			}
 		}
 	}
-	fn try_read_exact(&mut self, buf: &mut[u8], pos: &mut usize, timeout: Duration)
+	fn try_read_at_least(&mut self, buf: &mut[u8], pos: &mut usize, min: usize, timeout: Duration)
 		-> Result<(), TimeoutIoError>
 	{
 		// Compute the deadline
-		let deadline = Instant::now() + timeout;
-		
+		let min = std::cmp::min(min, buf.len());
+		with_deadline(timeout, |deadline| {
+			// Loop until at least `min` bytes have been read
+			while *pos < min {
+				// Read as much data as available and wait for a read-event only if `self` is not
+				// ready
+				match self.read(&mut buf[*pos..]) {
+					Ok(0) => return Err(TimeoutIoError::UnexpectedEof),
+					Ok(read) => *pos += read,
+					Err(error) => match TimeoutIoError::from(error) {
+						TimeoutIoError::TimedOut{ .. } => {
+							self.wait_for_event(EventMask::new_r(), deadline.remaining())?;
+						},
+						error if error.should_retry() => (),
+						error => return Err(error)
+					}
+				}
+			}
+			Ok(())
+		})
+	}
+	fn try_read_exact_into(&mut self, targets: &mut[&mut[u8]], pos: &mut usize, timeout: Duration)
+		-> Result<(), TimeoutIoError>
+	{
+		// Compute the deadline
+		with_deadline(timeout, |deadline| {
+			// Fill the targets in order
+			let mut offset = 0;
+			for target in targets.iter_mut() {
+				// Skip already filled targets and compute the position within the target
+				let len = target.len();
+				if *pos >= offset + len {
+					offset += len;
+					continue
+				}
+				let mut target_pos = pos.saturating_sub(offset);
+				
+				// Read the target and update `pos` even on errors
+				let result = self.try_read_exact(target, &mut target_pos, deadline.remaining());
+				*pos = offset + target_pos;
+				result?;
+				offset += len;
+			}
+			Ok(())
+		})
+	}
+	fn try_read_vectored_exact(&mut self, bufs: &mut[IoSliceMut], pos: &mut usize,
+		timeout: Duration) -> Result<(), TimeoutIoError>
+	{
+		// Compute the deadline
+		with_deadline(timeout, |deadline| {
+			// Loop until all slices are filled completely
+			while bufs.iter().any(|buf| !buf.is_empty()) {
+				// Read data (opportunistically) and wait for a read-event only if `self` is not
+				// ready
+				match self.read_vectored(bufs) {
+					Ok(0) => return Err(TimeoutIoError::UnexpectedEof),
+					Ok(read) => {
+						// Advance the slices in place
+						*pos += read;
+						let mut remaining = read;
+						for buf in bufs.iter_mut() {
+							let advance = min(buf.len(), remaining);
+							buf.advance(advance);
+							remaining -= advance;
+						}
+					},
+					Err(error) => match TimeoutIoError::from(error) {
+						TimeoutIoError::TimedOut{ .. } => {
+							self.wait_for_event(EventMask::new_r(), deadline.remaining())?;
+						},
+						error if error.should_retry() => (),
+						error => return Err(error)
+					}
+				}
+			}
+			Ok(())
+		})
+	}
+	fn try_read_string(&mut self, len: usize, timeout: Duration) -> Result<String, TimeoutIoError> {
+		// Read the bytes and validate them
+		let mut buf = vec![0; len];
+		self.try_read_exact(&mut buf, &mut 0, timeout)?;
+		String::from_utf8(buf).map_err(|_| TimeoutIoError::InvalidInput)
+	}
+	fn try_read_varint(&mut self, timeout: Duration) -> Result<u64, TimeoutIoError> {
+		// Compute deadline
+		with_deadline(timeout, |deadline| {
+			// Read up to 10 groups of 7 bits
+			let mut value = 0u64;
+			for index in 0..VARINT_MAX_LEN {
+				// Read the next byte
+				let mut byte = [0];
+				match self.try_read_exact(&mut byte, &mut 0, deadline.remaining()) {
+					Err(TimeoutIoError::UnexpectedEof) if index == 0 => {
+						return Err(TimeoutIoError::GracefulEof)
+					},
+					result => result?
+				}
+				
+				// Append the group (the 10th group may only contain the most significant bit)
+				let group = u64::from(byte[0] & 0x7f);
+				if index == VARINT_MAX_LEN - 1 && group > 1 {
+					return Err(TimeoutIoError::InvalidInput)
+				}
+				value |= group << (7 * index);
+				if byte[0] & 0x80 == 0 { return Ok(value) }
+			}
+			Err(TimeoutIoError::InvalidInput)
+		})
+	}
+	fn try_read_exact_min_rate(&mut self, buf: &mut[u8], pos: &mut usize, min_bytes_per_sec: u64,
+		timeout: Duration) -> Result<(), TimeoutIoError>
+	{
+		// Compute the deadline and the minimum amount of bytes per window
+		with_deadline(timeout, |deadline| {
+			let start = Instant::now();
+			let floor = u128::from(min_bytes_per_sec) * RATE_WINDOW.as_millis() / 1000;
+			let mut samples: VecDeque<(Instant, usize)> = VecDeque::new();
+			
+			// Loop until buffer is filled completely
+			while *pos < buf.len() {
+				// Drop the samples that left the window and check the rate (once a full window
+				// elapsed)
+				let now = Instant::now();
+				let expired = |(time, _): &(Instant, usize)| now - *time > RATE_WINDOW;
+				while samples.front().is_some_and(expired) {
+					samples.pop_front();
+				}
+				let received: usize = samples.iter().map(|(_, read)| read).sum();
+				if now.duration_since(start) >= RATE_WINDOW && (received as u128) < floor {
+					return Err(TimeoutIoError::TooSlow)
+				}
+				
+				// Read data and wait for a read-event if `self` is not ready (the wait is capped so
+				// that we check the rate regularly)
+				match self.read(&mut buf[*pos..]) {
+					Ok(0) => return Err(TimeoutIoError::UnexpectedEof),
+					Ok(read) => {
+						*pos += read;
+						samples.push_back((Instant::now(), read));
+					},
+					Err(error) => match TimeoutIoError::from(error) {
+						TimeoutIoError::TimedOut{ .. } => {
+							let remaining = deadline.remaining();
+							let wait = min(remaining, RATE_CHECK_INTERVAL);
+							match self.wait_for_event(EventMask::new_r(), wait) {
+								Err(e) if e.is_timeout() && remaining > RATE_CHECK_INTERVAL => (),
+								result => { result?; }
+							}
+						},
+						error if error.should_retry() => (),
+						error => return Err(error)
+					}
+				}
+			}
+			Ok(())
+		})
+	}
+	fn try_read_exact_shared(&mut self, buf: &mut[u8], pos: &mut usize, deadline: &SharedDeadline)
+		-> Result<(), TimeoutIoError>
+	{
 		// Loop until buffer is filled completely
 		while *pos < buf.len() {
-			// Wait for read-event and read data
-			self.wait_for_event(EventMask::new_r(), deadline.remaining())?;
-			match self.read(&mut buf[*pos..]) {
-				Ok(0) => return Err(TimeoutIoError::UnexpectedEof),
-				Ok(read) => *pos += read,
-				Err(error) => {
-					let error = TimeoutIoError::from(error);
-					if !error.should_retry() { return Err(error) }
-				}
+			match self.try_read(buf, pos, min(deadline.remaining(), SharedDeadline::GRANULARITY)) {
+				Err(error) if error.is_timeout() && !deadline.remaining().is_zero() => (),
+				Err(TimeoutIoError::GracefulEof) => return Err(TimeoutIoError::UnexpectedEof),
+				result => result?
 			}
 		}
 		Ok(())
@@ -95,19 +683,120 @@ impl<T: Read + WaitForEvent> Reader for T {
 		-> Result<bool, TimeoutIoError>
 	{
 		// Compute deadline
-		let deadline = Instant::now() + timeout;
+		with_deadline(timeout, |deadline| {
+			// Loop until `data` has been filled
+			while *pos < buf.len() {
+				// Read next byte
+				let next = *pos + 1;
+				self.try_read_exact(&mut buf[..next], pos, deadline.remaining())?;
+				
+				// Check for pattern
+				if *pos >= pat.len() && &buf[*pos - pat.len() .. *pos] == pat {
+					return Ok(true)
+				}
+			}
+			Ok(false)
+		})
+	}
+	fn try_read_until_unescaped(&mut self, buf: &mut[u8], pos: &mut usize, delim: u8, escape: u8,
+		timeout: Duration) -> Result<bool, TimeoutIoError>
+	{
+		// Validate the input and compute deadline
+		if delim == escape { return Err(TimeoutIoError::InvalidInput) }
+		with_deadline(timeout, |deadline| {
+			// Loop until `data` has been filled
+			while *pos < buf.len() {
+				// Read next byte
+				let next = *pos + 1;
+				self.try_read_exact(&mut buf[..next], pos, deadline.remaining())?;
+				
+				// Check for an unescaped delimiter (i.e. an even amount of preceding escapes)
+				if buf[*pos - 1] == delim {
+					let escaped = buf[..*pos - 1].iter().rev().take_while(|b| **b == escape);
+					let escapes = escaped.count();
+					if escapes % 2 == 0 { return Ok(true) }
+				}
+			}
+			Ok(false)
+		})
+	}
+	fn try_read_until_limit(&mut self, buf: &mut Vec<u8>, pat: &[u8], limit: usize,
+		timeout: Duration) -> Result<Option<usize>, TimeoutIoError>
+	{
+		// Compute deadline (the existing bytes in `buf` are scanned completely on entry because
+		// they may already contain a match; e.g. pipelined data from a previous call)
+		with_deadline(timeout, |deadline| {
+			let overlap = pat.len().saturating_sub(1);
+			let mut scan_from = 0;
+			
+			loop {
+				// Check for pattern and move the scan window
+				if let Some(end) = find_pattern(&buf[scan_from..], pat) {
+					return Ok(Some(scan_from + end))
+				}
+				scan_from = buf.len().saturating_sub(overlap).max(scan_from);
+				if buf.len() >= limit { return Ok(None) }
+				
+				// Read the next chunk
+				let mut pos = buf.len();
+				buf.resize(min(pos + CHUNK_SIZE, limit), 0);
+				let result = self.try_read(buf, &mut pos, deadline.remaining());
+				buf.truncate(pos);
+				match result {
+					Err(TimeoutIoError::GracefulEof) => return Err(TimeoutIoError::UnexpectedEof),
+					result => result?
+				}
+			}
+		})
+	}
+}
+
+
+/// Reads exactly `a_buf.len()` bytes from `a` and `b_buf.len()` bytes from `b` – in whatever order
+/// the data arrives – until both buffers are filled or `timeout` is exceeded (e.g. for a protocol
+/// that multiplexes a control and a data channel)
+///
+/// _Info: Both streams are waited on with a single `SelectSet`; after each wait, every stream that
+/// became readable is read once (`a` before `b`). So a stream that is continuously readable can't
+/// starve the other one, and a stream whose buffer is full is not waited on anymore._
+///
+/// _Note: Since there are no positions, the contents of the buffers are unspecified if an error
+/// occurs (e.g. `TimedOut` or `GracefulEof` on either stream)._
+///
+/// __Warning: `a` and `b` must be non-blocking or the function won't work as expected__
+pub fn read_exact_from_either<S: Read + RawFd>(a: &mut S, a_buf: &mut[u8], b: &mut S,
+	b_buf: &mut[u8], timeout: Duration) -> Result<(), TimeoutIoError>
+{
+	// Compute deadline
+	with_deadline(timeout, |deadline| {
+		let (mut a_pos, mut b_pos) = (0, 0);
 		
-		// Loop until `data` has been filled
-		while *pos < buf.len() {
-			// Read next byte
-			let next = *pos + 1;
-			self.try_read_exact(&mut buf[..next], pos, deadline.remaining())?;
+		// Loop until both buffers are filled
+		while a_pos < a_buf.len() || b_pos < b_buf.len() {
+			// Wait for the streams that still need data
+			let (a_ready, b_ready) = {
+				let mut set = SelectSet::new();
+				if a_pos < a_buf.len() { set.push(&*a, EventMask::new_r()) }
+				if b_pos < b_buf.len() { set.push(&*b, EventMask::new_r()) }
+				let ready = set.select(deadline.remaining())?;
+				if ready.is_empty() { return Err(TIMED_OUT) }
+				(
+					ready.iter().any(|(handle, _)| ptr::eq(*handle, &*a)),
+					ready.iter().any(|(handle, _)| ptr::eq(*handle, &*b))
+				)
+			};
 			
-			// Check for pattern
-			if *pos >= pat.len() && &buf[*pos - pat.len() .. *pos] == pat {
-				return Ok(true)
+			// Perform one non-blocking read on each ready stream (spurious wakeups are ignored)
+			for (ready, stream, buf, pos) in [(a_ready, &mut *a, &mut *a_buf, &mut a_pos),
+				(b_ready, &mut *b, &mut *b_buf, &mut b_pos)]
+			{
+				if !ready { continue }
+				match stream.try_read(buf, pos, Duration::from_secs(0)) {
+					Err(TimeoutIoError::TimedOut{ .. }) => (),
+					result => result?
+				}
 			}
 		}
-		Ok(false)
-	}
-}
\ No newline at end of file
+		Ok(())
+	})
+}
@@ -0,0 +1,75 @@
+use crate::{ TimeoutIoError, WaitForEvent, EventMask, Waker, WokenOr };
+use std::{
+	time::Duration,
+	io::{ self, Read, IoSliceMut }
+};
+
+
+/// An adapter that reads from `R` but waits for readiness on a separate `W` (e.g. a TLS-session
+/// whose readiness must be queried on the underlying socket); this makes `Reader` available for
+/// `Read`-types that do not expose a raw fd/socket themselves
+///
+/// _Info: All `Reader`-functions wait on `W` and read from `R`; `set_blocking_mode` is applied to
+/// `W`._
+///
+/// __Warning: `W` must reflect the readiness of `R`: a read-event on `W` must mean that `R` can
+/// make progress, and `R` must return `WouldBlock` instead of blocking if it cannot. If `R`
+/// buffers data internally (so that it is readable although `W` is not), the `Reader`-functions
+/// may time out although data is available.__
+#[derive(Debug)]
+pub struct ReaderOn<R, W> {
+	read: R,
+	wait: W
+}
+impl<R: Read, W: WaitForEvent> ReaderOn<R, W> {
+	/// Creates a new adapter that reads from `read` and waits for readiness on `wait`
+	pub fn new(read: R, wait: W) -> Self {
+		Self{ read, wait }
+	}
+	
+	/// References to the read- and the readiness-source
+	pub fn get_ref(&self) -> (&R, &W) {
+		(&self.read, &self.wait)
+	}
+	/// Mutable references to the read- and the readiness-source
+	pub fn get_mut(&mut self) -> (&mut R, &mut W) {
+		(&mut self.read, &mut self.wait)
+	}
+	/// Returns the read- and the readiness-source
+	pub fn into_inner(self) -> (R, W) {
+		(self.read, self.wait)
+	}
+}
+impl<R: Read, W> Read for ReaderOn<R, W> {
+	fn read(&mut self, buf: &mut[u8]) -> io::Result<usize> {
+		self.read.read(buf)
+	}
+	fn read_vectored(&mut self, bufs: &mut[IoSliceMut]) -> io::Result<usize> {
+		self.read.read_vectored(bufs)
+	}
+}
+impl<R, W: WaitForEvent> WaitForEvent for ReaderOn<R, W> {
+	fn wait_for_event(&self, event: EventMask, timeout: Duration)
+		-> Result<EventMask, TimeoutIoError>
+	{
+		self.wait.wait_for_event(event, timeout)
+	}
+	fn wait_for_event_precise(&self, event: EventMask, timeout: Duration)
+		-> Result<EventMask, TimeoutIoError>
+	{
+		self.wait.wait_for_event_precise(event, timeout)
+	}
+	fn wait_for_event_multi(&self, events: &[EventMask], timeout: Duration)
+		-> Result<Vec<EventMask>, TimeoutIoError>
+	{
+		self.wait.wait_for_event_multi(events, timeout)
+	}
+	fn wait_for_event_or_wake(&self, event: EventMask, waker: &Waker, timeout: Duration)
+		-> Result<WokenOr<EventMask>, TimeoutIoError>
+	{
+		self.wait.wait_for_event_or_wake(event, waker, timeout)
+	}
+	fn set_blocking_mode(&self, make_blocking: bool) -> Result<(), TimeoutIoError> {
+		self.wait.set_blocking_mode(make_blocking)
+	}
+}
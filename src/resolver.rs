@@ -1,63 +1,314 @@
-use super::{ TimeoutIoError, InstantExt };
+use super::{
+	TimeoutIoError, InstantExt, deadline_after, with_deadline, TIMED_OUT,
+	backoff::retry_with_backoff
+};
 use std::{
-	thread, str::FromStr,
-	net::{ SocketAddr, ToSocketAddrs },
+	io, thread, str::FromStr,
+	fmt::{ self, Display, Formatter },
+	net::{ SocketAddr, ToSocketAddrs, IpAddr, Ipv4Addr, Ipv6Addr },
 	time::{ Duration, Instant },
-	sync::mpsc::{ self, RecvTimeoutError }
+	sync::mpsc
 };
 
 
+/// A validated `host:port`-pair (e.g. `"localhost:80"`, `"127.0.0.1:80"` or `"[::1]:80"`)
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct HostPort {
+	/// The host name or IP-address (without brackets)
+	pub host: String,
+	/// The port
+	pub port: u16
+}
+impl FromStr for HostPort {
+	type Err = TimeoutIoError;
+	
+	/// Parses a `host:port`-pair; IPv6-addresses must be enclosed in brackets (e.g. `"[::1]:80"`)
+	///
+	/// Returns `InvalidInput` if the port is missing or invalid, if the host is empty or if an
+	/// IPv6-address is not enclosed in brackets.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		// Split host and port
+		let (host, port) = match s.strip_prefix('[') {
+			Some(bracketed) => {
+				let (host, rest) = bracketed.split_once(']').ok_or(TimeoutIoError::InvalidInput)?;
+				(host, rest.strip_prefix(':').ok_or(TimeoutIoError::InvalidInput)?)
+			},
+			None => {
+				let (host, port) = s.rsplit_once(':').ok_or(TimeoutIoError::InvalidInput)?;
+				if host.contains(':') { return Err(TimeoutIoError::InvalidInput) }
+				(host, port)
+			}
+		};
+		
+		// Validate host and port
+		if host.is_empty() || host.chars().any(char::is_whitespace) {
+			return Err(TimeoutIoError::InvalidInput)
+		}
+		let port = port.parse().map_err(|_| TimeoutIoError::InvalidInput)?;
+		Ok(Self{ host: host.to_string(), port })
+	}
+}
+impl Display for HostPort {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match self.host.contains(':') {
+			true => write!(f, "[{}]:{}", self.host, self.port),
+			false => write!(f, "{}:{}", self.host, self.port)
+		}
+	}
+}
+
+
+/// An IP-network in CIDR-notation (e.g. "10.0.0.0/8" or "2001:db8::/32")
+///
+/// _Note: Host-bits in the address are cleared during parsing (i.e. "10.1.2.3/8" becomes
+/// "10.0.0.0/8")._
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct IpCidr {
+	address: IpAddr,
+	prefix_len: u8
+}
+impl IpCidr {
+	/// The network address (with all host-bits cleared)
+	pub fn address(&self) -> IpAddr {
+		self.address
+	}
+	/// The prefix length (i.e. the amount of network-bits)
+	pub fn prefix_len(&self) -> u8 {
+		self.prefix_len
+	}
+	
+	/// Checks if `address` is within this network
+	///
+	/// _Note: IPv4-addresses are never contained in an IPv6-network and vice versa (this includes
+	/// IPv4-mapped IPv6-addresses like "::ffff:10.0.0.1")._
+	pub fn contains(&self, address: &IpAddr) -> bool {
+		match (self.address, address) {
+			(IpAddr::V4(network), IpAddr::V4(address)) => {
+				let mask = Self::mask(self.prefix_len, 32) as u32;
+				u32::from(*address) & mask == u32::from(network)
+			},
+			(IpAddr::V6(network), IpAddr::V6(address)) => {
+				let mask = Self::mask(self.prefix_len, 128);
+				u128::from(*address) & mask == u128::from(network)
+			},
+			_ => false
+		}
+	}
+	
+	/// Creates a mask with the upper `prefix_len` of `bits` bits set
+	fn mask(prefix_len: u8, bits: u32) -> u128 {
+		match prefix_len {
+			0 => 0,
+			prefix_len => (u128::MAX << (128 - prefix_len as u32)) >> (128 - bits)
+		}
+	}
+}
+impl FromStr for IpCidr {
+	type Err = TimeoutIoError;
+	
+	/// Parses an `address/prefix_len`-pair
+	///
+	/// Returns `InvalidInput` if the address or prefix length is missing or invalid or if the prefix
+	/// length exceeds the address family's bit-length (32 for IPv4, 128 for IPv6).
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		// Split and parse address and prefix length
+		let (address, prefix_len) = s.split_once('/').ok_or(TimeoutIoError::InvalidInput)?;
+		let address = IpAddr::from_str(address).map_err(|_| TimeoutIoError::InvalidInput)?;
+		if prefix_len.is_empty() || !prefix_len.bytes().all(|b| b.is_ascii_digit()) {
+			return Err(TimeoutIoError::InvalidInput)
+		}
+		let prefix_len: u8 = prefix_len.parse().map_err(|_| TimeoutIoError::InvalidInput)?;
+		
+		// Validate the prefix length and clear the host-bits
+		let address = match address {
+			IpAddr::V4(address) if prefix_len <= 32 => {
+				let mask = Self::mask(prefix_len, 32) as u32;
+				IpAddr::V4(Ipv4Addr::from(u32::from(address) & mask))
+			},
+			IpAddr::V6(address) if prefix_len <= 128 => {
+				let mask = Self::mask(prefix_len, 128);
+				IpAddr::V6(Ipv6Addr::from(u128::from(address) & mask))
+			},
+			_ => return Err(TimeoutIoError::InvalidInput)
+		};
+		Ok(Self{ address, prefix_len })
+	}
+}
+impl Display for IpCidr {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		write!(f, "{}/{}", self.address, self.prefix_len)
+	}
+}
+
+
+/// Options for a DNS-resolution with per-attempt timeouts (see `DnsResolvable::try_dns_resolve_ex`)
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct DnsOptions {
+	/// The time after which a single resolution attempt is abandoned
+	pub attempt_timeout: Duration,
+	/// The time after which the entire resolution fails with `TimedOut`
+	pub total_timeout: Duration,
+	/// The maximum amount of attempts (`0` is treated like `1`)
+	pub max_attempts: usize
+}
+
+
+/// The addresses resolved by `DnsResolvable::try_dns_resolve_all`
+///
+/// _Info: This implements `ToSocketAddrs` by yielding the cached addresses – i.e. no further DNS
+/// resolution (and no blocking) happens if you pass it to e.g. `TcpStream::connect`._
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ResolvedAddrs {
+	addrs: Vec<SocketAddr>
+}
+impl ResolvedAddrs {
+	/// The resolved addresses (e.g. for `connect_any`)
+	pub fn addrs(&self) -> &[SocketAddr] {
+		&self.addrs
+	}
+	/// Returns the resolved addresses
+	pub fn into_inner(self) -> Vec<SocketAddr> {
+		self.addrs
+	}
+}
+impl ToSocketAddrs for ResolvedAddrs {
+	type Iter = std::vec::IntoIter<SocketAddr>;
+	fn to_socket_addrs(&self) -> io::Result<Self::Iter> {
+		Ok(self.addrs.clone().into_iter())
+	}
+}
+
+
 /// A trait for elements which contain a DNS-resolvable address
 pub trait DnsResolvable {
 	/// Tries to resolve a domain-name or IP-address until `timeout` is exceeded
 	///
 	/// _Info: If you want to resolve an address like "localhost" or "crates.io" you __must__
-	/// include the port number like this: "localhost:80" or "crates.io:443"_
+	/// include the port number like this: "localhost:80" or "crates.io:443"; the address is
+	/// validated up front (see `HostPort`) and `InvalidInput` is returned immediately if it is
+	/// malformed._
+	///
+	/// _Info: Retryable resolver errors are retried with an exponential backoff (10ms initially, up
+	/// to 1s)._
 	///
 	/// __Warning: because `getaddrinfo` only provides a synchronous API, we have to resolve in a
 	/// background thread. This means the background thread may outlive this call until the OS'
 	/// `connect`-timeout is reached.__
 	fn try_dns_resolve(&self, timeout: Duration) -> Result<SocketAddr, TimeoutIoError>;
+	/// Like `try_dns_resolve`, but returns _all_ resolved addresses (in the resolver's order; at
+	/// least one)
+	///
+	/// _Info: The result implements `ToSocketAddrs`, so you can pass it straight into
+	/// `TcpStream::connect` or `UdpSocket::bind` without resolving again (see `ResolvedAddrs`)._
+	///
+	/// __Warning: see `try_dns_resolve` for the background thread.__
+	fn try_dns_resolve_all(&self, timeout: Duration) -> Result<ResolvedAddrs, TimeoutIoError>;
+	/// Tries to resolve a domain-name or IP-address with up to `options.max_attempts` attempts,
+	/// where each attempt is abandoned after `options.attempt_timeout` and the entire resolution
+	/// fails after `options.total_timeout` (see `resolve_with_options`)
+	///
+	/// This gives finer control for flaky resolvers than `try_dns_resolve`: a hanging
+	/// `getaddrinfo`-call does not consume the entire timeout.
+	///
+	/// __Warning: an abandoned attempt cannot be cancelled; it's background thread lingers until
+	/// `getaddrinfo` returns. So in the worst case, up to `options.max_attempts` threads per call
+	/// may outlive this call.__
+	fn try_dns_resolve_ex(&self, options: DnsOptions) -> Result<SocketAddr, TimeoutIoError>;
 }
 impl<T: ToString> DnsResolvable for T {
 	fn try_dns_resolve(&self, timeout: Duration) -> Result<SocketAddr, TimeoutIoError> {
-		// Create address and channels
-		let address = self.to_string();
+		Ok(self.try_dns_resolve_all(timeout)?.addrs[0])
+	}
+	fn try_dns_resolve_all(&self, timeout: Duration) -> Result<ResolvedAddrs, TimeoutIoError> {
+		// Validate address and create channels
+		let address = HostPort::from_str(&self.to_string())?.to_string();
 		let (sender, receiver) = mpsc::channel();
 		
 		// Run resolver task
-		enum Msg{ Ping, Result(Result<SocketAddr, TimeoutIoError>) }
-		thread::spawn(move || {
-			let result = loop {
-				// Check for timeout
-				if sender.send(Msg::Ping).is_err() { return }
-				
-				// Resolve name
-				match address.as_str().to_socket_addrs() {
-					Ok(mut addresses) => break match addresses.next() {
-						Some(address) => Ok(address),
-						None => Err(TimeoutIoError::NotFound)
-					},
-					Err(error) => {
-						let error = TimeoutIoError::from(error);
-						if !error.should_retry() { break Err(error) }
+		enum Msg{ Ping, Result(Result<ResolvedAddrs, TimeoutIoError>) }
+		with_deadline(timeout, |deadline| {
+			thread::spawn(move || {
+				// Resolve name (and back off between retries to avoid hammering the resolver)
+				let result = retry_with_backoff(deadline.remaining(), || {
+					// Check for timeout
+					if sender.send(Msg::Ping).is_err() {
+						return Err(TimeoutIoError::ConnectionLost{ source: None })
+					}
+					
+					let addrs: Vec<SocketAddr> = address.as_str().to_socket_addrs()?.collect();
+					match addrs.is_empty() {
+						true => Err(TimeoutIoError::NotFound),
+						false => Ok(ResolvedAddrs{ addrs })
 					}
-				};
-			};
-			let _ = sender.send(Msg::Result(result));
-		});
+				});
+				let _ = sender.send(Msg::Result(result));
+			});
+			
+			// Wait for result (a disconnected channel means that the resolver thread crashed)
+			'receive_loop: loop {
+				match receiver.recv_timeout(deadline.remaining())? {
+					Msg::Ping => continue 'receive_loop,
+					Msg::Result(result) => return result
+				}
+			}
+		})
+	}
+	fn try_dns_resolve_ex(&self, options: DnsOptions) -> Result<SocketAddr, TimeoutIoError> {
+		let address = HostPort::from_str(&self.to_string())?.to_string();
+		resolve_with_options(options, move || {
+			let mut addresses = address.as_str().to_socket_addrs()?;
+			addresses.next().ok_or(TimeoutIoError::NotFound)
+		})
+	}
+}
+
+
+/// Calls `resolve` in a background thread for up to `options.max_attempts` attempts until it
+/// succeeds or returns a non-retryable error; each attempt is abandoned after
+/// `options.attempt_timeout` and the entire resolution fails with `TimedOut` after
+/// `options.total_timeout`
+///
+/// This is the machinery behind `DnsResolvable::try_dns_resolve_ex`; you can use it with a custom
+/// resolver (e.g. a DNS-over-HTTPS-client that has only a blocking API).
+///
+/// _Info: A new attempt is started immediately after the previous attempt failed or was
+/// abandoned. If all attempts failed, the error of the last attempt (or `TimedOut` if it was
+/// abandoned) is returned._
+///
+/// __Warning: an abandoned attempt cannot be cancelled; it's background thread lingers until
+/// `resolve` returns (the result is discarded then).__
+pub fn resolve_with_options<F>(options: DnsOptions, resolve: F)
+	-> Result<SocketAddr, TimeoutIoError> where F: Fn() -> Result<SocketAddr, TimeoutIoError> + Clone + Send + 'static
+{
+	// Compute the deadline
+	let (start, limit) = (Instant::now(), options.total_timeout);
+	let deadline = deadline_after(limit);
+	
+	// Start the attempts
+	let mut last_error = TIMED_OUT;
+	for _ in 0..std::cmp::max(options.max_attempts, 1) {
+		// Check for timeout
+		let remaining = deadline.remaining();
+		if remaining.is_zero() { return Err(TimeoutIoError::timed_out(start, limit)) }
+		
+		// Run the attempt in the background
+		let (sender, receiver) = mpsc::channel();
+		let resolve = resolve.clone();
+		thread::spawn(move || { let _ = sender.send(resolve()); });
 		
-		// Wait for result
-		let deadline = Instant::now() + timeout;
-		'receive_loop: loop {
-			match receiver.recv_timeout(deadline.remaining()) {
-				Ok(Msg::Ping) => continue 'receive_loop,
-				Ok(Msg::Result(result)) => return result,
-				Err(RecvTimeoutError::Timeout) => return Err(TimeoutIoError::TimedOut),
-				Err(_) => panic!("Resolver thread crashed without result")
+		// Wait for the result (a disconnected channel means that the resolver thread crashed)
+		let attempt_timeout = std::cmp::min(options.attempt_timeout, remaining);
+		last_error = match receiver.recv_timeout(attempt_timeout) {
+			Ok(Ok(address)) => return Ok(address),
+			Ok(Err(error)) if !error.should_retry() => return Err(error),
+			Ok(Err(error)) => error,
+			Err(error) => match TimeoutIoError::from(error) {
+				TimeoutIoError::TimedOut{ .. } => TimeoutIoError::timed_out(start, limit),
+				error => return Err(error)
 			}
-		}
+		};
 	}
+	Err(last_error)
 }
 
 
@@ -67,6 +318,10 @@ pub trait IpParseable {
 	///
 	/// Returns either __the parsed address__ or a corresponding `IoError`
 	fn parse_ip(&self) -> Result<SocketAddr, TimeoutIoError>;
+	/// Parses an IP-network in CIDR-notation (e.g. "10.0.0.0/8" or "2001:db8::/32"; see `IpCidr`)
+	///
+	/// Returns either __the parsed network__ or `InvalidInput`
+	fn parse_cidr(&self) -> Result<IpCidr, TimeoutIoError>;
 }
 impl<T: AsRef<str>> IpParseable for T {
 	fn parse_ip(&self) -> Result<SocketAddr, TimeoutIoError> {
@@ -75,6 +330,9 @@ impl<T: AsRef<str>> IpParseable for T {
 			Err(_) => Err(TimeoutIoError::InvalidInput)
 		}
 	}
+	fn parse_cidr(&self) -> Result<IpCidr, TimeoutIoError> {
+		IpCidr::from_str(self.as_ref())
+	}
 }
 
 
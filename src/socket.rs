@@ -0,0 +1,219 @@
+use crate::{ TimeoutIoError, RawFd, Writer, event::libselect };
+use std::{
+	io::{ self, Write },
+	time::Duration,
+	net::{ TcpStream, UdpSocket, SocketAddr }
+};
+
+
+/// A trait for configuring socket options
+pub trait SocketOptions {
+	/// Configures `SO_LINGER` to define the behavior of `close`:
+	///  - `None` disables lingering: `close` returns immediately and the OS tries to send the
+	///    remaining data in the background (the default)
+	///  - `Some(Duration::from_secs(0))` resets the connection (RST) on `close` and discards all
+	///    unsent data
+	///  - `Some(timeout)` makes `close` block until the remaining data has been sent or `timeout`
+	///    has expired (sub-second timeouts are rounded up to whole seconds)
+	fn set_linger_t(&self, linger: Option<Duration>) -> Result<(), TimeoutIoError>;
+	/// Enables or disables `SO_KEEPALIVE` (i.e. whether the OS periodically probes idle
+	/// connections to detect dead peers)
+	fn set_keepalive_t(&self, keepalive: bool) -> Result<(), TimeoutIoError>;
+	/// Enables or disables `TCP_NODELAY` (i.e. disables or enables Nagle's algorithm)
+	fn set_nodelay_t(&self, nodelay: bool) -> Result<(), TimeoutIoError>;
+	/// "Corks" the socket while `f` is executed, so that the data that `f` writes to the passed
+	/// `Corked`-writer (e.g. a header and a body written separately) is coalesced into as few
+	/// segments as possible; the socket is uncorked (and the pending data is sent) afterwards –
+	/// even if `f` fails
+	///
+	/// _Info: On Linux, this uses `TCP_CORK`; on BSDs and macOS, this uses `TCP_NOPUSH` (note that
+	/// macOS may delay the pending data until the next write after uncorking). On other platforms
+	/// (e.g. windows), the writes are buffered in memory and written at once after `f` returned;
+	/// `flush_timeout` limits this final write (it is ignored if the platform supports corking)._
+	///
+	/// _Note: Unlike disabling `TCP_NODELAY`, this affects only the writes within `f`._
+	fn with_cork(&self, flush_timeout: Duration,
+		f: impl FnOnce(&mut Corked) -> Result<(), TimeoutIoError>) -> Result<(), TimeoutIoError>
+		where Self: Sized;
+	
+	/// Like `peer_addr`, but returns a `TimeoutIoError` (e.g. to avoid mixing `io::Error` and
+	/// `TimeoutIoError` in one function)
+	fn peer_addr_t(&self) -> Result<SocketAddr, TimeoutIoError>;
+	/// Like `local_addr`, but returns a `TimeoutIoError` (e.g. to avoid mixing `io::Error` and
+	/// `TimeoutIoError` in one function)
+	fn local_addr_t(&self) -> Result<SocketAddr, TimeoutIoError>;
+}
+impl SocketOptions for TcpStream {
+	fn set_linger_t(&self, linger: Option<Duration>) -> Result<(), TimeoutIoError> {
+		// Compute the linger time in seconds
+		let (enabled, seconds) = match linger {
+			Some(linger) if linger.subsec_nanos() > 0 => (1, linger.as_secs().saturating_add(1)),
+			Some(linger) => (1, linger.as_secs()),
+			None => (0, 0)
+		};
+		let seconds = std::cmp::min(seconds, i32::MAX as u64) as u32;
+		
+		// Set the option
+		match unsafe{ libselect::set_linger(self.raw_fd(), enabled, seconds) } {
+			0 => Ok(()),
+			e => Err(io::Error::from_raw_os_error(e).into())
+		}
+	}
+	
+	fn set_keepalive_t(&self, keepalive: bool) -> Result<(), TimeoutIoError> {
+		match unsafe{ libselect::set_keepalive(self.raw_fd(), if keepalive { 1 } else { 0 }) } {
+			0 => Ok(()),
+			e => Err(io::Error::from_raw_os_error(e).into())
+		}
+	}
+	fn set_nodelay_t(&self, nodelay: bool) -> Result<(), TimeoutIoError> {
+		Ok(self.set_nodelay(nodelay)?)
+	}
+	fn with_cork(&self, flush_timeout: Duration,
+		f: impl FnOnce(&mut Corked) -> Result<(), TimeoutIoError>) -> Result<(), TimeoutIoError>
+	{
+		// Cork the socket (or buffer the writes if the platform does not support corking)
+		let mut corked = Corked{ stream: self, buf: Vec::new(), buffered: cfg!(not(unix)) };
+		#[cfg(unix)]
+		set_cork(self, true)?;
+		
+		// Execute `f`, write the buffered data and uncork the socket
+		let result = f(&mut corked);
+		let flushed = corked.try_flush(flush_timeout);
+		#[cfg(unix)]
+		let flushed = flushed.and(set_cork(self, false));
+		result.and(flushed)
+	}
+	
+	fn peer_addr_t(&self) -> Result<SocketAddr, TimeoutIoError> {
+		Ok(self.peer_addr()?)
+	}
+	fn local_addr_t(&self) -> Result<SocketAddr, TimeoutIoError> {
+		Ok(self.local_addr()?)
+	}
+}
+
+
+/// The writer that is passed to the closure of `SocketOptions::with_cork`
+///
+/// _Info: On platforms without corking, the written data is buffered in memory; so writes never
+/// block there and the data is written when `with_cork` flushes the writer._
+pub struct Corked<'a> {
+	stream: &'a TcpStream,
+	/// The data that has not been written yet (only used if `buffered` is set)
+	buf: Vec<u8>,
+	buffered: bool
+}
+impl Write for Corked<'_> {
+	fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+		match self.buffered {
+			true => {
+				self.buf.extend_from_slice(data);
+				Ok(data.len())
+			},
+			false => self.stream.write(data)
+		}
+	}
+	fn flush(&mut self) -> io::Result<()> {
+		// Write the buffered data
+		while !self.buf.is_empty() {
+			match self.stream.write(&self.buf)? {
+				0 => return Err(io::ErrorKind::WriteZero.into()),
+				written => drop(self.buf.drain(..written))
+			}
+		}
+		self.stream.flush()
+	}
+}
+#[cfg(unix)]
+impl std::os::unix::io::AsRawFd for Corked<'_> {
+	fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+		self.stream.as_raw_fd()
+	}
+}
+#[cfg(windows)]
+impl std::os::windows::io::AsRawSocket for Corked<'_> {
+	fn as_raw_socket(&self) -> std::os::windows::io::RawSocket {
+		self.stream.as_raw_socket()
+	}
+}
+
+
+/// A trait for configuring IP-level options (e.g. to mark latency-sensitive traffic for QoS)
+pub trait IpOptions {
+	/// Like `set_ttl`, but returns a `TimeoutIoError`; `ttl` must be within `1..=255` (otherwise
+	/// `InvalidInput` is returned without touching the socket)
+	fn set_ttl_t(&self, ttl: u32) -> Result<(), TimeoutIoError>;
+	/// Sets the IPv4 type-of-service byte (`IP_TOS`); the upper 6 bits are the DSCP (i.e.
+	/// `tos = dscp << 2`, e.g. `0xB8` for "expedited forwarding") and the lower 2 bits are ECN
+	///
+	/// _Note: The OS may ignore or overwrite the ECN bits (e.g. Linux manages them itself for
+	/// TCP); and on windows, the option is usually ignored unless it is enabled by a policy. For
+	/// IPv6-sockets, use `set_traffic_class`._
+	fn set_tos(&self, tos: u8) -> Result<(), TimeoutIoError>;
+	/// Sets the IPv6 traffic class (`IPV6_TCLASS`), which has the same layout as the IPv4
+	/// type-of-service byte (see `set_tos`)
+	///
+	/// _Note: This is only available on unix; if the platform does not support the option, an
+	/// error is returned._
+	#[cfg(unix)]
+	fn set_traffic_class(&self, traffic_class: u8) -> Result<(), TimeoutIoError>;
+}
+impl IpOptions for TcpStream {
+	fn set_ttl_t(&self, ttl: u32) -> Result<(), TimeoutIoError> {
+		validate_ttl(ttl)?;
+		Ok(self.set_ttl(ttl)?)
+	}
+	fn set_tos(&self, tos: u8) -> Result<(), TimeoutIoError> {
+		set_tos(self, tos)
+	}
+	#[cfg(unix)]
+	fn set_traffic_class(&self, traffic_class: u8) -> Result<(), TimeoutIoError> {
+		set_traffic_class(self, traffic_class)
+	}
+}
+impl IpOptions for UdpSocket {
+	fn set_ttl_t(&self, ttl: u32) -> Result<(), TimeoutIoError> {
+		validate_ttl(ttl)?;
+		Ok(self.set_ttl(ttl)?)
+	}
+	fn set_tos(&self, tos: u8) -> Result<(), TimeoutIoError> {
+		set_tos(self, tos)
+	}
+	#[cfg(unix)]
+	fn set_traffic_class(&self, traffic_class: u8) -> Result<(), TimeoutIoError> {
+		set_traffic_class(self, traffic_class)
+	}
+}
+
+
+/// Validates that `ttl` is a valid IP time-to-live
+fn validate_ttl(ttl: u32) -> Result<(), TimeoutIoError> {
+	match ttl {
+		1..=255 => Ok(()),
+		_ => Err(TimeoutIoError::InvalidInput)
+	}
+}
+/// Sets the IPv4 type-of-service byte on `socket` (see `IpOptions::set_tos`)
+fn set_tos(socket: &impl RawFd, tos: u8) -> Result<(), TimeoutIoError> {
+	match unsafe{ libselect::set_tos(socket.raw_fd(), tos) } {
+		0 => Ok(()),
+		e => Err(io::Error::from_raw_os_error(e).into())
+	}
+}
+/// Sets the IPv6 traffic class on `socket` (see `IpOptions::set_traffic_class`)
+#[cfg(unix)]
+fn set_traffic_class(socket: &impl RawFd, traffic_class: u8) -> Result<(), TimeoutIoError> {
+	match unsafe{ libselect::set_traffic_class(socket.raw_fd(), traffic_class) } {
+		0 => Ok(()),
+		e => Err(io::Error::from_raw_os_error(e).into())
+	}
+}
+/// Sets the platform's cork-option on `socket` (see `SocketOptions::with_cork`)
+#[cfg(unix)]
+fn set_cork(socket: &impl RawFd, enabled: bool) -> Result<(), TimeoutIoError> {
+	match unsafe{ libselect::set_cork(socket.raw_fd(), if enabled { 1 } else { 0 }) } {
+		0 => Ok(()),
+		e => Err(io::Error::from_raw_os_error(e).into())
+	}
+}
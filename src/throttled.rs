@@ -0,0 +1,161 @@
+use crate::{ TimeoutIoError, InstantExt, with_deadline, TIMED_OUT, Reader, Writer };
+use std::{
+	cmp::{ min, max }, thread,
+	time::{ Duration, Instant }
+};
+
+
+/// A stream adapter that caps the throughput of the `try_*`-calls at `bytes_per_sec` (e.g. for
+/// bandwidth shaping in a test harness or for a polite client); this is the inverse of
+/// `Reader::try_read_exact_min_rate`
+///
+/// _Info: The rate is enforced using a token bucket that holds up to 100ms worth of bytes; so a
+/// single call transfers at most `bytes_per_sec / 10` bytes (at least 1 byte) and a burst after
+/// an idle period is limited to this amount. Reads and writes share the same bucket._
+///
+/// _Note: Throttling respects the operation's `timeout`: if the next token is available before
+/// the deadline, the call sleeps until then; otherwise it sleeps until the deadline and returns
+/// `TimedOut` (so throttling never causes a `TimedOut` before the deadline has actually passed).
+/// As with every `try_*`-call, `pos` reflects the progress made before the error._
+#[derive(Debug)]
+pub struct ThrottledStream<S> {
+	inner: S,
+	bytes_per_sec: u64,
+	tokens: f64,
+	refilled: Instant
+}
+impl<S> ThrottledStream<S> {
+	/// Creates a new throttled stream that transfers at most `bytes_per_sec` (a rate of `0` is
+	/// treated like `1`)
+	pub fn new(inner: S, bytes_per_sec: u64) -> Self {
+		let bytes_per_sec = max(bytes_per_sec, 1);
+		let mut this = Self{ inner, bytes_per_sec, tokens: 0.0, refilled: Instant::now() };
+		this.tokens = this.capacity();
+		this
+	}
+	
+	/// The configured rate limit
+	pub fn bytes_per_sec(&self) -> u64 {
+		self.bytes_per_sec
+	}
+	
+	/// A reference to the underlying stream
+	pub fn get_ref(&self) -> &S {
+		&self.inner
+	}
+	/// A mutable reference to the underlying stream
+	pub fn get_mut(&mut self) -> &mut S {
+		&mut self.inner
+	}
+	/// Returns the underlying stream
+	pub fn into_inner(self) -> S {
+		self.inner
+	}
+	
+	/// The maximum amount of tokens in the bucket
+	fn capacity(&self) -> f64 {
+		f64::max(self.bytes_per_sec as f64 / 10.0, 1.0)
+	}
+	/// Waits until at least one token is available (or fails with `TimedOut` after sleeping until
+	/// `deadline` if this is not possible in time) and returns the amount of bytes that may be
+	/// transferred (at most `wanted`)
+	fn acquire(&mut self, wanted: usize, deadline: Instant) -> Result<usize, TimeoutIoError> {
+		// Refill the bucket
+		let now = Instant::now();
+		let refill = now.duration_since(self.refilled).as_secs_f64() * self.bytes_per_sec as f64;
+		self.tokens = f64::min(self.tokens + refill, self.capacity());
+		self.refilled = now;
+		
+		// Wait for the next token if necessary
+		if self.tokens < 1.0 {
+			let wait = Duration::from_secs_f64((1.0 - self.tokens) / self.bytes_per_sec as f64);
+			let remaining = deadline.remaining();
+			if wait > remaining {
+				thread::sleep(remaining);
+				return Err(TIMED_OUT)
+			}
+			thread::sleep(wait);
+			return self.acquire(wanted, deadline)
+		}
+		Ok(min(wanted, self.tokens as usize))
+	}
+	/// Removes the tokens for `transferred` bytes from the bucket
+	fn consume(&mut self, transferred: usize) {
+		self.tokens -= transferred as f64;
+	}
+}
+impl<S: Reader> ThrottledStream<S> {
+	/// Like `Reader::try_read`, but throttled (i.e. `buf` is filled at most up to the amount of
+	/// available tokens)
+	///
+	/// __Warning: the underlying stream must be non-blocking or the function won't work as
+	/// expected__
+	pub fn try_read(&mut self, buf: &mut[u8], pos: &mut usize, timeout: Duration)
+		-> Result<(), TimeoutIoError>
+	{
+		// Compute the deadline and acquire the tokens
+		with_deadline(timeout, |deadline| {
+			if *pos >= buf.len() { return self.inner.try_read(buf, pos, timeout) }
+			let allowed = self.acquire(buf.len() - *pos, deadline)?;
+			
+			// Read the data
+			let start = *pos;
+			let buf = &mut buf[..start + allowed];
+			let result = self.inner.try_read(buf, pos, deadline.remaining());
+			self.consume(*pos - start);
+			result
+		})
+	}
+	/// Like `Reader::try_read_exact`, but throttled
+	///
+	/// __Warning: the underlying stream must be non-blocking or the function won't work as
+	/// expected__
+	pub fn try_read_exact(&mut self, buf: &mut[u8], pos: &mut usize, timeout: Duration)
+		-> Result<(), TimeoutIoError>
+	{
+		with_deadline(timeout, |deadline| {
+			while *pos < buf.len() {
+				self.try_read(buf, pos, deadline.remaining())?;
+			}
+			Ok(())
+		})
+	}
+}
+impl<S: Writer> ThrottledStream<S> {
+	/// Like `Writer::try_write`, but throttled (i.e. at most the amount of available tokens is
+	/// written)
+	///
+	/// __Warning: the underlying stream must be non-blocking or the function won't work as
+	/// expected__
+	pub fn try_write(&mut self, data: &[u8], pos: &mut usize, timeout: Duration)
+		-> Result<(), TimeoutIoError>
+	{
+		// Compute the deadline and acquire the tokens
+		with_deadline(timeout, |deadline| {
+			if *pos >= data.len() { return Ok(()) }
+			let allowed = self.acquire(data.len() - *pos, deadline)?;
+			
+			// Write the data
+			let (start, mut written) = (*pos, 0);
+			let result = self.inner.try_write(&data[start..start + allowed], &mut written,
+				deadline.remaining());
+			*pos += written;
+			self.consume(written);
+			result
+		})
+	}
+	/// Like `Writer::try_write_exact`, but throttled
+	///
+	/// __Warning: the underlying stream must be non-blocking or the function won't work as
+	/// expected__
+	pub fn try_write_exact(&mut self, data: &[u8], pos: &mut usize, timeout: Duration)
+		-> Result<(), TimeoutIoError>
+	{
+		with_deadline(timeout, |deadline| {
+			while *pos < data.len() {
+				self.try_write(data, pos, deadline.remaining())?;
+			}
+			Ok(())
+		})
+	}
+}
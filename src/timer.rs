@@ -0,0 +1,94 @@
+use crate::{ TimeoutIoError, InstantExt, deadline_after };
+use std::time::{ Duration, Instant };
+#[cfg(target_os = "linux")]
+use std::{
+	io, mem, ptr, fs::File,
+	os::unix::io::{ AsRawFd, FromRawFd }
+};
+
+
+/// A one-shot timer that can be registered in a `SelectSet` (see `SelectSet::push_timer`) so that
+/// IO-readiness and timer expiry are reported by the same wait
+///
+/// _Info: On Linux, the timer is backed by a `timerfd` that is waited on together with the other
+/// handles. On other platforms, the select-timeout is capped at the earliest timer deadline and the
+/// expiry is synthesized after the wait._
+///
+/// _Note: An expired timer is reported by every select until it is `set` again or `cancel`led._
+#[derive(Debug)]
+pub struct Timer {
+	deadline: Option<Instant>,
+	#[cfg(target_os = "linux")]
+	timer_fd: File
+}
+impl Timer {
+	/// Creates a new disarmed timer
+	#[cfg(target_os = "linux")]
+	pub fn new() -> Result<Self, TimeoutIoError> {
+		let flags = libc::TFD_NONBLOCK | libc::TFD_CLOEXEC;
+		match unsafe{ libc::timerfd_create(libc::CLOCK_MONOTONIC, flags) } {
+			-1 => Err(io::Error::last_os_error().into()),
+			fd => Ok(Self{ deadline: None, timer_fd: unsafe{ File::from_raw_fd(fd) } })
+		}
+	}
+	/// Creates a new disarmed timer
+	#[cfg(not(target_os = "linux"))]
+	pub fn new() -> Result<Self, TimeoutIoError> {
+		Ok(Self{ deadline: None })
+	}
+	
+	/// Arms the timer so that it expires after `timeout` (counted from now); a previous deadline is
+	/// replaced
+	pub fn set(&mut self, timeout: Duration) -> Result<(), TimeoutIoError> {
+		// Compute the deadline before arming the `timerfd` so that it never fires before the deadline
+		// (a zero `it_value` would disarm the `timerfd`, so we round it up to 1ns)
+		let deadline = deadline_after(timeout);
+		#[cfg(target_os = "linux")]
+		self.set_timer_fd(timeout.max(Duration::from_nanos(1)))?;
+		self.deadline = Some(deadline);
+		Ok(())
+	}
+	/// Disarms the timer
+	pub fn cancel(&mut self) -> Result<(), TimeoutIoError> {
+		#[cfg(target_os = "linux")]
+		self.set_timer_fd(Duration::from_secs(0))?;
+		self.deadline = None;
+		Ok(())
+	}
+	
+	/// The deadline or `None` if the timer is disarmed
+	pub fn deadline(&self) -> Option<Instant> {
+		self.deadline
+	}
+	/// The remaining time until the timer expires or `None` if the timer is disarmed
+	pub fn remaining(&self) -> Option<Duration> {
+		self.deadline.map(InstantExt::remaining)
+	}
+	/// Whether the timer is armed and has expired
+	pub fn is_expired(&self) -> bool {
+		self.remaining().is_some_and(|remaining| remaining.is_zero())
+	}
+	
+	/// The raw FD to wait on or `None` if the expiry must be synthesized
+	#[cfg(target_os = "linux")]
+	pub(crate) fn raw_fd(&self) -> Option<u64> {
+		Some(self.timer_fd.as_raw_fd() as u64)
+	}
+	/// The raw FD to wait on or `None` if the expiry must be synthesized
+	#[cfg(not(target_os = "linux"))]
+	pub(crate) fn raw_fd(&self) -> Option<u64> {
+		None
+	}
+	
+	/// Sets the `timerfd` to expire once after `timeout` (or disarms it if `timeout` is zero)
+	#[cfg(target_os = "linux")]
+	fn set_timer_fd(&self, timeout: Duration) -> Result<(), TimeoutIoError> {
+		let mut spec: libc::itimerspec = unsafe{ mem::zeroed() };
+		spec.it_value.tv_sec = timeout.as_secs().min(libc::time_t::MAX as u64) as _;
+		spec.it_value.tv_nsec = timeout.subsec_nanos() as _;
+		match unsafe{ libc::timerfd_settime(self.timer_fd.as_raw_fd(), 0, &spec, ptr::null_mut()) } {
+			-1 => Err(io::Error::last_os_error().into()),
+			_ => Ok(())
+		}
+	}
+}
@@ -0,0 +1,108 @@
+use std::io::{ self, Read, Write };
+
+
+/// What a `TrackedStream` does if it is dropped with unflushed data
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DropPolicy {
+	/// Print a warning to stderr
+	Warn,
+	/// Panic (unless the thread is already panicking)
+	Panic
+}
+
+
+/// A diagnostic wrapper that detects if a stream is dropped while written data may not have been
+/// flushed (which often causes silent truncation for the peer)
+///
+/// Every `write` marks the stream as dirty and every successful `flush` marks it as clean; if the
+/// stream is dropped while it is dirty, the `DropPolicy` is applied.
+///
+/// _Info: This is a best-effort heuristic: we cannot know if the kernel has actually sent the data,
+/// only if the data was flushed through all user-space buffers. The tracking is only compiled into
+/// debug-builds (`debug_assertions`); in release-builds, the wrapper only forwards the calls and
+/// dropping is not checked at all._
+#[derive(Debug)]
+pub struct TrackedStream<S: Write> {
+	inner: S,
+	#[cfg(debug_assertions)]
+	policy: DropPolicy,
+	#[cfg(debug_assertions)]
+	dirty: bool
+}
+impl<S: Write> TrackedStream<S> {
+	/// Wraps `inner` and applies `policy` if it is dropped with unflushed data
+	#[cfg(debug_assertions)]
+	pub fn new(inner: S, policy: DropPolicy) -> Self {
+		Self{ inner, policy, dirty: false }
+	}
+	/// Wraps `inner` and applies `policy` if it is dropped with unflushed data
+	#[cfg(not(debug_assertions))]
+	pub fn new(inner: S, _policy: DropPolicy) -> Self {
+		Self{ inner }
+	}
+	
+	/// Whether data was written but not flushed yet
+	///
+	/// _Info: This is always `false` in release-builds (where nothing is tracked)._
+	pub fn may_lose_data(&self) -> bool {
+		#[cfg(debug_assertions)]
+		let dirty = self.dirty;
+		#[cfg(not(debug_assertions))]
+		let dirty = false;
+		dirty
+	}
+	
+	/// Gets a reference to the underlying stream
+	pub fn get_ref(&self) -> &S {
+		&self.inner
+	}
+	/// Gets a mutable reference to the underlying stream
+	pub fn get_mut(&mut self) -> &mut S {
+		&mut self.inner
+	}
+}
+impl<S: Write> Write for TrackedStream<S> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		let written = self.inner.write(buf)?;
+		#[cfg(debug_assertions)]
+		if written > 0 { self.dirty = true }
+		Ok(written)
+	}
+	fn flush(&mut self) -> io::Result<()> {
+		self.inner.flush()?;
+		#[cfg(debug_assertions)]
+		{ self.dirty = false }
+		Ok(())
+	}
+}
+impl<S: Write + Read> Read for TrackedStream<S> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		self.inner.read(buf)
+	}
+}
+#[cfg(unix)]
+impl<S: Write + std::os::unix::io::AsRawFd> std::os::unix::io::AsRawFd for TrackedStream<S> {
+	fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+		self.inner.as_raw_fd()
+	}
+}
+#[cfg(windows)]
+impl<S: Write + std::os::windows::io::AsRawSocket> std::os::windows::io::AsRawSocket for TrackedStream<S> {
+	fn as_raw_socket(&self) -> std::os::windows::io::RawSocket {
+		self.inner.as_raw_socket()
+	}
+}
+#[cfg(debug_assertions)]
+impl<S: Write> Drop for TrackedStream<S> {
+	fn drop(&mut self) {
+		// Check if we may lose data
+		if !self.dirty { return }
+		
+		const MESSAGE: &str = "TrackedStream dropped with unflushed data; the peer may miss data";
+		match self.policy {
+			DropPolicy::Warn => eprintln!("{}", MESSAGE),
+			DropPolicy::Panic if !std::thread::panicking() => panic!("{}", MESSAGE),
+			DropPolicy::Panic => ()
+		}
+	}
+}
@@ -0,0 +1,138 @@
+use crate::{ TimeoutIoError, WaitForEvent, EventMask };
+use std::{
+	time::Duration,
+	io::{ self, Read, Write }
+};
+#[cfg(target_os = "linux")]
+use std::{ fs::File, os::unix::io::FromRawFd };
+#[cfg(all(unix, not(target_os = "linux")))]
+use std::os::unix::net::UnixStream;
+#[cfg(windows)]
+use std::net::{ TcpListener, TcpStream };
+
+
+/// The result of a wait that can be interrupted by a `Waker`
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum WokenOr<T> {
+	/// The waker was signaled
+	Woken,
+	/// The handle fired
+	Event(T)
+}
+
+
+/// A handle that can be signaled from another thread to interrupt a wait (see
+/// `WaitForEvent::wait_for_event_or_wake`)
+///
+/// _Info: The waker uses an `eventfd` on Linux, a unix-socket-pair on other unix-targets and a
+/// loopback TCP-socket-pair on windows._
+///
+/// _Note: The waker remains signaled until it is `reset`; so a single `wake` interrupts all current
+/// and future waits on it (e.g. to shut down multiple threads at once)._
+#[derive(Debug)]
+pub struct Waker {
+	#[cfg(target_os = "linux")]
+	event_fd: File,
+	#[cfg(all(unix, not(target_os = "linux")))]
+	pipe: (UnixStream, UnixStream),
+	#[cfg(windows)]
+	pipe: (TcpStream, TcpStream)
+}
+impl Waker {
+	/// Creates a new unsignaled waker
+	#[cfg(target_os = "linux")]
+	pub fn new() -> Result<Self, TimeoutIoError> {
+		match unsafe{ libc::eventfd(0, libc::EFD_NONBLOCK | libc::EFD_CLOEXEC) } {
+			-1 => Err(io::Error::last_os_error().into()),
+			fd => Ok(Self{ event_fd: unsafe{ File::from_raw_fd(fd) } })
+		}
+	}
+	/// Creates a new unsignaled waker
+	#[cfg(all(unix, not(target_os = "linux")))]
+	pub fn new() -> Result<Self, TimeoutIoError> {
+		let (reader, writer) = UnixStream::pair()?;
+		reader.set_nonblocking(true)?;
+		writer.set_nonblocking(true)?;
+		Ok(Self{ pipe: (reader, writer) })
+	}
+	/// Creates a new unsignaled waker
+	#[cfg(windows)]
+	pub fn new() -> Result<Self, TimeoutIoError> {
+		// Connect a socket-pair over loopback
+		let listener = TcpListener::bind("127.0.0.1:0")?;
+		let writer = TcpStream::connect(listener.local_addr()?)?;
+		let reader = loop {
+			// Ensure that we accept our own connection
+			let (reader, address) = listener.accept()?;
+			if address == writer.local_addr()? { break reader }
+		};
+		
+		reader.set_nonblocking(true)?;
+		writer.set_nonblocking(true)?;
+		writer.set_nodelay(true)?;
+		Ok(Self{ pipe: (reader, writer) })
+	}
+	
+	/// Signals the waker (this never blocks; signaling an already signaled waker is a no-op)
+	pub fn wake(&self) -> Result<(), TimeoutIoError> {
+		#[cfg(target_os = "linux")]
+		let result = (&self.event_fd).write(&1u64.to_ne_bytes());
+		#[cfg(not(target_os = "linux"))]
+		let result = (&self.pipe.1).write(&[1]);
+		
+		// A full counter/pipe means that the waker is already signaled
+		match result {
+			Err(error) if error.kind() != io::ErrorKind::WouldBlock => Err(error.into()),
+			_ => Ok(())
+		}
+	}
+	
+	/// Resets the waker to the unsignaled state
+	pub fn reset(&self) -> Result<(), TimeoutIoError> {
+		#[cfg(target_os = "linux")]
+		let mut reader = &self.event_fd;
+		#[cfg(not(target_os = "linux"))]
+		let mut reader = &self.pipe.0;
+		
+		// Drain the counter/pipe
+		let mut buf = [0; 64];
+		loop {
+			match reader.read(&mut buf) {
+				Ok(0) => return Err(TimeoutIoError::ConnectionLost{ source: None }),
+				Ok(_) => continue,
+				Err(error) if error.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+				Err(error) => match TimeoutIoError::from(error) {
+					TimeoutIoError::InterruptedSyscall => continue,
+					error => return Err(error)
+				}
+			}
+		}
+	}
+	
+	/// Checks if the waker is currently signaled (without resetting it)
+	pub fn is_woken(&self) -> Result<bool, TimeoutIoError> {
+		match self.wait_for_event(EventMask::new_r(), Duration::from_secs(0)) {
+			Ok(_) => Ok(true),
+			Err(TimeoutIoError::TimedOut{ .. }) => Ok(false),
+			Err(error) => Err(error)
+		}
+	}
+}
+#[cfg(target_os = "linux")]
+impl std::os::unix::io::AsRawFd for Waker {
+	fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+		self.event_fd.as_raw_fd()
+	}
+}
+#[cfg(all(unix, not(target_os = "linux")))]
+impl std::os::unix::io::AsRawFd for Waker {
+	fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+		self.pipe.0.as_raw_fd()
+	}
+}
+#[cfg(windows)]
+impl std::os::windows::io::AsRawSocket for Waker {
+	fn as_raw_socket(&self) -> std::os::windows::io::RawSocket {
+		self.pipe.0.as_raw_socket()
+	}
+}
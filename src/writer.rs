@@ -1,11 +1,29 @@
-use crate::{ TimeoutIoError, InstantExt, WaitForEvent, EventMask };
+use crate::{
+	TimeoutIoError, InstantExt, with_deadline, TIMED_OUT, WaitForEvent, EventMask, TimeoutConfig,
+	RawFd
+};
 use std::{
+	fs::File,
 	io::Write,
-	time::{ Duration, Instant }
+	time::Duration
 };
+#[cfg(unix)]
+use crate::event::libselect;
 
 
 /// A trait for writing with timeouts
+///
+/// _Note: This trait is implemented for all types that implement `std::io::Write` and
+/// `WaitForEvent` – i.e. the type must expose a raw fd/socket. In-memory types like
+/// `std::io::Cursor` or `&[u8]` are not supported._
+///
+/// _Info: The trait is object safe, so that you can store heterogeneous streams (e.g. `TcpStream`
+/// and `UnixStream`) as `Box<dyn Writer>`; generic conveniences must be bounded by `Self: Sized`._
+#[diagnostic::on_unimplemented(
+	message = "`{Self}` cannot be used as a `Writer`",
+	label = "`{Self}` does not implement `Writer`",
+	note = "`Writer` is implemented for all `std::io::Write + WaitForEvent` types; the type must expose a raw fd/socket (see `RawFd`)"
+)]
 pub trait Writer {
 	/// Executes _one_ `write`-operation to write _as much bytes as possible_ from `data[*pos..]`
 	/// and adjusts `pos` accordingly
@@ -17,10 +35,46 @@ pub trait Writer {
 	/// either one successful `write`-operation or the `timeout` was hit or a non-recoverable error
 	/// occurred._
 	///
+	/// _Note: A zero `timeout` performs exactly one non-blocking attempt and returns `TimedOut`
+	/// immediately if `self` is not ready._
+	///
 	/// __Warning: `self` must non-blocking or the function won't work as expected__
 	fn try_write(&mut self, data: &[u8], pos: &mut usize, timeout: Duration)
 		-> Result<(), TimeoutIoError>;
 	
+	/// Like `try_write`, but attempts the `write` _before_ waiting and returns `(written,
+	/// would_block_count)` – where `would_block_count` is the amount of `write`-attempts that
+	/// returned `WouldBlock` before the successful one (e.g. to adapt write-batch-sizes to
+	/// backpressure)
+	///
+	/// _Info: A count of `0` means that the kernel's send buffer had room immediately; so you may
+	/// grow your batches. A nonzero count means that the send buffer was full and you had to wait
+	/// for the peer to drain it; so you may shrink your batches (or write less often)._
+	///
+	/// _Note: `pos` is adjusted like in `try_write`. On errors (including `TimedOut`), the count is
+	/// not available._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn try_write_counted(&mut self, data: &[u8], pos: &mut usize, timeout: Duration)
+		-> Result<(usize, usize), TimeoutIoError>;
+	
+	/// Like `try_write`, but uses the write-timeout of the process-wide `TimeoutConfig`
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn try_write_default(&mut self, data: &[u8], pos: &mut usize) -> Result<(), TimeoutIoError>;
+	
+	/// Like `try_write`, but waits for `mask` instead of `EventMask::new_w()` before writing
+	///
+	/// _Note: The write is attempted as soon as _any_ event in `mask` occurs; so an unusual mask
+	/// (e.g. one that includes read-events) may result in `write`-attempts while `self` is not
+	/// writeable. These are retried transparently until the `timeout` is hit, but can cause busy
+	/// waiting if the event in question persists. A mask without the error-event ignores
+	/// out-of-band/error-conditions._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn try_write_with_mask(&mut self, data: &[u8], pos: &mut usize, mask: EventMask,
+		timeout: Duration) -> Result<(), TimeoutIoError>;
+	
 	/// Reads until `buf[*pos..]` has been written completely and adjusts `pos` _on every successful
 	/// `write`-call_ (so that you can continue seamlessly on `TimedOut`-errors etc.)
 	///
@@ -31,55 +85,267 @@ pub trait Writer {
 	/// `data` has been filled completely or the `timeout` was hit or a non-recoverable error
 	/// occurred._
 	///
+	/// _Info: The first `write` is attempted _before_ waiting for a write-event; so if `self` is
+	/// already writeable, this saves the wait-syscall. We only wait if `write` returns
+	/// `WouldBlock`._
+	///
 	/// __Warning: `self` must non-blocking or the function won't work as expected__
 	fn try_write_exact(&mut self, data: &[u8], pos: &mut usize, timeout: Duration)
 		-> Result<(), TimeoutIoError>;
+	
+	/// Writes each chunk yielded by `chunks` completely (via `try_write_exact`) until the iterator
+	/// is exhausted; all chunks share the same `timeout` and the function stops at the first error
+	///
+	/// This is useful to stream data that is generated on the fly (e.g. a response body) without
+	/// materializing it in memory.
+	///
+	/// _Info: `chunks` is consumed lazily – the next chunk is only requested after the previous
+	/// chunk has been written completely._
+	///
+	/// _Note: Since there is no `pos`-argument, you cannot continue seamlessly on `TimedOut`-errors;
+	/// the partially written chunk is lost._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn try_write_chunks<'a>(&mut self, chunks: impl Iterator<Item = &'a [u8]>, timeout: Duration)
+		-> Result<(), TimeoutIoError> where Self: Sized;
+	
+	/// Flushes `self` until all buffered data has reached the underlying handle
+	///
+	/// This is necessary for buffered writers; for raw handles like a `TcpStream`, this is a no-op
+	///
+	/// _Note: This function catches all interal timeouts/interrupts and returns only if either
+	/// `self` has been flushed completely or the `timeout` was hit or a non-recoverable error
+	/// occurred._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn try_flush(&mut self, timeout: Duration) -> Result<(), TimeoutIoError>;
+	
+	/// Writes `data` completely and flushes `self` afterwards (so that the data actually reaches
+	/// the underlying handle); both operations share the same `timeout`
+	///
+	/// _Note: Since there is no `pos`-argument, you cannot continue seamlessly on `TimedOut`-errors;
+	/// use `try_write_exact` and `try_flush` if you need this._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn try_write_all(&mut self, data: &[u8], timeout: Duration) -> Result<(), TimeoutIoError>;
+	
+	/// Sends up to `count` bytes from `file` starting at `*offset` and advances `offset` _on every
+	/// successful transfer_ (so that you can continue seamlessly on `TimedOut`-errors etc.).
+	/// Returns the amount of bytes sent, which is only less than `count` if the end of `file` was
+	/// reached.
+	///
+	/// _Info: On Linux/Android, macOS/iOS and FreeBSD/DragonFly, this uses `sendfile(2)`, so that
+	/// the data is transferred by the kernel without being copied through userspace. On other
+	/// platforms (or if the kernel rejects the handles, e.g. for non-socket targets on BSDs), it
+	/// falls back to a `read`+`write`-loop with an internal 64 KiB buffer._
+	///
+	/// _Note: `offset` is independent of the file's cursor – the cursor is not used or moved on
+	/// unix (Windows' positional read moves it though). If the fallback fails within a chunk,
+	/// `offset` is advanced by the bytes that were written._
+	///
+	/// __Warning: `self` must non-blocking or the function won't work as expected__
+	fn try_sendfile(&mut self, file: &File, offset: &mut u64, count: usize, timeout: Duration)
+		-> Result<usize, TimeoutIoError> where Self: Sized + RawFd;
 }
 impl<T: Write + WaitForEvent> Writer for T {
 	fn try_write(&mut self, data: &[u8], pos: &mut usize, timeout: Duration)
 		-> Result<(), TimeoutIoError>
+	{
+		self.try_write_with_mask(data, pos, EventMask::new_w(), timeout)
+	}
+	fn try_write_counted(&mut self, data: &[u8], pos: &mut usize, timeout: Duration)
+		-> Result<(usize, usize), TimeoutIoError>
+	{
+		// Compute the deadline
+		with_deadline(timeout, |deadline| {
+			// Write data and count the `WouldBlock`s
+			if *pos >= data.len() { return Ok((0, 0)) }
+			let mut would_block_count = 0;
+			loop {
+				match self.write(&data[*pos..]) {
+					Ok(0) => return Err(TimeoutIoError::UnexpectedEof),
+					Ok(written) => {
+						*pos += written;
+						return Ok((written, would_block_count))
+					},
+					Err(error) => match TimeoutIoError::from(error) {
+						TimeoutIoError::TimedOut{ .. } => {
+							would_block_count += 1;
+							if timeout.is_zero() { return Err(TIMED_OUT) }
+							self.wait_for_event(EventMask::new_w(), deadline.remaining())?;
+						},
+						error if error.should_retry() => (),
+						error => return Err(error)
+					}
+				}
+			}
+		})
+	}
+	fn try_write_default(&mut self, data: &[u8], pos: &mut usize) -> Result<(), TimeoutIoError> {
+		self.try_write(data, pos, TimeoutConfig::current().write_timeout())
+	}
+	fn try_write_with_mask(&mut self, data: &[u8], pos: &mut usize, mask: EventMask,
+		timeout: Duration) -> Result<(), TimeoutIoError>
 	{
 		// Compute the deadline
-		let deadline = Instant::now() + timeout;
-		
-		// Wait for write-events and write data
-		if *pos >= data.len() { return Ok(()) }
-		loop {
-			self.wait_for_event(EventMask::new_w(),deadline.remaining())?;
-			match self.write(data) {
-				Ok(0) => return Err(TimeoutIoError::UnexpectedEof),
-				Ok(written) => {
-					*pos += written;
-					return Ok(())
-				},
-				Err(error) => {
-					let error = TimeoutIoError::from(error);
-					if !error.should_retry() { return Err(error) }
+		with_deadline(timeout, |deadline| {
+			// Wait for write-events and write data
+			if *pos >= data.len() { return Ok(()) }
+			loop {
+				// Wait for write-event (unless we only perform a non-blocking attempt) and write
+				// data
+				if !timeout.is_zero() { self.wait_for_event(mask, deadline.remaining())?; }
+				match self.write(&data[*pos..]) {
+					Ok(0) => return Err(TimeoutIoError::UnexpectedEof),
+					Ok(written) => {
+						*pos += written;
+						return Ok(())
+					},
+					Err(error) => {
+						let error = TimeoutIoError::from(error);
+						if !error.should_retry() || (timeout.is_zero() && error.is_timeout()) {
+							return Err(error)
+						}
+					}
 				}
 			}
-		}
+		})
 	}
 	fn try_write_exact(&mut self, data: &[u8], pos: &mut usize, timeout: Duration)
 		-> Result<(), TimeoutIoError>
 	{
 		// Compute the deadline
-		let deadline = Instant::now() + timeout;
-		
-		// Loop until `data` has been written
-		while *pos < data.len() {
-			// Wait for write-event
-			self.wait_for_event(EventMask::new_w(), deadline.remaining())?;
+		with_deadline(timeout, |deadline| {
+			// Loop until `data` has been written
+			while *pos < data.len() {
+				// Write data (opportunistically, because the send buffer is often not full) and
+				// wait for a write-event only if `self` is not ready
+				match self.write(&data[*pos..]) {
+					Ok(0) => return Err(TimeoutIoError::UnexpectedEof),
+					Ok(written) => *pos += written,
+					Err(error) => match TimeoutIoError::from(error) {
+						TimeoutIoError::TimedOut{ .. } => {
+							self.wait_for_event(EventMask::new_w(), deadline.remaining())?;
+						},
+						error if error.should_retry() => (),
+						error => return Err(error)
+					}
+				}
+			}
+			Ok(())
+		})
+	}
+	fn try_write_chunks<'a>(&mut self, chunks: impl Iterator<Item = &'a [u8]>, timeout: Duration)
+		-> Result<(), TimeoutIoError>
+	{
+		// Compute the deadline
+		with_deadline(timeout, |deadline| {
+			// Write the chunks as they are yielded
+			for chunk in chunks {
+				self.try_write_exact(chunk, &mut 0, deadline.remaining())?;
+			}
+			Ok(())
+		})
+	}
+	fn try_flush(&mut self, timeout: Duration) -> Result<(), TimeoutIoError> {
+		// Compute the deadline
+		with_deadline(timeout, |deadline| {
+			// Loop until `self` has been flushed
+			loop {
+				match self.flush() {
+					Ok(_) => return Ok(()),
+					Err(error) => {
+						let error = TimeoutIoError::from(error);
+						if !error.should_retry() { return Err(error) }
+					}
+				}
+				
+				// Wait for write-event
+				self.wait_for_event(EventMask::new_w(), deadline.remaining())?;
+			}
+		})
+	}
+	fn try_write_all(&mut self, data: &[u8], timeout: Duration) -> Result<(), TimeoutIoError> {
+		// Compute the deadline
+		with_deadline(timeout, |deadline| {
+			// Write and flush the data
+			self.try_write_exact(data, &mut 0, deadline.remaining())?;
+			self.try_flush(deadline.remaining())
+		})
+	}
+	fn try_sendfile(&mut self, file: &File, offset: &mut u64, count: usize, timeout: Duration)
+		-> Result<usize, TimeoutIoError> where Self: RawFd
+	{
+		// Compute the deadline
+		with_deadline(timeout, |deadline| {
+			let mut sent = 0;
 			
-			// Write data
-			match self.write(&data[*pos..]) {
-				Ok(0) => return Err(TimeoutIoError::UnexpectedEof),
-				Ok(written) => *pos += written,
-				Err(error) => {
-					let error = TimeoutIoError::from(error);
-					if !error.should_retry() { return Err(error) }
+			// Send the file natively if possible
+			#[cfg(unix)]
+			while sent < count {
+				match send_file(self, file, offset, count - sent) {
+					Ok(Some(0)) => return Ok(sent),
+					Ok(Some(chunk)) => sent += chunk,
+					Ok(None) => break,
+					Err(TimeoutIoError::TimedOut{ .. }) => {
+						self.wait_for_event(EventMask::new_w(), deadline.remaining())?;
+					},
+					Err(error) if error.should_retry() => (),
+					Err(error) => return Err(error)
 				}
 			}
-		}
-		Ok(())
+			
+			// Copy the remaining data through userspace
+			let mut buf = vec![0; std::cmp::min(count - sent, 64 * 1024)];
+			while sent < count {
+				// Read the next chunk
+				let to_read = std::cmp::min(count - sent, buf.len());
+				let read = match read_at(file, &mut buf[..to_read], *offset) {
+					Ok(0) => return Ok(sent),
+					Ok(read) => read,
+					Err(error) => match TimeoutIoError::from(error) {
+						error if error.should_retry() => continue,
+						error => return Err(error)
+					}
+				};
+				
+				// Write the chunk
+				let mut pos = 0;
+				let result = self.try_write_exact(&buf[..read], &mut pos, deadline.remaining());
+				*offset += pos as u64;
+				sent += pos;
+				result?;
+			}
+			Ok(sent)
+		})
 	}
-}
\ No newline at end of file
+}
+
+
+/// Performs one `sendfile`-call from `file` to `socket` and returns the amount of bytes sent or
+/// `None` if `sendfile` is not supported for the handles
+#[cfg(unix)]
+fn send_file(socket: &impl RawFd, file: &File, offset: &mut u64, count: usize)
+	-> Result<Option<usize>, TimeoutIoError>
+{
+	use std::{ io, os::unix::io::AsRawFd };
+	
+	let mut sent = 0;
+	let (fd, file_fd) = (socket.raw_fd(), file.as_raw_fd() as u64);
+	let result = unsafe{ libselect::send_file(fd, file_fd, offset, count as u64, &mut sent) };
+	match result {
+		0 => Ok(Some(sent as usize)),
+		libc::ENOSYS | libc::EINVAL | libc::ENOTSOCK | libc::EOPNOTSUPP => Ok(None),
+		e => Err(io::Error::from_raw_os_error(e).into())
+	}
+}
+/// Reads from `file` at `offset` without using the file's cursor
+#[cfg(unix)]
+fn read_at(file: &File, buf: &mut[u8], offset: u64) -> std::io::Result<usize> {
+	std::os::unix::fs::FileExt::read_at(file, buf, offset)
+}
+/// Reads from `file` at `offset` (this moves the file's cursor)
+#[cfg(windows)]
+fn read_at(file: &File, buf: &mut[u8], offset: u64) -> std::io::Result<usize> {
+	std::os::windows::fs::FileExt::seek_read(file, buf, offset)
+}
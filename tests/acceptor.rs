@@ -1,10 +1,47 @@
 use timeout_io::*;
 use std::{
-	time::Duration, thread,
-	net::{ TcpListener, TcpStream }
+	thread,
+	io::{ self, Read, Write },
+	time::{ Duration, Instant },
+	net::{ TcpListener, TcpStream },
+	sync::{ Arc, mpsc::{ self, RecvTimeoutError }, atomic::{ AtomicUsize, Ordering } }
 };
 
 
+/// An acceptor that is always readable but fails the first `would_blocks` `accept`s with
+/// `EWOULDBLOCK` (i.e. spurious wakeups) and every other `accept` with `ECONNABORTED`
+#[cfg(unix)]
+struct AbortingAcceptor {
+	listener: TcpListener,
+	_stream: TcpStream,
+	would_blocks: usize,
+	accepts: AtomicUsize
+}
+#[cfg(unix)]
+impl AbortingAcceptor {
+	fn new(would_blocks: usize) -> Self {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let _stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+		Self{ listener, _stream, would_blocks, accepts: AtomicUsize::new(0) }
+	}
+}
+#[cfg(unix)]
+impl std::os::unix::io::AsRawFd for AbortingAcceptor {
+	fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+		self.listener.as_raw_fd()
+	}
+}
+#[cfg(unix)]
+impl StdAcceptor<TcpStream> for AbortingAcceptor {
+	fn accept(&self) -> Result<TcpStream, io::Error> {
+		match self.accepts.fetch_add(1, Ordering::SeqCst) < self.would_blocks {
+			true => Err(io::ErrorKind::WouldBlock.into()),
+			false => Err(io::ErrorKind::ConnectionAborted.into())
+		}
+	}
+}
+
+
 #[test]
 fn test_accept_ok() {
 	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
@@ -18,10 +55,323 @@ fn test_accept_ok() {
 	Acceptor::try_accept(&listener, Duration::from_secs(7)).unwrap();
 }
 #[test]
+fn test_accept_zero_timeout() {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	assert_eq!(
+		Acceptor::try_accept(&listener, Duration::from_secs(0)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+	
+	let _stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	thread::sleep(Duration::from_secs(1));
+	Acceptor::try_accept(&listener, Duration::from_secs(0)).unwrap();
+}
+#[test]
 fn test_accept_timeout() {
 	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
 	assert_eq!(
-		Acceptor::try_accept(&listener, Duration::from_secs(4)).unwrap_err(),
-		TimeoutIoError::TimedOut
+		Acceptor::try_accept(&listener, Duration::from_secs(4)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
 	)
-}
\ No newline at end of file
+}
+#[test]
+fn test_accept_timeout_includes_setup() {
+	// Start with a blocking listener so that the setup has to flip the mode
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let timeouts = [Duration::from_millis(1), Duration::from_millis(50), Duration::from_millis(200)];
+	for timeout in timeouts {
+		listener.set_blocking_mode(true).unwrap();
+		let start = Instant::now();
+		let error = Acceptor::try_accept(&listener, timeout).unwrap_err();
+		let elapsed = start.elapsed();
+		
+		// The call returns within `timeout` of entry (plus a small scheduling tolerance)
+		assert_eq!(error.kind(), TimeoutIoErrorKind::TimedOut);
+		assert!(elapsed < timeout + Duration::from_millis(100), "{:?} >= {:?}", elapsed, timeout);
+	}
+}
+#[test]
+fn test_accept_blocking_timeout() {
+	let listener = Arc::new(TcpListener::bind("127.0.0.1:0").unwrap());
+	let address = listener.local_addr().unwrap();
+	
+	// Time out and accept
+	let start = Instant::now();
+	assert_eq!(
+		Acceptor::accept_blocking_timeout(&*listener, Duration::from_millis(500))
+			.unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+	assert!(start.elapsed() >= Duration::from_millis(450));
+	let _stream = TcpStream::connect(address).unwrap();
+	Acceptor::accept_blocking_timeout(&*listener, Duration::from_secs(4)).unwrap();
+	
+	// The listener is still blocking (and the socket-timeout has been restored), so a plain
+	// `accept` blocks until there is a connection
+	let (sender, receiver) = mpsc::channel();
+	let blocking = listener.clone();
+	thread::spawn(move || sender.send(blocking.accept().map(|_| ())).unwrap());
+	assert_eq!(
+		receiver.recv_timeout(Duration::from_secs(1)).unwrap_err(),
+		RecvTimeoutError::Timeout
+	);
+	
+	let _stream = TcpStream::connect(address).unwrap();
+	receiver.recv_timeout(Duration::from_secs(4)).unwrap().unwrap();
+}
+#[test]
+fn test_accept_with_options() {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let _stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	
+	// Accept the connection
+	let options = AcceptOptions{
+		nodelay: true, keepalive: true, linger: Some(Duration::from_secs(7)),
+		..Default::default()
+	};
+	let mut stream = listener.accept_with_options(Duration::from_secs(4), &options).unwrap();
+	
+	// Validate the options
+	assert!(stream.nodelay().unwrap());
+	assert_eq!(stream.read(&mut [0; 4]).unwrap_err().kind(), io::ErrorKind::WouldBlock);
+	#[cfg(unix)] {
+		use std::{ mem, os::unix::io::AsRawFd };
+		
+		let mut keepalive: libc::c_int = 0;
+		let mut len = mem::size_of::<libc::c_int>() as libc::socklen_t;
+		let result = unsafe{ libc::getsockopt(
+			stream.as_raw_fd(), libc::SOL_SOCKET, libc::SO_KEEPALIVE,
+			&mut keepalive as *mut _ as *mut libc::c_void, &mut len
+		) };
+		assert_eq!((result, keepalive), (0, 1));
+		
+		let mut linger: libc::linger = unsafe{ mem::zeroed() };
+		let mut len = mem::size_of::<libc::linger>() as libc::socklen_t;
+		let result = unsafe{ libc::getsockopt(
+			stream.as_raw_fd(), libc::SOL_SOCKET, libc::SO_LINGER,
+			&mut linger as *mut _ as *mut libc::c_void, &mut len
+		) };
+		assert_eq!((result, linger.l_onoff, linger.l_linger), (0, 1, 7));
+	}
+}
+#[test]
+fn test_accept_for_reactor() {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	
+	// Accept the connection and validate the options
+	let mut stream = listener.accept_for_reactor(Duration::from_secs(4)).unwrap();
+	assert!(stream.nodelay().unwrap());
+	assert_eq!(stream.read(&mut [0; 4]).unwrap_err().kind(), io::ErrorKind::WouldBlock);
+	
+	// The stream is immediately usable
+	client.write_all(b"Testolope").unwrap();
+	let (mut buf, mut pos) = ([0; 9], 0);
+	stream.try_read_exact(&mut buf, &mut pos, Duration::from_secs(4)).unwrap();
+	assert_eq!(&buf, b"Testolope");
+}
+#[test] #[cfg(unix)]
+fn test_accept_bounded_exhausted() {
+	let acceptor = AbortingAcceptor::new(0);
+	let result: Result<TcpStream, _> = acceptor.try_accept_bounded(Duration::from_secs(4), 7);
+	
+	assert_eq!(result.unwrap_err().kind(), TimeoutIoErrorKind::ConnectionLost);
+	assert_eq!(acceptor.accepts.load(Ordering::SeqCst), 8);
+}
+#[test] #[cfg(unix)]
+fn test_accept_bounded_spurious_wakeups() {
+	// Spurious wakeups don't count against the retry budget
+	let acceptor = AbortingAcceptor::new(5);
+	let result: Result<TcpStream, _> = acceptor.try_accept_bounded(Duration::from_secs(4), 7);
+	
+	assert_eq!(result.unwrap_err().kind(), TimeoutIoErrorKind::ConnectionLost);
+	assert_eq!(acceptor.accepts.load(Ordering::SeqCst), 5 + 8);
+}
+
+
+#[test]
+fn test_bind_reuseaddr_ok() {
+	let listener = bind_reuseaddr(&"127.0.0.1:0".parse().unwrap()).unwrap();
+	
+	let address = listener.local_addr().unwrap();
+	thread::spawn(move || {
+		thread::sleep(Duration::from_secs(1));
+		TcpStream::connect(address).unwrap();
+	});
+	
+	Acceptor::try_accept(&listener, Duration::from_secs(4)).unwrap();
+}
+#[test] #[cfg(unix)]
+fn test_bind_reuseport_ok() {
+	let l0 = bind_reuseport(&"127.0.0.1:0".parse().unwrap()).unwrap();
+	let l1 = bind_reuseport(&l0.local_addr().unwrap()).unwrap();
+	assert_eq!(l0.local_addr().unwrap(), l1.local_addr().unwrap());
+}
+#[test]
+fn test_bind_reuseaddr_in_use() {
+	let l0 = TcpListener::bind("127.0.0.1:0").unwrap();
+	bind_reuseaddr(&l0.local_addr().unwrap()).unwrap_err();
+}
+
+
+#[test]
+fn test_accept_nb() {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap().into_nonblocking().unwrap();
+	let idle: Option<TcpStream> = listener.accept_nb().unwrap();
+	assert!(idle.is_none());
+	
+	// Connect and wait until the connection is pending
+	let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	listener.wait_for_event(EventMask::new_r(), Duration::from_secs(4)).unwrap();
+	let accepted: Option<TcpStream> = listener.accept_nb().unwrap();
+	assert_eq!(accepted.unwrap().peer_addr().unwrap(), stream.local_addr().unwrap());
+}
+#[test]
+fn test_accept_from_nb() {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap().into_nonblocking().unwrap();
+	assert!(accept_from_nb(&listener).unwrap().is_none());
+	
+	let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	listener.wait_for_event(EventMask::new_r(), Duration::from_secs(4)).unwrap();
+	let (_accepted, address) = accept_from_nb(&listener).unwrap().unwrap();
+	assert_eq!(address, stream.local_addr().unwrap());
+}
+/// Breaks `listener` (a shut down listener fails every `accept`)
+#[cfg(target_os = "linux")]
+fn break_listener(listener: &TcpListener) {
+	use std::os::unix::io::AsRawFd;
+	assert_eq!(unsafe{ libc::shutdown(listener.as_raw_fd(), libc::SHUT_RD) }, 0);
+}
+/// Connects to `address` and retries while the connection is refused (i.e. while the server is not
+/// listening yet)
+#[cfg(target_os = "linux")]
+fn connect_retry(address: std::net::SocketAddr) -> TcpStream {
+	let start = Instant::now();
+	loop {
+		match TcpStream::connect(address) {
+			Err(error) if error.kind() == io::ErrorKind::ConnectionRefused => {
+				assert!(start.elapsed() < Duration::from_secs(4), "Failed to connect");
+				thread::sleep(Duration::from_millis(10));
+			},
+			result => return result.unwrap()
+		}
+	}
+}
+#[test] #[cfg(target_os = "linux")]
+fn test_serve_resilient_rebind() {
+	// Reserve an address and serve in background
+	let address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+	let (sender, accepted) = mpsc::channel();
+	let server = thread::spawn(move || {
+		let (mut count, mut rebinds) = (0, Vec::new());
+		let result = serve_resilient(&address, Duration::from_millis(100), |listener, connection| {
+			if connection.is_none() { return true }
+			
+			// Break the listener after the first connection and stop after the second one
+			count += 1;
+			if count == 1 { break_listener(listener) }
+			sender.send(()).unwrap();
+			count < 2
+		}, |_, rebind| rebinds.push(rebind), 1);
+		result.map(|_| rebinds)
+	});
+	
+	// Connect twice (the second connection must be accepted by the rebound listener)
+	let _s0 = connect_retry(address);
+	accepted.recv_timeout(Duration::from_secs(4)).unwrap();
+	let _s1 = connect_retry(address);
+	accepted.recv_timeout(Duration::from_secs(4)).unwrap();
+	assert_eq!(server.join().unwrap().unwrap(), vec![1]);
+}
+#[test] #[cfg(target_os = "linux")]
+fn test_serve_resilient_exhausted() {
+	// Break the listener on the first connection without any rebind budget
+	let address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+	let server = thread::spawn(move || {
+		serve_resilient(&address, Duration::from_millis(100), |listener, connection| {
+			if connection.is_some() { break_listener(listener) }
+			true
+		}, |_, _| panic!("Unexpected rebind"), 0)
+	});
+	
+	let _s0 = connect_retry(address);
+	assert!(server.join().unwrap().is_err());
+}
+#[test]
+fn test_shared_acceptor() {
+	const THREADS: usize = 4;
+	const PER_THREAD: usize = 8;
+	
+	// Accept concurrently
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let address = listener.local_addr().unwrap();
+	let acceptor = Arc::new(SharedAcceptor::new(listener).unwrap());
+	let workers: Vec<_> = (0..THREADS).map(|_| {
+		let acceptor = acceptor.clone();
+		thread::spawn(move || (0..PER_THREAD)
+			.map(|_| {
+				let connection: TcpStream = acceptor.try_accept(Duration::from_secs(8)).unwrap();
+				connection.peer_addr().unwrap()
+			})
+			.collect::<Vec<_>>())
+	}).collect();
+	
+	// Connect and validate that every connection was accepted exactly once
+	let clients: Vec<_> = (0..THREADS * PER_THREAD).map(|_| TcpStream::connect(address).unwrap())
+		.collect();
+	let mut accepted: Vec<_> = workers.into_iter()
+		.flat_map(|worker| worker.join().unwrap())
+		.collect();
+	let mut expected: Vec<_> = clients.iter().map(|client| client.local_addr().unwrap()).collect();
+	accepted.sort();
+	expected.sort();
+	assert_eq!(accepted, expected);
+	
+	// The listener is still non-blocking and there are no pending connections
+	let start = Instant::now();
+	assert_eq!(
+		acceptor.try_accept::<TcpStream>(Duration::from_secs(0)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+	assert!(acceptor.get_ref().accept_nb().unwrap().is_none());
+	assert!(start.elapsed() < Duration::from_secs(1));
+}
+#[test] #[cfg(target_os = "linux")]
+fn test_accept_preconfigured_listener() {
+	use std::{ mem, os::unix::io::{ AsRawFd, FromRawFd } };
+	
+	/// Gets the `SO_RCVBUF`-option of `fd`
+	fn receive_buffer(fd: libc::c_int) -> libc::c_int {
+		let (mut size, mut len) = (0 as libc::c_int, mem::size_of::<libc::c_int>() as libc::socklen_t);
+		let result = unsafe{ libc::getsockopt(
+			fd, libc::SOL_SOCKET, libc::SO_RCVBUF, &mut size as *mut _ as *mut libc::c_void, &mut len
+		) };
+		assert_eq!(result, 0);
+		size
+	}
+	
+	// Create, configure, bind and listen manually (like `socket2` would do)
+	let fd = unsafe{ libc::socket(libc::AF_INET, libc::SOCK_STREAM | libc::SOCK_CLOEXEC, 0) };
+	assert!(fd >= 0);
+	let size: libc::c_int = 32 * 1024;
+	assert_eq!(unsafe{ libc::setsockopt(
+		fd, libc::SOL_SOCKET, libc::SO_RCVBUF,
+		&size as *const _ as *const libc::c_void, mem::size_of::<libc::c_int>() as _
+	) }, 0);
+	let configured = receive_buffer(fd);
+	
+	let mut address: libc::sockaddr_in = unsafe{ mem::zeroed() };
+	address.sin_family = libc::AF_INET as _;
+	address.sin_addr.s_addr = u32::from(std::net::Ipv4Addr::LOCALHOST).to_be();
+	assert_eq!(unsafe{ libc::bind(
+		fd, &address as *const _ as *const libc::sockaddr,
+		mem::size_of::<libc::sockaddr_in>() as _
+	) }, 0);
+	assert_eq!(unsafe{ libc::listen(fd, 16) }, 0);
+	let listener = unsafe{ TcpListener::from_raw_fd(fd) };
+	
+	// Accept through the crate and validate that the option survived
+	let _client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	let _connection: TcpStream = listener.try_accept(Duration::from_secs(4)).unwrap();
+	assert_eq!(receive_buffer(listener.as_raw_fd()), configured);
+}
@@ -0,0 +1,142 @@
+use timeout_io::*;
+use std::{
+	thread, cell::Cell, time::Duration,
+	alloc::{ GlobalAlloc, Layout, System },
+	net::{ TcpListener, TcpStream },
+	sync::mpsc
+};
+
+
+/// An allocator that counts the allocations of the current thread
+struct CountingAllocator;
+thread_local! {
+	static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+unsafe impl GlobalAlloc for CountingAllocator {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+		System.alloc(layout)
+	}
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		System.dealloc(ptr, layout)
+	}
+}
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Counts the allocations performed by `f` on the current thread
+fn allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+	let start = ALLOCATIONS.with(Cell::get);
+	let result = f();
+	(result, ALLOCATIONS.with(Cell::get) - start)
+}
+
+fn socket_pair() -> (TcpStream, TcpStream) {
+	// Create listener
+	let (listener, address) = {
+		// Create listener (to capture the address) and channels
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let address = listener.local_addr().unwrap();
+		let (sender, receiver) = mpsc::channel();
+		
+		// Listen in background
+		thread::spawn(move || sender.send(listener.accept().unwrap().0).unwrap());
+		(receiver, address)
+	};
+	
+	// Create and connect stream
+	let (s0, s1) = (TcpStream::connect(address).unwrap(), listener.recv().unwrap());
+	s0.set_blocking_mode(false).unwrap();
+	s1.set_blocking_mode(false).unwrap();
+	
+	(s0, s1)
+}
+
+
+#[test]
+fn test_wait_for_event_no_allocations() {
+	let (s0, mut s1) = socket_pair();
+	
+	// Time out without allocating
+	let (result, count) = allocations(|| {
+		s0.wait_for_event(EventMask::new_r(), Duration::from_millis(10))
+	});
+	assert_eq!(result.unwrap_err().kind(), TimeoutIoErrorKind::TimedOut);
+	assert_eq!(count, 0);
+	
+	// Wait for an event without allocating
+	s1.try_write_exact(b"Testolope", &mut 0, Duration::from_secs(1)).unwrap();
+	let (result, count) = allocations(|| {
+		s0.wait_for_event(EventMask::new_r(), Duration::from_secs(1))
+	});
+	assert!(result.unwrap().rwe().0);
+	assert_eq!(count, 0);
+	
+	let (result, count) = allocations(|| {
+		s0.wait_for_event_precise(EventMask::new_r(), Duration::from_micros(250))
+	});
+	assert!(result.unwrap().rwe().0);
+	assert_eq!(count, 0);
+}
+#[test]
+fn test_dispatch_no_allocations() {
+	let (s0, s1) = socket_pair();
+	let mut writer = s1.try_clone().unwrap();
+	let mut set = SelectSet::new();
+	set.push(&s0, EventMask::new_r());
+	set.push(&s0, EventMask::new_w());
+	set.push(&s1, EventMask::new_r());
+	
+	// The first dispatch allocates the buffers
+	let fired = set.dispatch(Duration::from_millis(10), |_, _| ()).unwrap();
+	assert_eq!(fired, 1);
+	
+	// Subsequent dispatches reuse them (with and without a read-event)
+	let (result, count) = allocations(|| set.dispatch(Duration::from_millis(10), |_, _| ()));
+	assert_eq!(result.unwrap(), 1);
+	assert_eq!(count, 0);
+	
+	writer.try_write_exact(b"Testolope", &mut 0, Duration::from_secs(1)).unwrap();
+	let mut readable = 0;
+	let (result, count) = allocations(|| {
+		set.dispatch(Duration::from_secs(1), |_, event| if event.rwe().0 { readable += 1 })
+	});
+	assert_eq!((result.unwrap(), readable), (2, 1));
+	assert_eq!(count, 0);
+}
+#[test]
+fn test_select_small_set_allocations() {
+	let (s0, s1) = socket_pair();
+	
+	// Small sets of distinct FDs only allocate the returned events
+	let fds = [(s0.raw_fd(), EventMask::new_r()), (s1.raw_fd(), EventMask::new_w())];
+	let (result, count) = allocations(|| poll_many(&fds, Duration::from_millis(10)));
+	assert!(result.unwrap()[1].rwe().1);
+	assert_eq!(count, 1);
+	
+	let events = [EventMask::new_r(), EventMask::new_w()];
+	let (result, count) = allocations(|| {
+		s0.wait_for_event_multi(&events, Duration::from_millis(10))
+	});
+	assert!(result.unwrap()[1].rwe().1);
+	assert_eq!(count, 1);
+}
+#[test]
+fn test_wait_either_no_allocations() {
+	let (s0, mut s1) = socket_pair();
+	let waker = Waker::new().unwrap();
+	
+	// Time out and wait for an event without allocating
+	let (result, count) = allocations(|| {
+		wait_either(&s0, EventMask::new_r(), &waker, EventMask::new_r(), Duration::from_millis(10))
+	});
+	assert_eq!(result.unwrap_err().kind(), TimeoutIoErrorKind::TimedOut);
+	assert_eq!(count, 0);
+	
+	s1.try_write_exact(b"Testolope", &mut 0, Duration::from_secs(1)).unwrap();
+	let (result, count) = allocations(|| {
+		wait_either(&s0, EventMask::new_r(), &waker, EventMask::new_r(), Duration::from_secs(1))
+	});
+	assert!(matches!(result.unwrap(), (Some(_), None)));
+	assert_eq!(count, 0);
+}
@@ -0,0 +1,100 @@
+use timeout_io::*;
+use std::{
+	time::Duration,
+	io::{ self, Read },
+	net::{ TcpListener, TcpStream }
+};
+
+
+/// A stream wrapper that counts the `read`-calls
+#[cfg(unix)]
+struct CountingStream {
+	stream: TcpStream,
+	reads: usize
+}
+#[cfg(unix)]
+impl Read for CountingStream {
+	fn read(&mut self, buf: &mut[u8]) -> io::Result<usize> {
+		self.reads += 1;
+		self.stream.read(buf)
+	}
+}
+#[cfg(unix)]
+impl std::os::unix::io::AsRawFd for CountingStream {
+	fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+		self.stream.as_raw_fd()
+	}
+}
+
+
+fn socket_pair() -> (TcpStream, TcpStream) {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let s0 = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	let s1 = listener.accept().unwrap().0;
+	(s0.into_nonblocking().unwrap(), s1.into_nonblocking().unwrap())
+}
+
+
+#[test]
+fn test_fill_buf_consume() {
+	let (mut s0, s1) = socket_pair();
+	s0.try_write_exact(b"Testolope", &mut 0, Duration::from_secs(1)).unwrap();
+	
+	let mut reader = TimeoutBufReader::with_capacity(4, s1);
+	assert_eq!(reader.capacity(), 4);
+	assert_eq!(reader.try_fill_buf(Duration::from_secs(4)).unwrap(), b"Test");
+	assert_eq!(reader.try_fill_buf(Duration::from_secs(4)).unwrap(), b"Test");
+	reader.consume(3);
+	assert_eq!(reader.try_fill_buf(Duration::from_secs(4)).unwrap(), b"t");
+	reader.consume(1);
+	assert_eq!(reader.try_fill_buf(Duration::from_secs(4)).unwrap(), b"olop");
+}
+#[test]
+fn test_read_until() {
+	let (mut s0, s1) = socket_pair();
+	s0.try_write_exact(b"Line 1\r\nLine 2\r\nPartial", &mut 0, Duration::from_secs(1)).unwrap();
+	
+	// Read the lines (use a small buffer so that the delimiter straddles two fills)
+	let mut reader = TimeoutBufReader::with_capacity(7, s1);
+	let mut line = Vec::new();
+	assert!(reader.try_read_until(&mut line, b"\r\n", 1024, Duration::from_secs(4)).unwrap());
+	assert_eq!(line, b"Line 1\r\n");
+	
+	line.clear();
+	assert!(reader.try_read_until(&mut line, b"\r\n", 1024, Duration::from_secs(4)).unwrap());
+	assert_eq!(line, b"Line 2\r\n");
+	
+	// Test the limit and the timeout
+	line.clear();
+	assert!(!reader.try_read_until(&mut line, b"\r\n", 4, Duration::from_secs(4)).unwrap());
+	assert_eq!(line, b"Part");
+	assert_eq!(
+		reader.try_read_until(&mut line, b"\r\n", 1024, Duration::from_secs(1)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+	assert_eq!(line, b"Partial");
+}
+#[test] #[cfg(unix)]
+fn test_read_until_syscalls() {
+	// Create a long line
+	let mut data = vec![b'x'; 2048];
+	data.extend_from_slice(b"\r\n");
+	
+	// Read the line with the raw stream
+	let (mut s0, s1) = socket_pair();
+	s0.try_write_exact(&data, &mut 0, Duration::from_secs(1)).unwrap();
+	let mut raw = CountingStream{ stream: s1, reads: 0 };
+	let (mut buf, mut pos) = (vec![0; 4096], 0);
+	assert!(raw.try_read_until(&mut buf, &mut pos, b"\r\n", Duration::from_secs(4)).unwrap());
+	
+	// Read the line with the buffered reader
+	let (mut s0, s1) = socket_pair();
+	s0.try_write_exact(&data, &mut 0, Duration::from_secs(1)).unwrap();
+	let mut buffered = TimeoutBufReader::new(CountingStream{ stream: s1, reads: 0 });
+	let mut line = Vec::new();
+	assert!(buffered.try_read_until(&mut line, b"\r\n", 4096, Duration::from_secs(4)).unwrap());
+	assert_eq!(line, data);
+	
+	let (raw, buffered) = (raw.reads, buffered.get_ref().reads);
+	assert!(buffered * 100 < raw, "Too many reads: {} (raw: {})", buffered, raw);
+}
@@ -0,0 +1,28 @@
+#![cfg(feature = "checked")]
+
+use timeout_io::*;
+use std::{ time::Duration, net::UdpSocket };
+
+
+#[test] #[cfg(unix)]
+fn test_stale_fd() {
+	use std::os::unix::io::AsRawFd;
+	
+	// Close a socket and wait on it's stale FD
+	let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+	let stale = socket.as_raw_fd() as u64;
+	drop(socket);
+	
+	assert_eq!(
+		poll_many(&[(stale, EventMask::new_r())], Duration::from_secs(1)).unwrap_err(),
+		TimeoutIoError::InvalidInput
+	);
+}
+#[test]
+fn test_valid_fd() {
+	let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+	assert_eq!(
+		socket.wait_for_event(EventMask::new_r(), Duration::from_millis(100)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+}
@@ -0,0 +1,71 @@
+use timeout_io::*;
+use std::{
+	thread,
+	io::Write,
+	time::Duration,
+	net::{ TcpListener, TcpStream }
+};
+
+
+fn socket_pair() -> (TcpStream, TcpStream) {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let s0 = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	let s1 = listener.accept().unwrap().0;
+	(s0.into_nonblocking().unwrap(), s1.into_nonblocking().unwrap())
+}
+
+
+#[test]
+fn test_read_frame() {
+	let (mut s0, s1) = socket_pair();
+	let mut s1 = s1.into_blocking().unwrap();
+	
+	// Write two frames at once and a third frame in chunks (split within the prefix)
+	s1.write_all(b"\x00\x00\x00\x04Test\x00\x00\x00\x05olope").unwrap();
+	thread::spawn(move || {
+		for chunk in [&b"\x00\x00"[..], b"\x00\x01", b"!"].iter() {
+			thread::sleep(Duration::from_millis(100));
+			s1.write_all(chunk).unwrap();
+		}
+	});
+	
+	// Decode the frames
+	let (mut decoder, mut buf) = (LengthPrefixedDecoder::new(Endianness::Big, 1024), Vec::new());
+	let frame = s0.try_read_frame(&mut decoder, &mut buf, Duration::from_secs(4)).unwrap();
+	assert_eq!(frame, b"Test");
+	assert_eq!(buf, b"\x00\x00\x00\x05olope");
+	let frame = s0.try_read_frame(&mut decoder, &mut buf, Duration::from_secs(0)).unwrap();
+	assert_eq!(frame, b"olope");
+	let frame = s0.try_read_frame(&mut decoder, &mut buf, Duration::from_secs(4)).unwrap();
+	assert_eq!(frame, b"!");
+	
+	// The connection was closed between two frames
+	assert_eq!(
+		s0.try_read_frame(&mut decoder, &mut buf, Duration::from_secs(4)).unwrap_err(),
+		TimeoutIoError::GracefulEof
+	);
+}
+#[test]
+fn test_read_frame_err() {
+	let (mut s0, s1) = socket_pair();
+	let mut s1 = s1.into_blocking().unwrap();
+	
+	// A frame that exceeds the limit
+	s1.write_all(b"\x05\x00\x00\x00olope").unwrap();
+	let (mut decoder, mut buf) = (LengthPrefixedDecoder::new(Endianness::Little, 4), Vec::new());
+	assert_eq!(
+		s0.try_read_frame(&mut decoder, &mut buf, Duration::from_secs(4)).unwrap_err(),
+		TimeoutIoError::LimitExceeded
+	);
+	
+	// A truncated frame
+	let (mut s0, s1) = socket_pair();
+	let mut s1 = s1.into_blocking().unwrap();
+	let (mut decoder, mut buf) = (LengthPrefixedDecoder::new(Endianness::Little, 1024), Vec::new());
+	s1.write_all(b"\x05\x00\x00\x00Test").unwrap();
+	drop(s1);
+	assert_eq!(
+		s0.try_read_frame(&mut decoder, &mut buf, Duration::from_secs(4)).unwrap_err(),
+		TimeoutIoError::UnexpectedEof
+	);
+}
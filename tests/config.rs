@@ -0,0 +1,60 @@
+use timeout_io::*;
+use std::{
+	time::{ Duration, Instant },
+	net::{ TcpListener, TcpStream }
+};
+
+
+fn socket_pair() -> (TcpStream, TcpStream) {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let s0 = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	let s1 = listener.accept().unwrap().0;
+	(s0.into_nonblocking().unwrap(), s1.into_nonblocking().unwrap())
+}
+
+
+// Since the config is process-wide, everything is tested in one function
+#[test]
+fn test_default_config() {
+	let (mut s0, mut s1) = socket_pair();
+	assert_eq!(TimeoutConfig::current(), TimeoutConfig::new(Duration::from_secs(30)));
+	
+	// Install a short read-timeout
+	TimeoutConfig::new(Duration::from_secs(4)).read(Duration::from_millis(500)).install();
+	assert_eq!(TimeoutConfig::current().read_timeout(), Duration::from_millis(500));
+	assert_eq!(TimeoutConfig::current().write_timeout(), Duration::from_secs(4));
+	
+	let start = Instant::now();
+	assert_eq!(
+		s0.try_read_default(&mut [0; 4], &mut 0).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+	let elapsed = start.elapsed();
+	assert!(elapsed >= Duration::from_millis(450) && elapsed < Duration::from_secs(2));
+	
+	// Change the default again
+	TimeoutConfig::new(Duration::from_millis(1500)).install();
+	let start = Instant::now();
+	assert_eq!(
+		s0.try_read_default(&mut [0; 4], &mut 0).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+	assert!(start.elapsed() >= Duration::from_millis(1450));
+	
+	// Write and read with the default
+	let mut pos = 0;
+	s1.try_write_default(b"Testolope", &mut pos).unwrap();
+	assert_eq!(pos, 9);
+	
+	let (mut buf, mut pos) = ([0; 9], 0);
+	s0.try_read_default(&mut buf, &mut pos).unwrap();
+	assert_eq!(&buf[..pos], &b"Testolope"[..pos]);
+	
+	// An explicit timeout overrides the default
+	let start = Instant::now();
+	assert_eq!(
+		s0.try_read(&mut [0; 4], &mut 0, Duration::from_millis(100)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+	assert!(start.elapsed() < Duration::from_millis(1000));
+}
@@ -0,0 +1,69 @@
+use timeout_io::*;
+use std::{
+	time::Duration,
+	net::{ TcpListener, SocketAddr }
+};
+
+
+/// Returns an address where nobody listens
+fn closed_address() -> SocketAddr {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	listener.local_addr().unwrap()
+}
+
+
+#[test]
+fn test_connect_any_last() {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let addrs = [closed_address(), closed_address(), listener.local_addr().unwrap()];
+	
+	let stream = connect_any(&addrs, Duration::from_secs(1), Duration::from_secs(4)).unwrap();
+	assert_eq!(stream.peer_addr().unwrap(), listener.local_addr().unwrap());
+}
+#[test]
+fn test_connect_any_refused() {
+	let addrs = [closed_address(), closed_address()];
+	assert_eq!(
+		connect_any(&addrs, Duration::from_secs(1), Duration::from_secs(4)).unwrap_err().kind(),
+		TimeoutIoErrorKind::Refused
+	);
+}
+#[test]
+fn test_connect_any_budget() {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let addrs = [listener.local_addr().unwrap()];
+	assert_eq!(
+		connect_any(&addrs, Duration::from_secs(1), Duration::from_secs(0)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+	assert_eq!(
+		connect_any(&[], Duration::from_secs(1), Duration::from_secs(4)).unwrap_err(),
+		TimeoutIoError::InvalidInput
+	);
+}
+#[test] #[cfg(unix)]
+fn test_socket_pair() {
+	let (mut s0, mut s1) = socket_pair().unwrap();
+	
+	// Round-trip data in both directions
+	s0.try_write_exact(b"Testolope", &mut 0, Duration::from_secs(1)).unwrap();
+	let mut buf = [0; 9];
+	s1.try_read_exact(&mut buf, &mut 0, Duration::from_secs(1)).unwrap();
+	assert_eq!(&buf, b"Testolope");
+	
+	s1.try_write_exact(b"Testolope", &mut 0, Duration::from_secs(1)).unwrap();
+	let mut buf = [0; 9];
+	s0.try_read_exact(&mut buf, &mut 0, Duration::from_secs(1)).unwrap();
+	assert_eq!(&buf, b"Testolope");
+	
+	// Both ends are non-blocking
+	use std::io::{ Read, ErrorKind::WouldBlock };
+	assert_eq!(s0.read(&mut buf).unwrap_err().kind(), WouldBlock);
+	assert_eq!(s1.read(&mut buf).unwrap_err().kind(), WouldBlock);
+	
+	// Both ends are close-on-exec
+	use std::os::unix::io::AsRawFd;
+	for fd in [s0.as_raw_fd(), s1.as_raw_fd()] {
+		assert_ne!(unsafe{ libc::fcntl(fd, libc::F_GETFD) } & libc::FD_CLOEXEC, 0);
+	}
+}
@@ -0,0 +1,122 @@
+use timeout_io::*;
+use std::{
+	time::Duration,
+	net::UdpSocket
+};
+
+
+fn socket_pair() -> (UdpSocket, UdpSocket) {
+	let s0 = UdpSocket::bind("127.0.0.1:0").unwrap();
+	let s1 = UdpSocket::bind("127.0.0.1:0").unwrap();
+	s0.set_blocking_mode(false).unwrap();
+	s1.set_blocking_mode(false).unwrap();
+	(s0, s1)
+}
+
+
+#[test]
+fn test_mmsg_ok() {
+	let (s0, s1) = socket_pair();
+	let address = s1.local_addr().unwrap();
+	
+	let msgs: Vec<(&[u8], _)> = vec![(b"Test", address), (b"olope", address), (b"!", address)];
+	assert_eq!(s0.try_send_mmsg(&msgs, Duration::from_secs(1)).unwrap(), 3);
+	
+	// Receive into more buffers than datagrams are available
+	let mut bufs = [[0u8; 16]; 4];
+	let mut buf_refs: Vec<&mut[u8]> = bufs.iter_mut().map(|b| b.as_mut()).collect();
+	let received = s1.try_receive_mmsg(&mut buf_refs, Duration::from_secs(1)).unwrap();
+	
+	assert_eq!(received.len(), 3);
+	assert!(received.iter().all(|(_, from)| *from == s0.local_addr().unwrap()));
+	assert_eq!(&bufs[0][..received[0].0], b"Test");
+	assert_eq!(&bufs[1][..received[1].0], b"olope");
+	assert_eq!(&bufs[2][..received[2].0], b"!");
+}
+#[test]
+fn test_mmsg_timeout() {
+	let (_s0, s1) = socket_pair();
+	
+	let mut buf = [0u8; 16];
+	assert_eq!(
+		s1.try_receive_mmsg(&mut [&mut buf], Duration::from_secs(1)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	)
+}
+#[test]
+fn test_mmsg_batch() {
+	const BATCH: usize = 32;
+	let (s0, s1) = socket_pair();
+	let address = s1.local_addr().unwrap();
+	
+	// A whole batch is sent with one call (i.e. one `sendmmsg`-syscall on Linux)
+	let msgs: Vec<(&[u8], _)> = (0..BATCH).map(|_| (&b"Testolope"[..], address)).collect();
+	assert_eq!(s0.try_send_mmsg(&msgs, Duration::from_secs(1)).unwrap(), BATCH);
+	
+	// The whole batch is received with one call, too (loopback-datagrams are queued immediately)
+	let mut bufs = [[0u8; 16]; BATCH + 1];
+	let mut buf_refs: Vec<&mut[u8]> = bufs.iter_mut().map(|b| b.as_mut()).collect();
+	let received = s1.try_receive_mmsg(&mut buf_refs, Duration::from_secs(1)).unwrap();
+	assert_eq!(received.len(), BATCH);
+	assert!(bufs[..BATCH].iter().all(|buf| buf.starts_with(b"Testolope")));
+}
+#[test]
+fn test_accept_udp() {
+	let server = UdpSocket::bind("127.0.0.1:0").unwrap().into_nonblocking().unwrap();
+	let (c0, c1) = socket_pair();
+	
+	// Send the initial datagrams
+	c0.send_to(b"Hello from 0", server.local_addr().unwrap()).unwrap();
+	c1.send_to(b"Hello from 1", server.local_addr().unwrap()).unwrap();
+	
+	// Accept both peers and reply over the connected sockets
+	for _ in 0..2 {
+		let (socket, peer, payload) = server.accept_udp(Duration::from_secs(4)).unwrap();
+		assert_eq!(socket.peer_addr().unwrap(), peer);
+		match peer {
+			p if p == c0.local_addr().unwrap() => assert_eq!(payload, b"Hello from 0"),
+			p if p == c1.local_addr().unwrap() => assert_eq!(payload, b"Hello from 1"),
+			p => panic!("Unexpected peer: {}", p)
+		}
+		socket.send(&payload).unwrap();
+	}
+	
+	// Receive the replies (which originate from the new sockets)
+	for client in [c0, c1].iter() {
+		let mut buf = [0u8; 16];
+		let mut bufs = [&mut buf[..]];
+		let received = client.try_receive_mmsg(&mut bufs, Duration::from_secs(4)).unwrap();
+		assert_ne!(received[0].1, server.local_addr().unwrap());
+		assert!(buf.starts_with(b"Hello from "));
+	}
+	
+	// The server socket keeps listening
+	assert_eq!(
+		server.accept_udp(Duration::from_millis(100)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+}
+#[test]
+fn test_receive_vec() {
+	let (s0, s1) = socket_pair();
+	let address = s1.local_addr().unwrap();
+	
+	// Send a datagram that is larger than a default small buffer and an empty datagram
+	let data: Vec<u8> = (0..9000).map(|i| i as u8).collect();
+	s0.send_to(&data, address).unwrap();
+	s0.send_to(b"", address).unwrap();
+	s0.send_to(b"Testolope", address).unwrap();
+	
+	let (received, from) = s1.try_receive_vec(65536, Duration::from_secs(4)).unwrap();
+	assert_eq!(from, s0.local_addr().unwrap());
+	assert_eq!(received, data);
+	assert!(s1.try_receive_vec(65536, Duration::from_secs(4)).unwrap().0.is_empty());
+	
+	// Respect `max`
+	let (received, _) = s1.try_receive_vec(4, Duration::from_secs(4)).unwrap();
+	assert_eq!(received, b"Test");
+	assert_eq!(
+		s1.try_receive_vec(4, Duration::from_millis(100)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+}
@@ -0,0 +1,53 @@
+use timeout_io::*;
+use std::{
+	thread,
+	time::{ Duration, Instant },
+	net::{ TcpListener, TcpStream }
+};
+
+
+fn socket_pair() -> (TcpStream, TcpStream) {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let s0 = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	let s1 = listener.accept().unwrap().0;
+	(s0.into_nonblocking().unwrap(), s1.into_nonblocking().unwrap())
+}
+
+
+#[test]
+fn test_deadline_reader_ok() {
+	let (mut s0, s1) = socket_pair();
+	s0.try_write_exact(b"Testolope", &mut 0, Duration::from_secs(1)).unwrap();
+	
+	let mut reader = DeadlineReader::new(s1, Duration::from_secs(4));
+	let (mut buf, mut pos) = ([0u8; 9], 0);
+	reader.try_read_exact(&mut buf[..4], &mut pos).unwrap();
+	reader.try_read_exact(&mut buf, &mut pos).unwrap();
+	assert_eq!(&buf, b"Testolope");
+	assert!(reader.remaining() <= Duration::from_secs(4));
+}
+#[test]
+fn test_deadline_reader_never_idle() {
+	let (mut s0, s1) = socket_pair();
+	
+	// Drip-feed data forever
+	thread::spawn(move || loop {
+		if s0.try_write_exact(b"!", &mut 0, Duration::from_secs(1)).is_err() { break }
+		thread::sleep(Duration::from_millis(50));
+	});
+	
+	// Each single read succeeds, but all reads together cannot exceed the deadline
+	let (start, mut reader) = (Instant::now(), DeadlineReader::new(s1, Duration::from_secs(1)));
+	let error = loop {
+		if let Err(error) = reader.try_read(&mut [0; 16], &mut 0) { break error }
+	};
+	assert_eq!(error.kind(), TimeoutIoErrorKind::TimedOut);
+	assert!(start.elapsed() < Duration::from_millis(1500));
+	
+	// Once expired, the reader times out immediately even if data is available
+	thread::sleep(Duration::from_millis(200));
+	assert_eq!(
+		reader.try_read(&mut [0; 16], &mut 0).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+}
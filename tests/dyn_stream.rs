@@ -0,0 +1,49 @@
+use timeout_io::*;
+use std::{
+	io::Write,
+	time::Duration,
+	net::{ TcpListener, TcpStream }
+};
+
+
+fn socket_pair() -> (TcpStream, TcpStream) {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let s0 = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	let s1 = listener.accept().unwrap().0;
+	s0.set_blocking_mode(false).unwrap();
+	(s0, s1)
+}
+
+
+#[test]
+fn test_dyn_stream_boxed() {
+	let (s0, mut s1) = socket_pair();
+	let mut boxed = Box::new(s0);
+	
+	// Read and write through the erased stream
+	let mut stream = DynStream::new(&mut *boxed);
+	s1.write_all(b"Testolope").unwrap();
+	let (mut buf, mut pos) = ([0; 9], 0);
+	stream.try_read_exact(&mut buf, &mut pos, Duration::from_secs(4)).unwrap();
+	assert_eq!(&buf, b"Testolope");
+	stream.try_write_exact(b"Test", &mut 0, Duration::from_secs(4)).unwrap();
+	
+	// Timeouts work as usual
+	assert_eq!(
+		stream.try_read_exact(&mut buf, &mut 0, Duration::from_millis(200)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+}
+#[test]
+fn test_dyn_stream_erased() {
+	let (s0, mut s1) = socket_pair();
+	let fd = s0.raw_fd();
+	
+	// Store heterogeneous streams as trait objects and erase them for reading
+	let mut streams: Vec<Box<dyn ReadWrite>> = vec![Box::new(s0)];
+	let mut stream = unsafe{ DynStream::from_raw_parts(&mut *streams[0], fd) };
+	s1.write_all(b"Testolope").unwrap();
+	let mut buf = Vec::new();
+	stream.try_read_append(&mut buf, 9, Duration::from_secs(4)).unwrap();
+	assert_eq!(buf, b"Testolope");
+}
@@ -0,0 +1,232 @@
+use timeout_io::*;
+use std::{
+	io, time::Duration, collections::HashMap, net::TcpListener,
+	sync::mpsc::{ self, RecvTimeoutError, TryRecvError, RecvError }
+};
+
+
+/// A `TimedOut`-error with arbitrary durations (the durations are ignored by comparisons)
+const TIMED_OUT: TimeoutIoError =
+	TimeoutIoError::TimedOut{ waited: Duration::from_secs(0), limit: Duration::from_secs(0) };
+
+
+#[test]
+fn test_from_recv_timeout_error() {
+	assert_eq!(
+		TimeoutIoError::from(RecvTimeoutError::Timeout).kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+	assert_eq!(
+		TimeoutIoError::from(RecvTimeoutError::Disconnected).kind(),
+		TimeoutIoErrorKind::ConnectionLost
+	);
+}
+#[test]
+fn test_from_try_recv_error() {
+	assert_eq!(TimeoutIoError::from(TryRecvError::Empty).kind(), TimeoutIoErrorKind::TimedOut);
+	assert_eq!(
+		TimeoutIoError::from(TryRecvError::Disconnected).kind(),
+		TimeoutIoErrorKind::ConnectionLost
+	);
+	assert_eq!(TimeoutIoError::from(RecvError).kind(), TimeoutIoErrorKind::ConnectionLost);
+}
+#[test]
+fn test_from_recv_timeout_error_channel() {
+	let (sender, receiver) = mpsc::channel::<()>();
+	assert_eq!(
+		TimeoutIoError::from(receiver.recv_timeout(Duration::from_millis(10)).unwrap_err()).kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+	
+	drop(sender);
+	assert_eq!(
+		TimeoutIoError::from(receiver.recv_timeout(Duration::from_millis(10)).unwrap_err()).kind(),
+		TimeoutIoErrorKind::ConnectionLost
+	);
+}
+
+#[test]
+fn test_kind() {
+	let (a, b) = (
+		TimeoutIoError::Other{ desc: "a".to_string() },
+		TimeoutIoError::Other{ desc: "b".to_string() }
+	);
+	assert_ne!(a, b);
+	assert_eq!(a.kind(), b.kind());
+	assert_eq!(a.kind(), TimeoutIoErrorKind::Other);
+	assert_eq!(TIMED_OUT.kind(), TimeoutIoErrorKind::TimedOut);
+	
+	// Aggregate by kind
+	let mut counters = HashMap::new();
+	for error in [a, b, TIMED_OUT].iter() {
+		*counters.entry(error.kind()).or_insert(0) += 1;
+	}
+	assert_eq!(counters[&TimeoutIoErrorKind::Other], 2);
+	assert_eq!(counters[&TimeoutIoErrorKind::TimedOut], 1);
+}
+
+#[test]
+fn test_from_io_error_addr() {
+	let mappings = [
+		(io::ErrorKind::AddrInUse, TimeoutIoErrorKind::AddrInUse),
+		(io::ErrorKind::AddrNotAvailable, TimeoutIoErrorKind::AddrNotAvailable),
+		(io::ErrorKind::PermissionDenied, TimeoutIoErrorKind::PermissionDenied)
+	];
+	for (kind, expected) in mappings.iter() {
+		let error = TimeoutIoError::from(io::Error::new(*kind, "Synthesized error"));
+		assert_eq!(&error.kind(), expected);
+		assert!(!error.should_retry());
+	}
+}
+#[test]
+fn test_bind_addr_in_use() {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let error = TcpListener::bind(listener.local_addr().unwrap()).unwrap_err();
+	assert_eq!(TimeoutIoError::from(error).kind(), TimeoutIoErrorKind::AddrInUse);
+}
+#[test]
+fn test_from_desc() {
+	let expected = TimeoutIoError::Other{ desc: "Invalid handshake".to_string() };
+	assert_eq!(TimeoutIoError::other("Invalid handshake"), expected);
+	assert_eq!(TimeoutIoError::other(String::from("Invalid handshake")), expected);
+	assert_eq!(TimeoutIoError::from("Invalid handshake"), expected);
+	assert_eq!(TimeoutIoError::from("Invalid handshake".to_string()), expected);
+	
+	// `?` converts both descriptions and `io::Error`s
+	fn fails(io: bool) -> Result<(), TimeoutIoError> {
+		if io { Err(io::Error::from(io::ErrorKind::TimedOut))? }
+		Err("Invalid handshake")?
+	}
+	assert_eq!(fails(true).unwrap_err().kind(), TimeoutIoErrorKind::TimedOut);
+	assert_eq!(fails(false).unwrap_err(), expected);
+}
+#[test]
+fn test_timed_out_durations() {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let stream = std::net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	let (mut stream, _peer) = (stream.into_nonblocking().unwrap(), listener.accept().unwrap());
+	
+	// A full wait reports the timeout of the whole call
+	let (mut buf, mut pos) = ([0; 9], 0);
+	let error = stream.try_read_exact(&mut buf, &mut pos, Duration::from_millis(500)).unwrap_err();
+	match error {
+		TimeoutIoError::TimedOut{ waited, limit } => {
+			assert_eq!(limit, Duration::from_millis(500));
+			assert!(waited >= Duration::from_millis(450), "{:?}", error);
+		},
+		error => panic!("Unexpected error: {:?}", error)
+	}
+	
+	// An almost immediate timeout due to a tiny budget
+	let error = stream.wait_for_event(EventMask::new_r(), Duration::from_millis(1)).unwrap_err();
+	match error {
+		TimeoutIoError::TimedOut{ waited, limit } => {
+			assert_eq!(limit, Duration::from_millis(1));
+			assert!(waited < Duration::from_millis(450), "{:?}", error);
+		},
+		error => panic!("Unexpected error: {:?}", error)
+	}
+	
+	// A non-blocking attempt does not wait and multi-handle waits report their durations, too
+	let error = stream.try_read(&mut buf, &mut pos, Duration::from_secs(0)).unwrap_err();
+	assert!(matches!(error, TimeoutIoError::TimedOut{ limit, .. } if limit.is_zero()));
+	let (r, timeout) = (EventMask::new_r(), Duration::from_millis(100));
+	let error = wait_either(&stream, r, &stream, r, timeout).unwrap_err();
+	assert!(matches!(error, TimeoutIoError::TimedOut{ waited, .. } if !waited.is_zero()));
+	
+	// The durations are ignored by comparisons
+	assert_eq!(error, TIMED_OUT);
+}
+#[test]
+fn test_from_io_error_unreachable() {
+	let mappings = [
+		(io::ErrorKind::ConnectionRefused, TimeoutIoErrorKind::Refused),
+		(io::ErrorKind::HostUnreachable, TimeoutIoErrorKind::Unreachable),
+		(io::ErrorKind::NetworkUnreachable, TimeoutIoErrorKind::Unreachable)
+	];
+	for (kind, expected) in mappings.iter() {
+		let error = TimeoutIoError::from(io::Error::new(*kind, "Synthesized error"));
+		assert_eq!(&error.kind(), expected);
+		assert_ne!(error.kind(), TimeoutIoErrorKind::Other);
+		assert!(!error.should_retry());
+	}
+}
+#[test]
+fn test_connect_refused() {
+	let address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+	let error = std::net::TcpStream::connect(address).unwrap_err();
+	assert_eq!(TimeoutIoError::from(error).kind(), TimeoutIoErrorKind::Refused);
+}
+#[test]
+fn test_classification() {
+	let transient = [TimeoutIoError::InterruptedSyscall];
+	let timeout = [TIMED_OUT];
+	let fatal = [
+		TimeoutIoError::UnexpectedEof, TimeoutIoError::GracefulEof,
+		TimeoutIoError::ConnectionLost{ source: None }, TimeoutIoError::NotFound,
+		TimeoutIoError::InvalidInput, TimeoutIoError::LimitExceeded,
+		TimeoutIoError::AddrInUse{ source: None }, TimeoutIoError::AddrNotAvailable{ source: None },
+		TimeoutIoError::PermissionDenied{ source: None }, TimeoutIoError::TooSlow,
+		TimeoutIoError::Refused{ source: None }, TimeoutIoError::Unreachable{ source: None },
+		TimeoutIoError::other("Synthesized error")
+	];
+	for error in transient.iter() {
+		assert!(error.is_transient() && !error.is_timeout() && error.should_retry());
+	}
+	for error in timeout.iter() {
+		assert!(!error.is_transient() && error.is_timeout() && error.should_retry());
+	}
+	for error in fatal.iter() {
+		assert!(!error.is_transient() && !error.is_timeout() && !error.should_retry(), "{}", error);
+	}
+	
+	// `WouldBlock` is a timeout
+	let error = TimeoutIoError::from(io::Error::from(io::ErrorKind::WouldBlock));
+	assert!(error.is_timeout());
+	let error = TimeoutIoError::from(io::Error::from(io::ErrorKind::Interrupted));
+	assert!(error.is_transient());
+}
+#[test]
+fn test_into_io_error() {
+	// All variants round-trip
+	let errors = [
+		TimeoutIoError::InterruptedSyscall, TIMED_OUT, TimeoutIoError::UnexpectedEof,
+		TimeoutIoError::GracefulEof, TimeoutIoError::ConnectionLost{ source: None },
+		TimeoutIoError::NotFound, TimeoutIoError::InvalidInput, TimeoutIoError::LimitExceeded,
+		TimeoutIoError::AddrInUse{ source: None }, TimeoutIoError::AddrNotAvailable{ source: None },
+		TimeoutIoError::PermissionDenied{ source: None }, TimeoutIoError::TooSlow,
+		TimeoutIoError::Refused{ source: None }, TimeoutIoError::Unreachable{ source: None },
+		TimeoutIoError::other("Testolope")
+	];
+	for error in errors {
+		assert_eq!(TimeoutIoError::from(error.clone().into_io_error()), error);
+	}
+	assert_eq!(TimeoutIoError::NotFound.into_io_error().kind(), io::ErrorKind::NotFound);
+	assert_eq!(TIMED_OUT.into_io_error().kind(), io::ErrorKind::TimedOut);
+	
+	// A `ConnectionLost` created from a real `io::Error` keeps it's kind
+	let original = io::Error::from(io::ErrorKind::ConnectionReset);
+	let error = TimeoutIoError::from(original).into_io_error();
+	assert_eq!(error.kind(), io::ErrorKind::ConnectionReset);
+	
+	// ... and it's raw OS error code
+	#[cfg(unix)]
+	let code = libc::ECONNRESET;
+	#[cfg(windows)]
+	let code = 10054; // WSAECONNRESET
+	let error = TimeoutIoError::from(io::Error::from_raw_os_error(code));
+	assert_eq!(error.kind(), TimeoutIoErrorKind::ConnectionLost);
+	assert_eq!(error.as_io_error().unwrap().raw_os_error(), Some(code));
+	assert!(std::error::Error::source(&error).is_some());
+	assert_eq!(error.into_io_error().raw_os_error(), Some(code));
+	
+	// Errors created by this crate itself don't retain an `io::Error`
+	assert!(TimeoutIoError::from(RecvError).as_io_error().is_none());
+	assert!(TimeoutIoError::NotFound.as_io_error().is_none());
+	
+	// The wrapped `TimeoutIoError` can be recovered
+	let error = TimeoutIoError::other("Testolope").into_io_error();
+	assert_eq!(error.kind(), io::ErrorKind::Other);
+	let inner = error.get_ref().unwrap().downcast_ref::<TimeoutIoError>().unwrap();
+	assert_eq!(inner, &TimeoutIoError::other("Testolope"));
+}
@@ -0,0 +1,481 @@
+use timeout_io::*;
+use std::{
+	thread, io::{ Read, Write },
+	time::{ Duration, Instant },
+	net::{ TcpListener, TcpStream }
+};
+
+
+fn socket_pair() -> (TcpStream, TcpStream) {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let s0 = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	let s1 = listener.accept().unwrap().0;
+	(s0.into_nonblocking().unwrap(), s1.into_nonblocking().unwrap())
+}
+
+
+#[test] #[cfg(unix)]
+fn test_wait_either_wakeup() {
+	use std::os::unix::net::UnixStream;
+	
+	// Create a socket and a self-pipe
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	let (wakeup_r, mut wakeup_w) = UnixStream::pair().unwrap();
+	
+	// Interrupt the wait by writing to the pipe
+	thread::spawn(move || {
+		thread::sleep(Duration::from_secs(1));
+		wakeup_w.write_all(b"!").unwrap();
+	});
+	let (a, b) = wait_either(
+		&stream, EventMask::new_r(), &wakeup_r, EventMask::new_r(),
+		Duration::from_secs(4)
+	).unwrap();
+	assert_eq!(a, None);
+	assert_eq!(b.unwrap().rwe(), (true, false, false));
+}
+#[test]
+fn test_wait_either_ok() {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	
+	let (a, b) = wait_either(
+		&listener, EventMask::new_r(), &stream, EventMask::new_w(),
+		Duration::from_secs(4)
+	).unwrap();
+	assert_eq!(a.unwrap().rwe(), (true, false, false));
+	assert_eq!(b.unwrap().rwe(), (false, true, false));
+}
+#[test]
+fn test_wait_either_timeout() {
+	let l0 = TcpListener::bind("127.0.0.1:0").unwrap();
+	let l1 = TcpListener::bind("127.0.0.1:0").unwrap();
+	assert_eq!(
+		wait_either(&l0, EventMask::new_r(), &l1, EventMask::new_r(), Duration::from_secs(1))
+			.unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	)
+}
+
+
+#[test]
+fn test_select_grouped() {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let (s0, s1) = (
+		TcpStream::connect(listener.local_addr().unwrap()).unwrap(),
+		TcpStream::connect(listener.local_addr().unwrap()).unwrap()
+	);
+	let (mut s2, _) = listener.accept().unwrap();
+	let (_s3, _) = listener.accept().unwrap();
+	s2.write_all(b"Testolope").unwrap();
+	thread::sleep(Duration::from_secs(1));
+	
+	// `s0` is readable and writeable, `s1` is only writeable
+	let mut set = SelectSet::new();
+	set.push(&s0, EventMask::new_rw());
+	set.push(&s1, EventMask::new_rw());
+	let groups = set.select_grouped(Duration::from_secs(4)).unwrap();
+	
+	let fds = |handles: &[&TcpStream]| handles.iter().map(|h| h.raw_fd()).collect::<Vec<_>>();
+	assert_eq!(fds(&groups.readable), vec![s0.raw_fd()]);
+	assert_eq!(fds(&groups.writable), vec![s0.raw_fd(), s1.raw_fd()]);
+	assert!(groups.errored.is_empty());
+}
+
+
+#[test]
+fn test_into_blocking_nonblocking() {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	stream.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+	
+	// A non-blocking read returns immediately
+	let mut stream = stream.into_nonblocking().unwrap();
+	let start = Instant::now();
+	stream.read(&mut [0; 4]).unwrap_err();
+	assert!(start.elapsed() < Duration::from_secs(1));
+	
+	// A blocking read waits for the read timeout
+	let mut stream = stream.into_blocking().unwrap();
+	let start = Instant::now();
+	stream.read(&mut [0; 4]).unwrap_err();
+	assert!(start.elapsed() >= Duration::from_secs(1));
+}
+#[test] #[cfg(unix)]
+fn test_set_blocking_mode_flags() {
+	use std::{ fs::File, os::unix::{ io::AsRawFd, net::UnixStream } };
+	
+	/// Whether `O_NONBLOCK` is set on `fd`
+	fn is_nonblocking(fd: &impl AsRawFd) -> bool {
+		let flags = unsafe{ libc::fcntl(fd.as_raw_fd(), libc::F_GETFL, 0) };
+		assert_ne!(flags, -1);
+		flags & libc::O_NONBLOCK != 0
+	}
+	
+	// Toggle the mode on a socket and a non-socket FD and ensure that the other flags are kept
+	let (socket, _peer) = UnixStream::pair().unwrap();
+	let file = File::open("/dev/null").unwrap();
+	let flags = unsafe{ libc::fcntl(file.as_raw_fd(), libc::F_GETFL, 0) };
+	for _ in 0..2 {
+		socket.set_blocking_mode(false).unwrap();
+		file.set_blocking_mode(false).unwrap();
+		assert!(is_nonblocking(&socket) && is_nonblocking(&file));
+		
+		socket.set_blocking_mode(true).unwrap();
+		file.set_blocking_mode(true).unwrap();
+		assert!(!is_nonblocking(&socket) && !is_nonblocking(&file));
+		assert_eq!(unsafe{ libc::fcntl(file.as_raw_fd(), libc::F_GETFL, 0) }, flags);
+	}
+	
+	// A non-blocking read fails with `WouldBlock`
+	let mut socket = socket.into_nonblocking().unwrap();
+	assert_eq!(socket.read(&mut [0; 4]).unwrap_err().kind(), std::io::ErrorKind::WouldBlock);
+}
+
+
+#[test]
+fn test_select_duplicate_handle() {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	listener.accept().unwrap().0.write_all(b"Testolope").unwrap();
+	thread::sleep(Duration::from_secs(1));
+	
+	// Register the same (readable and writeable) handle for read and write separately
+	let mut set = SelectSet::new();
+	set.push(&stream, EventMask::new_r());
+	set.push(&stream, EventMask::new_w());
+	let events = set.select(Duration::from_secs(4)).unwrap();
+	
+	assert_eq!(events.len(), 2);
+	assert_eq!(events[0].1.rwe(), (true, false, false));
+	assert_eq!(events[1].1.rwe(), (false, true, false));
+}
+#[test]
+fn test_select_duplicate_handle_merged() {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let (s0, s1) = (
+		TcpStream::connect(listener.local_addr().unwrap()).unwrap(),
+		TcpStream::connect(listener.local_addr().unwrap()).unwrap()
+	);
+	listener.accept().unwrap().0.write_all(b"Testolope").unwrap();
+	thread::sleep(Duration::from_secs(1));
+	
+	// Register the same handle three times (and another handle once)
+	let mut set = SelectSet::new();
+	set.push(&s0, EventMask::new_r());
+	set.push(&s0, EventMask::new_w());
+	set.push(&s1, EventMask::new_w());
+	set.push(&s0, EventMask::new_rw());
+	assert_eq!(set.fd_count(), 2);
+	let events = set.select(Duration::from_secs(4)).unwrap();
+	
+	// Every registration is reported with the events of it's own mask
+	assert_eq!(events.len(), 4);
+	assert!(std::ptr::eq(events[0].0, &s0) && std::ptr::eq(events[3].0, &s0));
+	assert_eq!(events[0].1.rwe(), (true, false, false));
+	assert_eq!(events[1].1.rwe(), (false, true, false));
+	assert_eq!(events[2].1.rwe(), (false, true, false));
+	assert_eq!(events[3].1.rwe(), (true, true, false));
+}
+
+
+#[test]
+fn test_poll_many_equivalence() {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let (s0, s1) = (
+		TcpStream::connect(listener.local_addr().unwrap()).unwrap(),
+		TcpStream::connect(listener.local_addr().unwrap()).unwrap()
+	);
+	listener.accept().unwrap().0.write_all(b"Testolope").unwrap();
+	thread::sleep(Duration::from_secs(1));
+	
+	// Poll the raw FDs
+	let polled = poll_many(&[
+		(s0.raw_fd(), EventMask::new_r()), (s0.raw_fd(), EventMask::new_w()),
+		(s1.raw_fd(), EventMask::new_r()), (s1.raw_fd(), EventMask::new_w())
+	], Duration::from_secs(4)).unwrap();
+	
+	// Select the same handles
+	let mut set = SelectSet::new();
+	set.push(&s0, EventMask::new_r());
+	set.push(&s0, EventMask::new_w());
+	set.push(&s1, EventMask::new_r());
+	set.push(&s1, EventMask::new_w());
+	let selected: Vec<EventMask> = set.select(Duration::from_secs(4)).unwrap()
+		.into_iter().map(|(_, event)| event).collect();
+	
+	let polled: Vec<EventMask> = polled.into_iter().filter(|e| e.rwe() != (false, false, false))
+		.collect();
+	assert_eq!(polled, selected);
+	assert_eq!(polled.len(), 3);
+}
+#[test]
+fn test_wait_for_event_multi() {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	
+	let events = stream.wait_for_event_multi(
+		&[EventMask::new_r(), EventMask::new_w()],
+		Duration::from_secs(4)
+	).unwrap();
+	assert_eq!(events[0].rwe(), (false, false, false));
+	assert_eq!(events[1].rwe(), (false, true, false));
+}
+#[test] #[cfg(target_os = "linux")]
+fn test_wait_precise() {
+	let (s0, _s1) = socket_pair();
+	
+	// Take the fastest of multiple runs to be robust against scheduling delays
+	let elapsed = (0..16).map(|_| {
+		let start = Instant::now();
+		let result = s0.wait_for_event_precise(EventMask::new_r(), Duration::from_micros(500));
+		assert_eq!(result.unwrap_err().kind(), TimeoutIoErrorKind::TimedOut);
+		start.elapsed()
+	}).min().unwrap();
+	assert!(elapsed >= Duration::from_micros(500), "Waited too short: {:?}", elapsed);
+	assert!(elapsed < Duration::from_millis(1), "Waited too long: {:?}", elapsed);
+}
+#[test]
+fn test_wait_precise_ready() {
+	let (s0, mut s1) = socket_pair();
+	s1.try_write_exact(b"Testolope", &mut 0, Duration::from_secs(1)).unwrap();
+	
+	let event = s0.wait_for_event_precise(EventMask::new_r(), Duration::from_secs(4)).unwrap();
+	assert!(event.rwe().0);
+}
+#[test]
+fn test_wait_timed() {
+	let (s0, s1) = socket_pair();
+	
+	// Early readiness leaves most of the budget
+	let writer = thread::spawn(move || {
+		thread::sleep(Duration::from_millis(200));
+		(&s1).write_all(b"Testolope").unwrap();
+		s1
+	});
+	let (event, remaining) = s0.wait_for_event_timed(EventMask::new_r(), Duration::from_secs(4))
+		.unwrap();
+	assert!(event.rwe().0);
+	assert!(remaining > Duration::from_secs(3) && remaining < Duration::from_millis(3900));
+	let _s1 = writer.join().unwrap();
+	
+	// Immediate readiness leaves (almost) the full budget
+	let (_, remaining) = s0.wait_for_event_timed(EventMask::new_w(), Duration::from_secs(4))
+		.unwrap();
+	assert!(remaining > Duration::from_millis(3900));
+	
+	// If the event does not occur, the wait times out as usual
+	let (s2, _s3) = socket_pair();
+	assert_eq!(
+		s2.wait_for_event_timed(EventMask::new_r(), Duration::from_millis(100)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+}
+#[test] #[cfg(unix)]
+fn test_set_blocking_mode_non_socket() {
+	// On unix, non-socket FDs can be made non-blocking too
+	let file = std::fs::File::open("/dev/null").unwrap();
+	file.set_blocking_mode(false).unwrap();
+	file.set_blocking_mode(true).unwrap();
+}
+#[test] #[cfg(windows)]
+fn test_set_blocking_mode_non_socket() {
+	use std::os::windows::io::{ AsRawHandle, AsRawSocket, RawSocket };
+	
+	/// Wraps a file handle as (invalid) socket
+	struct FileAsSocket(std::fs::File);
+	impl AsRawSocket for FileAsSocket {
+		fn as_raw_socket(&self) -> RawSocket {
+			self.0.as_raw_handle() as RawSocket
+		}
+	}
+	
+	// Make sure that winsock is initialized and try to make a file non-blocking
+	let _ = socket_pair();
+	let file = FileAsSocket(std::fs::File::open("Cargo.toml").unwrap());
+	assert_eq!(file.set_blocking_mode(false).unwrap_err(), TimeoutIoError::InvalidInput);
+}
+#[test]
+fn test_select_edge() {
+	let (s0, mut s1) = socket_pair();
+	s1.try_write_exact(b"Testolope", &mut 0, Duration::from_secs(1)).unwrap();
+	
+	// Level-triggered selects report the undrained socket every time
+	for _ in 0..2 {
+		let mut set = SelectSet::new();
+		set.push(&s0, EventMask::new_r());
+		assert_eq!(set.select(Duration::from_secs(1)).unwrap().len(), 1);
+	}
+	
+	// Edge-triggered selects report it only once
+	let mut set = SelectSet::new();
+	set.push(&s0, EventMask::new_r());
+	assert_eq!(set.select_edge(Duration::from_secs(1)).unwrap().len(), 1);
+	let start = Instant::now();
+	assert!(set.select_edge(Duration::from_millis(500)).unwrap().is_empty());
+	assert!(start.elapsed() >= Duration::from_millis(450));
+	
+	// Drain and re-arm the socket; then it fires again on new data
+	let (mut buf, mut reader) = ([0; 9], s0.try_clone().unwrap());
+	reader.try_read_exact(&mut buf, &mut 0, Duration::from_secs(1)).unwrap();
+	set.rearm(&s0);
+	assert!(set.select_edge(Duration::from_millis(100)).unwrap().is_empty());
+	
+	s1.try_write_exact(b"!", &mut 0, Duration::from_secs(1)).unwrap();
+	let ready = set.select_edge(Duration::from_secs(1)).unwrap();
+	assert_eq!(ready.len(), 1);
+	assert!(ready[0].1.rwe().0);
+}
+#[test]
+fn test_dispatch() {
+	let (s0, mut s1) = socket_pair();
+	let (s2, _s3) = socket_pair();
+	s1.try_write_exact(b"Testolope", &mut 0, Duration::from_secs(1)).unwrap();
+	
+	// Only the readable `s0` and the writable `s2` fire
+	let mut set = SelectSet::new();
+	set.push(&s0, EventMask::new_r());
+	set.push(&s2, EventMask::new_r());
+	set.push(&s2, EventMask::new_w());
+	for _ in 0..2 {
+		let mut ready = Vec::new();
+		let fired = set.dispatch(Duration::from_secs(1), |handle, event| {
+			ready.push((handle.raw_fd(), event.rwe()));
+		}).unwrap();
+		assert_eq!(fired, 2);
+		assert_eq!(ready, vec![
+			(s0.raw_fd(), (true, false, false)),
+			(s2.raw_fd(), (false, true, false))
+		]);
+	}
+	
+	// Nothing fires after draining `s0` if we only wait for reads
+	let (mut buf, mut reader) = ([0; 9], s0.try_clone().unwrap());
+	reader.try_read_exact(&mut buf, &mut 0, Duration::from_secs(1)).unwrap();
+	let mut set = SelectSet::new();
+	set.push(&s0, EventMask::new_r());
+	set.push(&s2, EventMask::new_r());
+	let fired = set.dispatch(Duration::from_millis(100), |_, _| panic!("Unexpected event"));
+	assert_eq!(fired.unwrap(), 0);
+}
+#[test]
+fn test_hangup() {
+	let (s0, mut s1) = socket_pair();
+	
+	// Pending data is not a hangup
+	s1.try_write_exact(b"Testolope", &mut 0, Duration::from_secs(1)).unwrap();
+	let event = s0.wait_for_event(EventMask::new_rh(), Duration::from_secs(4)).unwrap();
+	assert!(event.rwe().0);
+	assert!(!event.contains_hangup());
+	
+	// Drain the data and shut the peer down
+	let (mut buf, mut reader) = ([0; 9], s0.try_clone().unwrap());
+	reader.try_read_exact(&mut buf, &mut 0, Duration::from_secs(1)).unwrap();
+	s1.shutdown(std::net::Shutdown::Write).unwrap();
+	
+	let event = s0.wait_for_event(EventMask::new_rh(), Duration::from_secs(4)).unwrap();
+	assert!(event.contains_hangup());
+	assert!(!EventMask::new_r().contains_hangup());
+}
+
+#[test]
+fn test_select_with_timers() {
+	let (s0, mut s1) = socket_pair();
+	let (mut short, mut long) = (Timer::new().unwrap(), Timer::new().unwrap());
+	short.set(Duration::from_millis(300)).unwrap();
+	long.set(Duration::from_secs(7)).unwrap();
+	
+	// The short timer fires as a distinct event
+	let start = Instant::now();
+	let mut select_set = SelectSet::new();
+	select_set.push(&s0, EventMask::new_r());
+	select_set.push_timer(&short);
+	select_set.push_timer(&long);
+	let events = select_set.select_with_timers(Duration::from_secs(4)).unwrap();
+	assert!(events.handles.is_empty());
+	assert_eq!(events.timers.len(), 1);
+	assert!(std::ptr::eq(events.timers[0], &short));
+	assert!(start.elapsed() >= Duration::from_millis(300));
+	assert!(start.elapsed() < Duration::from_millis(1000));
+	
+	// IO-readiness is reported separately (after cancelling the expired timer)
+	short.cancel().unwrap();
+	s1.write_all(b"Testolope").unwrap();
+	let mut select_set = SelectSet::new();
+	select_set.push(&s0, EventMask::new_r());
+	select_set.push_timer(&short);
+	select_set.push_timer(&long);
+	let events = select_set.select_with_timers(Duration::from_secs(4)).unwrap();
+	assert_eq!(events.handles.len(), 1);
+	assert!(events.timers.is_empty());
+}
+#[test]
+fn test_select_zero_timeout_snapshot() {
+	let pairs: Vec<(TcpStream, TcpStream)> = (0..4).map(|_| socket_pair()).collect();
+	
+	// Nothing is readable yet – the snapshot is empty and returned immediately
+	let mut set = SelectSet::new();
+	pairs.iter().for_each(|(s0, _)| set.push(s0, EventMask::new_r()));
+	let start = Instant::now();
+	assert!(set.select(Duration::ZERO).unwrap().is_empty());
+	assert!(start.elapsed() < Duration::from_millis(500));
+	
+	// Make the first and the third stream readable
+	for index in [0, 2].iter() {
+		(&pairs[*index].1).write_all(b"Testolope").unwrap();
+	}
+	thread::sleep(Duration::from_millis(500));
+	
+	// The snapshot contains exactly the readable streams (in push-order)
+	let mut set = SelectSet::new();
+	pairs.iter().for_each(|(s0, _)| set.push(s0, EventMask::new_r()));
+	let start = Instant::now();
+	let ready: Vec<u64> = set.select(Duration::ZERO).unwrap().into_iter()
+		.map(|(handle, event)| {
+			assert_eq!(event.rwe(), (true, false, false));
+			handle.raw_fd()
+		})
+		.collect();
+	assert!(start.elapsed() < Duration::from_millis(500));
+	assert_eq!(ready, vec![pairs[0].0.raw_fd(), pairs[2].0.raw_fd()]);
+}
+#[test]
+fn test_select_huge_timeout() {
+	let (s0, s1) = socket_pair();
+	let writer = thread::spawn(move || {
+		thread::sleep(Duration::from_millis(200));
+		(&s1).write_all(b"Testolope").unwrap();
+		s1
+	});
+	
+	// A timeout that overflows the raw timeout is clamped
+	let mut set = SelectSet::new();
+	set.push(&s0, EventMask::new_r());
+	assert_eq!(set.select(Duration::MAX).unwrap().len(), 1);
+	let _s1 = writer.join().unwrap();
+}
+#[test]
+fn test_wait_until_closed() {
+	let (s0, mut s1) = socket_pair();
+	
+	// An idle connection times out
+	assert_eq!(
+		s0.wait_until_closed(Duration::from_millis(200)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+	
+	// Pending data is not a hangup (and is not consumed)
+	s1.write_all(b"Testolope").unwrap();
+	assert!(!s0.wait_until_closed(Duration::from_secs(4)).unwrap());
+	let (mut buf, mut pos) = ([0; 9], 0);
+	s0.try_clone().unwrap().try_read_exact(&mut buf, &mut pos, Duration::from_secs(4)).unwrap();
+	assert_eq!(&buf, b"Testolope");
+	
+	// The peer closes the connection
+	thread::spawn(move || {
+		thread::sleep(Duration::from_millis(200));
+		drop(s1);
+	});
+	assert!(s0.wait_until_closed(Duration::from_secs(4)).unwrap());
+}
@@ -0,0 +1,45 @@
+use timeout_io::*;
+use std::{
+	thread,
+	time::{ Duration, Instant }
+};
+
+
+#[test]
+fn test_remaining_checked_future() {
+	let deadline = Instant::now() + Duration::from_secs(4);
+	assert!(deadline.remaining_checked().unwrap() > Duration::from_secs(3));
+	assert!(deadline.remaining() > Duration::from_secs(3));
+}
+#[test]
+fn test_remaining_checked_expired() {
+	// Wait until the deadline has just passed
+	let deadline = Instant::now() + Duration::from_millis(100);
+	while Instant::now() <= deadline { thread::yield_now() }
+	
+	assert_eq!(deadline.remaining_checked(), None);
+	assert_eq!(deadline.remaining(), Duration::from_secs(0));
+}
+#[test]
+fn test_remaining_checked_past() {
+	let deadline = Instant::now();
+	thread::sleep(Duration::from_millis(10));
+	
+	assert_eq!(deadline.remaining_checked(), None);
+	assert_eq!(deadline.remaining(), Duration::from_secs(0));
+}
+#[test]
+fn test_duration_helpers() {
+	assert_eq!(secs(4), Duration::from_secs(4));
+	assert_eq!(ms(250), Duration::from_millis(250));
+	
+	// The helpers can be used wherever a `Duration` is expected
+	let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+	let start = Instant::now();
+	assert_eq!(listener.try_accept(ms(250)).unwrap_err().kind(), TimeoutIoErrorKind::TimedOut);
+	assert_eq!(
+		listener.try_accept(Duration::from_millis(250)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+	assert!(start.elapsed() < secs(2));
+}
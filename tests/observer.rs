@@ -0,0 +1,84 @@
+use timeout_io::*;
+use std::{
+	thread, io::Write,
+	sync::{ Arc, Mutex },
+	time::Duration,
+	net::{ TcpListener, TcpStream }
+};
+
+
+fn socket_pair() -> (TcpStream, TcpStream) {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let s0 = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	let s1 = listener.accept().unwrap().0;
+	(s0.into_nonblocking().unwrap(), s1.into_nonblocking().unwrap())
+}
+
+
+/// A recorded wait
+type Wait = (Operation, u64, Duration, Result<(), TimeoutIoError>);
+
+
+/// An observer that records all waits
+#[derive(Default)]
+struct Recorder {
+	waits: Mutex<Vec<Wait>>
+}
+impl IoObserver for Recorder {
+	fn on_wait(&self, op: Operation, fd: u64, waited: Duration,
+		result: &Result<(), TimeoutIoError>)
+	{
+		self.waits.lock().unwrap().push((op, fd, waited, result.clone()));
+	}
+}
+
+
+#[test]
+fn test_observer() {
+	let recorder = Arc::new(Recorder::default());
+	set_observer(recorder.clone());
+	
+	// Perform a delayed read and a read that times out
+	let (mut s0, s1) = socket_pair();
+	let mut writer = s1.try_clone().unwrap().into_blocking().unwrap();
+	thread::spawn(move || {
+		thread::sleep(Duration::from_millis(500));
+		writer.write_all(b"Testolope").unwrap();
+	});
+	let mut buf = [0; 9];
+	s0.try_read_exact(&mut buf, &mut 0, Duration::from_secs(4)).unwrap();
+	assert_eq!(s0.try_read(&mut buf, &mut 0, Duration::from_millis(100)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut);
+	clear_observer();
+	
+	// Validate the recorded waits for `s0`
+	let waits: Vec<_> = recorder.waits.lock().unwrap().drain(..)
+		.filter(|(_, fd, _, _)| *fd == s0.raw_fd())
+		.collect();
+	assert!(waits.len() >= 2);
+	assert!(waits.iter().all(|(op, _, _, _)| *op == Operation::Read));
+	
+	let (_, _, waited, result) = &waits[0];
+	assert!(result.is_ok());
+	assert!(*waited >= Duration::from_millis(400) && *waited < Duration::from_secs(4));
+	let (_, _, waited, result) = waits.last().unwrap();
+	assert_eq!(result.as_ref().map_err(|e| e.kind()), Err(TimeoutIoErrorKind::TimedOut));
+	assert!(*waited >= Duration::from_millis(90) && *waited < Duration::from_secs(1));
+	
+	// No waits are recorded after the observer was cleared
+	s0.try_read(&mut buf, &mut 0, Duration::from_millis(10)).unwrap_err();
+	assert!(recorder.waits.lock().unwrap().is_empty());
+	
+	// Precise and multi waits are reported, too
+	set_observer(recorder.clone());
+	s0.wait_for_event_precise(EventMask::new_w(), Duration::from_millis(10)).unwrap();
+	let events = [EventMask::new_r(), EventMask::new_w()];
+	s0.wait_for_event_multi(&events, Duration::from_millis(10)).unwrap();
+	clear_observer();
+	
+	let ops: Vec<_> = recorder.waits.lock().unwrap().iter()
+		.filter(|(_, fd, _, result)| *fd == s0.raw_fd() && result.is_ok())
+		.map(|(op, _, _, _)| *op)
+		.collect();
+	assert_eq!(ops, [Operation::Write, Operation::ReadWrite]);
+}
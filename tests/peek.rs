@@ -0,0 +1,64 @@
+use timeout_io::*;
+use std::{
+	thread, time::Duration,
+	net::{ TcpListener, TcpStream }
+};
+
+
+fn socket_pair() -> (TcpStream, TcpStream) {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let s0 = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	let s1 = listener.accept().unwrap().0;
+	(s0.into_nonblocking().unwrap(), s1.into_nonblocking().unwrap())
+}
+
+
+#[test]
+fn test_peek_then_read() {
+	let (mut s0, mut s1) = socket_pair();
+	
+	// Send the prefix in two parts
+	let frame = [0, 0, 0, 5, b'H', b'e', b'l', b'l', b'o'];
+	s0.try_write_exact(&frame[..2], &mut 0, Duration::from_secs(1)).unwrap();
+	let writer = thread::spawn(move || {
+		thread::sleep(Duration::from_millis(100));
+		s0.try_write_exact(&frame[2..], &mut 0, Duration::from_secs(1)).unwrap();
+		s0
+	});
+	
+	// Peek the length (twice, to ensure nothing is consumed) and read the frame
+	assert_eq!(s1.try_peek_u32_be(Duration::from_secs(4)).unwrap(), 5);
+	assert_eq!(s1.try_peek_u32_be(Duration::from_secs(4)).unwrap(), 5);
+	
+	let mut buf = vec![0; 4 + 5];
+	s1.try_read_exact(&mut buf, &mut 0, Duration::from_secs(4)).unwrap();
+	assert_eq!(buf, frame);
+	let _s0 = writer.join().unwrap();
+}
+#[test]
+fn test_peek_uint() {
+	let (mut s0, s1) = socket_pair();
+	s0.try_write_exact(&[0x01, 0x02, 0x03], &mut 0, Duration::from_secs(1)).unwrap();
+	
+	assert_eq!(s1.try_peek_uint(2, Endianness::Big, Duration::from_secs(4)).unwrap(), 0x0102);
+	assert_eq!(s1.try_peek_uint(3, Endianness::Little, Duration::from_secs(4)).unwrap(), 0x030201);
+	assert_eq!(
+		s1.try_peek_uint(9, Endianness::Big, Duration::from_secs(4)).unwrap_err(),
+		TimeoutIoError::InvalidInput
+	);
+}
+#[test]
+fn test_peek_timeout() {
+	let (mut s0, s1) = socket_pair();
+	
+	// Only two bytes are available
+	s0.try_write_exact(&[0, 0], &mut 0, Duration::from_secs(1)).unwrap();
+	assert_eq!(
+		s1.try_peek_u32_be(Duration::from_millis(200)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+	assert_eq!(
+		s1.try_peek_u32_be(Duration::from_secs(0)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+}
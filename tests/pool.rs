@@ -0,0 +1,69 @@
+use timeout_io::*;
+use std::{
+	thread, time::Duration, sync::mpsc::{ self, Receiver },
+	net::{ TcpListener, TcpStream, SocketAddr }
+};
+
+
+fn listener() -> (SocketAddr, Receiver<TcpStream>) {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let address = listener.local_addr().unwrap();
+	
+	// Accept in background
+	let (sender, receiver) = mpsc::channel();
+	thread::spawn(move || for stream in listener.incoming() {
+		if sender.send(stream.unwrap()).is_err() { return }
+	});
+	(address, receiver)
+}
+
+
+#[test]
+fn test_pool_reuse() {
+	let (address, accepted) = listener();
+	let pool = ConnectionPool::new(Duration::from_secs(60));
+	
+	// Check out and return a connection
+	let local = pool.get(&address, Duration::from_secs(4)).unwrap().local_addr().unwrap();
+	let _peer = accepted.recv().unwrap();
+	assert_eq!(pool.idle_count(), 1);
+	
+	// The connection is reused
+	let connection = pool.get(&address, Duration::from_secs(4)).unwrap();
+	assert_eq!(connection.local_addr().unwrap(), local);
+	assert_eq!(pool.idle_count(), 0);
+}
+#[test]
+fn test_pool_errored() {
+	let (address, _accepted) = listener();
+	let pool = ConnectionPool::new(Duration::from_secs(60));
+	
+	pool.get(&address, Duration::from_secs(4)).unwrap().mark_errored();
+	assert_eq!(pool.idle_count(), 0);
+}
+#[test]
+fn test_pool_idle_eviction() {
+	let (address, _accepted) = listener();
+	let pool = ConnectionPool::new(Duration::from_secs(1));
+	
+	drop(pool.get(&address, Duration::from_secs(4)).unwrap());
+	assert_eq!(pool.idle_count(), 1);
+	
+	thread::sleep(Duration::from_secs(2));
+	pool.evict_idle();
+	assert_eq!(pool.idle_count(), 0);
+}
+#[test]
+fn test_pool_peer_closed() {
+	let (address, accepted) = listener();
+	let pool = ConnectionPool::new(Duration::from_secs(60));
+	
+	// Return a connection and close it on the peer's side
+	let local = pool.get(&address, Duration::from_secs(4)).unwrap().local_addr().unwrap();
+	drop(accepted.recv().unwrap());
+	thread::sleep(Duration::from_secs(1));
+	
+	// The closed connection is discarded
+	let connection = pool.get(&address, Duration::from_secs(4)).unwrap();
+	assert_ne!(connection.local_addr().unwrap(), local);
+}
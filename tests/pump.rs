@@ -0,0 +1,112 @@
+use timeout_io::*;
+use std::{
+	thread, time::Duration, io::Write, sync::mpsc,
+	net::{ TcpListener, TcpStream }
+};
+
+
+fn write_delayed(mut stream: impl 'static + Write + Send + RawFd, data: &'static [u8],
+	delay: Duration)
+{
+	thread::spawn(move || {
+		// We need this for `write_all`
+		stream.set_blocking_mode(true).unwrap();
+		
+		// Write the data
+		thread::sleep(delay);
+		stream.write_all(data).unwrap();
+	});
+}
+
+fn socket_pair() -> (TcpStream, TcpStream) {
+	// Create listener
+	let (listener, address) = {
+		// Create listener (to capture the address) and channels
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let address = listener.local_addr().unwrap();
+		let (sender, receiver) = mpsc::channel();
+		
+		// Listen in background
+		thread::spawn(move || sender.send(listener.accept().unwrap().0).unwrap());
+		(receiver, address)
+	};
+	
+	// Create and connect stream
+	let (s0, s1) = (TcpStream::connect(address).unwrap(), listener.recv().unwrap());
+	s0.set_blocking_mode(false).unwrap();
+	s1.set_blocking_mode(false).unwrap();
+	
+	(s0, s1)
+}
+
+fn timeouts(read_idle: u64, write: u64, total: u64) -> PumpTimeouts {
+	PumpTimeouts{
+		read_idle: Duration::from_secs(read_idle),
+		write: Duration::from_secs(write),
+		total: Duration::from_secs(total)
+	}
+}
+
+
+#[test]
+fn test_pump_ok() {
+	let ((mut src, src_peer), (mut dst, mut dst_peer)) = (socket_pair(), socket_pair());
+	write_delayed(src_peer, b"Testolope", Duration::from_secs(1));
+	
+	let mut moved = 0;
+	assert_eq!(pump(&mut src, &mut dst, &mut moved, timeouts(4, 4, 7)).unwrap(), None);
+	assert_eq!(moved, 9);
+	
+	let (mut buf, mut pos) = ([0u8; 9], 0);
+	dst_peer.try_read_exact(&mut buf, &mut pos, Duration::from_secs(4)).unwrap();
+	assert_eq!(&buf, b"Testolope");
+}
+#[test]
+fn test_pump_read_idle() {
+	let ((mut src, _src_peer), (mut dst, _dst_peer)) = (socket_pair(), socket_pair());
+	
+	let mut moved = 0;
+	assert_eq!(
+		pump(&mut src, &mut dst, &mut moved, timeouts(1, 4, 7)).unwrap(),
+		Some(PumpTimeout::ReadIdle)
+	);
+	assert_eq!(moved, 0);
+}
+#[test]
+fn test_pump_write() {
+	let ((mut src, src_peer), (mut dst, _dst_peer)) = (socket_pair(), socket_pair());
+	
+	// Send more data than the destination can buffer
+	let data = vec![0u8; 64 * 1024 * 1024];
+	let mut moved = 0;
+	thread::scope(|scope| {
+		let (mut src_peer, data) = (src_peer.into_blocking().unwrap(), &data);
+		scope.spawn(move || src_peer.write_all(data));
+		
+		assert_eq!(
+			pump(&mut src, &mut dst, &mut moved, timeouts(4, 1, 60)).unwrap(),
+			Some(PumpTimeout::Write)
+		);
+		
+		// Close the source with unread data (RST) so that the writer fails and the scope can end
+		drop(src);
+	});
+	assert!(moved > 0 && moved < data.len());
+}
+#[test]
+fn test_pump_total() {
+	let ((mut src, src_peer), (mut dst, _dst_peer)) = (socket_pair(), socket_pair());
+	
+	// Trickle data so that the idle timeout never fires
+	thread::spawn(move || {
+		let mut src_peer = src_peer.into_blocking().unwrap();
+		while src_peer.write_all(b"!").is_ok() { thread::sleep(Duration::from_millis(500)) }
+	});
+	
+	let mut moved = 0;
+	assert_eq!(
+		pump(&mut src, &mut dst, &mut moved, timeouts(2, 2, 3)).unwrap(),
+		Some(PumpTimeout::Total)
+	);
+	assert!(moved > 0);
+}
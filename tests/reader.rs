@@ -1,10 +1,89 @@
 use timeout_io::*;
 use std::{
-	time::Duration, thread, io::Write, sync::mpsc,
+	time::{ Duration, Instant }, thread, sync::mpsc,
+	io::{ self, Read, Write, IoSliceMut },
 	net::{ TcpListener, TcpStream }
 };
 
 
+/// A stream wrapper whose `read` fails with `Interrupted` every other call
+#[cfg(unix)]
+struct InterruptingStream {
+	stream: TcpStream,
+	interrupt: bool
+}
+#[cfg(unix)]
+impl Read for InterruptingStream {
+	fn read(&mut self, buf: &mut[u8]) -> io::Result<usize> {
+		self.interrupt = !self.interrupt;
+		match self.interrupt {
+			true => Err(io::ErrorKind::Interrupted.into()),
+			false => self.stream.read(buf)
+		}
+	}
+}
+#[cfg(unix)]
+impl std::os::unix::io::AsRawFd for InterruptingStream {
+	fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+		self.stream.as_raw_fd()
+	}
+}
+
+
+/// A stream wrapper that reads from `stream` but exposes the FD of `decoy` (so that waiting on it
+/// always times out)
+#[cfg(unix)]
+struct DecoyStream {
+	stream: TcpStream,
+	decoy: TcpStream
+}
+#[cfg(unix)]
+impl Read for DecoyStream {
+	fn read(&mut self, buf: &mut[u8]) -> io::Result<usize> {
+		self.stream.read(buf)
+	}
+}
+#[cfg(unix)]
+impl std::os::unix::io::AsRawFd for DecoyStream {
+	fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+		self.decoy.as_raw_fd()
+	}
+}
+
+
+/// A stream wrapper that records the address ranges of all buffers passed to `read`
+#[cfg(unix)]
+struct RecordingStream {
+	stream: TcpStream,
+	buffers: Vec<(usize, usize)>
+}
+#[cfg(unix)]
+impl Read for RecordingStream {
+	fn read(&mut self, buf: &mut[u8]) -> io::Result<usize> {
+		let read = self.stream.read(buf)?;
+		self.buffers.push((buf.as_ptr() as usize, read));
+		Ok(read)
+	}
+}
+#[cfg(unix)]
+impl std::os::unix::io::AsRawFd for RecordingStream {
+	fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+		self.stream.as_raw_fd()
+	}
+}
+
+
+/// A temporary file that is removed on drop (even if a test fails)
+#[cfg(unix)]
+struct TempFile(std::path::PathBuf);
+#[cfg(unix)]
+impl Drop for TempFile {
+	fn drop(&mut self) {
+		let _ = std::fs::remove_file(&self.0);
+	}
+}
+
+
 fn write_delayed(mut stream: impl 'static + Write + Send + RawFd, data: &'static [u8],
 	delay: Duration)
 {
@@ -58,7 +137,23 @@ fn test_read_oneshot_err() {
 	let (mut buf, mut pos) = ([0u8; 4096], 0);
 	assert_eq!(
 		s0.try_read(&mut buf, &mut pos, Duration::from_secs(4)).unwrap_err(),
-		TimeoutIoError::UnexpectedEof
+		TimeoutIoError::GracefulEof
+	)
+}
+#[test]
+fn test_read_oneshot_err_reset() {
+	let (mut s0, s1) = socket_pair();
+	
+	// Closing a socket with unread data triggers a reset
+	s0.try_write_exact(b"Testolope", &mut 0, Duration::from_secs(1)).unwrap();
+	thread::sleep(Duration::from_secs(1));
+	drop(s1);
+	thread::sleep(Duration::from_secs(1));
+	
+	let (mut buf, mut pos) = ([0u8; 4096], 0);
+	assert_eq!(
+		s0.try_read(&mut buf, &mut pos, Duration::from_secs(4)).unwrap_err().kind(),
+		TimeoutIoErrorKind::ConnectionLost
 	)
 }
 #[test]
@@ -66,10 +161,82 @@ fn test_read_oneshot_timeout() {
 	let (mut s0, _s1) = socket_pair();
 	let (mut buf, mut pos) = ([0u8; 4096], 0);
 	assert_eq!(
-		s0.try_read(&mut buf, &mut pos, Duration::from_secs(4)).unwrap_err(),
-		TimeoutIoError::TimedOut
+		s0.try_read(&mut buf, &mut pos, Duration::from_secs(4)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
 	)
 }
+#[test]
+fn test_read_oneshot_with_mask() {
+	let (mut s0, s1) = socket_pair();
+	write_delayed(
+		s1.try_clone().unwrap(), b"Testolope",
+		Duration::from_secs(1)
+	);
+	
+	// Read without waiting for error-events
+	let (mut buf, mut pos) = ([0u8; 4096], 0);
+	let mask = EventMask::from_rwe(true, false, false);
+	s0.try_read_with_mask(&mut buf, &mut pos, mask, Duration::from_secs(4)).unwrap();
+	assert_eq!(&buf[..pos], b"Testolope");
+	
+	// Time out if there is no data
+	assert_eq!(
+		s0.try_read_with_mask(&mut buf, &mut pos, mask, Duration::from_secs(1)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+}
+#[test]
+fn test_read_oneshot_zero_timeout() {
+	let (mut s0, mut s1) = socket_pair();
+	let (mut buf, mut pos) = ([0u8; 4096], 0);
+	assert_eq!(
+		s0.try_read(&mut buf, &mut pos, Duration::from_secs(0)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+	
+	s1.try_write_exact(b"Testolope", &mut 0, Duration::from_secs(1)).unwrap();
+	thread::sleep(Duration::from_secs(1));
+	s0.try_read(&mut buf, &mut pos, Duration::from_secs(0)).unwrap();
+	assert_eq!(&buf[..pos], b"Testolope");
+}
+#[test]
+fn test_read_append_ok() {
+	let (mut s0, s1) = socket_pair();
+	write_delayed(
+		s1.try_clone().unwrap(), b"Test",
+		Duration::from_secs(1)
+	);
+	write_delayed(
+		s1.try_clone().unwrap(), b"olope",
+		Duration::from_secs(3)
+	);
+	
+	// Accumulate two reads and hit the limit
+	let mut buf = Vec::new();
+	assert_eq!(s0.try_read_append(&mut buf, 8, Duration::from_secs(7)).unwrap(), 4);
+	assert_eq!(s0.try_read_append(&mut buf, 8, Duration::from_secs(7)).unwrap(), 4);
+	assert_eq!(buf, b"Testolop");
+	assert_eq!(
+		s0.try_read_append(&mut buf, 8, Duration::from_secs(7)).unwrap_err(),
+		TimeoutIoError::LimitExceeded
+	);
+	
+	// Continue with a higher limit
+	assert_eq!(s0.try_read_append(&mut buf, 4096, Duration::from_secs(7)).unwrap(), 1);
+	assert_eq!(buf, b"Testolope");
+}
+#[test]
+fn test_read_append_bounded() {
+	let (mut s0, s1) = socket_pair();
+	let mut s1 = s1.into_blocking().unwrap();
+	s1.write_all(&[7; 64 * 1024]).unwrap();
+	
+	// A huge spare capacity is not used (and zeroed) at once
+	let mut buf = Vec::with_capacity(16 * 1024 * 1024);
+	let read = s0.try_read_append(&mut buf, usize::MAX, Duration::from_secs(4)).unwrap();
+	assert!(read > 0 && read <= 4096, "{}", read);
+	assert_eq!(buf, vec![7; read]);
+}
 
 
 #[test]
@@ -88,6 +255,44 @@ fn test_read_exact_ok() {
 	s0.try_read_exact(&mut buf, &mut pos, Duration::from_secs(7)).unwrap();
 	assert_eq!(&buf, b"Testolope");
 }
+#[test] #[cfg(unix)]
+fn test_read_exact_interrupted() {
+	let (s0, s1) = socket_pair();
+	write_delayed(
+		s1.try_clone().unwrap(), b"Test",
+		Duration::from_secs(1)
+	);
+	write_delayed(
+		s1.try_clone().unwrap(), b"olope",
+		Duration::from_secs(3)
+	);
+	
+	let mut s0 = InterruptingStream{ stream: s0, interrupt: false };
+	let (mut buf, mut pos) = ([0u8; 9], 0);
+	s0.try_read_exact(&mut buf, &mut pos, Duration::from_secs(7)).unwrap();
+	assert_eq!(&buf, b"Testolope");
+}
+#[test] #[cfg(unix)]
+fn test_read_exact_fast_path() {
+	let (s0, mut s1) = socket_pair();
+	let (decoy, _decoy_peer) = socket_pair();
+	
+	// Make sure that the data is already available
+	s1.try_write_exact(b"Testolope", &mut 0, Duration::from_secs(1)).unwrap();
+	thread::sleep(Duration::from_millis(100));
+	
+	// The data is read without waiting (waiting on the decoy would time out)
+	let mut stream = DecoyStream{ stream: s0, decoy };
+	let (mut buf, mut pos) = ([0u8; 9], 0);
+	stream.try_read_exact(&mut buf, &mut pos, Duration::from_millis(500)).unwrap();
+	assert_eq!(&buf, b"Testolope");
+	
+	// If no data is available, we wait
+	assert_eq!(
+		stream.try_read_exact(&mut buf, &mut 0, Duration::from_millis(500)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+}
 #[test]
 fn test_read_exact_err() {
 	let (mut s0, s1) = socket_pair();
@@ -100,6 +305,142 @@ fn test_read_exact_err() {
 	)
 }
 #[test]
+fn test_read_exact_into_ok() {
+	let (mut s0, s1) = socket_pair();
+	let mut s1 = s1.into_blocking().unwrap();
+	
+	// Write the data in chunks that don't match the target boundaries
+	thread::spawn(move || {
+		for chunk in [&b"\x00\x00"[..], b"\x00\x05Testolo", b"pe0123456", b"789ABCDEFHello"].iter() {
+			s1.write_all(chunk).unwrap();
+			thread::sleep(Duration::from_millis(200));
+		}
+	});
+	
+	// Read the length, the ID and the payload
+	let (mut len, mut id, mut payload) = ([0u8; 4], [0u8; 16], [0u8; 5]);
+	let mut pos = 0;
+	s0.try_read_exact_into(&mut [&mut len, &mut id, &mut payload], &mut pos, Duration::from_secs(4))
+		.unwrap();
+	assert_eq!(pos, 25);
+	assert_eq!(u32::from_be_bytes(len), 5);
+	assert_eq!(&id, b"Testolope0123456");
+	assert_eq!(&payload, b"789AB");
+}
+#[test]
+fn test_read_exact_into_continue() {
+	let (mut s0, s1) = socket_pair();
+	write_delayed(s1.try_clone().unwrap(), b"Testolope", Duration::from_secs(0));
+	
+	// Time out within the second target
+	let (mut a, mut b) = ([0u8; 4], [0u8; 9]);
+	let mut pos = 0;
+	assert_eq!(
+		s0.try_read_exact_into(&mut [&mut a, &mut b], &mut pos, Duration::from_secs(1))
+			.unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+	assert_eq!(pos, 9);
+	
+	// Continue seamlessly
+	write_delayed(s1, b"!!!!", Duration::from_secs(0));
+	s0.try_read_exact_into(&mut [&mut a, &mut b], &mut pos, Duration::from_secs(4)).unwrap();
+	assert_eq!(pos, 13);
+	assert_eq!((&a, &b), (b"Test", b"olope!!!!"));
+}
+#[test]
+fn test_read_vectored_exact_ok() {
+	let (mut s0, s1) = socket_pair();
+	let mut s1 = s1.into_blocking().unwrap();
+	
+	// The first chunk fills the first slice only partially
+	thread::spawn(move || {
+		for chunk in [&b"Te"[..], b"stolo", b"pe"].iter() {
+			s1.write_all(chunk).unwrap();
+			thread::sleep(Duration::from_millis(200));
+		}
+	});
+	
+	let (mut a, mut b) = ([0u8; 4], [0u8; 5]);
+	let mut pos = 0;
+	let mut bufs = [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)];
+	s0.try_read_vectored_exact(&mut bufs, &mut pos, Duration::from_secs(4)).unwrap();
+	assert!(bufs.iter().all(|buf| buf.is_empty()));
+	assert_eq!(pos, 9);
+	assert_eq!((&a, &b), (b"Test", b"olope"));
+}
+#[test]
+fn test_read_vectored_exact_continue() {
+	let (mut s0, s1) = socket_pair();
+	write_delayed(s1.try_clone().unwrap(), b"Testolo", Duration::from_secs(0));
+	
+	// Time out within the second slice
+	let (mut a, mut b) = ([0u8; 4], [0u8; 9]);
+	let mut pos = 0;
+	{
+		let mut bufs = [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)];
+		assert_eq!(
+			s0.try_read_vectored_exact(&mut bufs, &mut pos, Duration::from_secs(1))
+				.unwrap_err().kind(),
+			TimeoutIoErrorKind::TimedOut
+		);
+		assert_eq!(pos, 7);
+		assert_eq!((bufs[0].len(), bufs[1].len()), (0, 6));
+		
+		// Continue seamlessly with the advanced slices
+		write_delayed(s1, b"pe!!!!", Duration::from_secs(0));
+		s0.try_read_vectored_exact(&mut bufs, &mut pos, Duration::from_secs(4)).unwrap();
+		assert_eq!(pos, 13);
+	}
+	assert_eq!((&a, &b), (b"Test", b"olope!!!!"));
+}
+#[test]
+fn test_read_exact_min_rate_ok() {
+	let (mut s0, s1) = socket_pair();
+	write_delayed(s1, b"Testolope", Duration::from_millis(500));
+	
+	let (mut buf, mut pos) = ([0u8; 9], 0);
+	s0.try_read_exact_min_rate(&mut buf, &mut pos, 1, Duration::from_secs(4)).unwrap();
+	assert_eq!(&buf, b"Testolope");
+}
+#[test]
+fn test_read_exact_min_rate_too_slow() {
+	let (mut s0, s1) = socket_pair();
+	let mut s1 = s1.into_blocking().unwrap();
+	
+	// Drip-feed 100 bytes per second
+	thread::spawn(move || for _ in 0..100 {
+		if s1.write_all(&[0; 10]).is_err() { break }
+		thread::sleep(Duration::from_millis(100));
+	});
+	
+	// The deadline is not hit, but the rate floor is
+	let (start, mut buf, mut pos) = (Instant::now(), [0u8; 1000], 0);
+	assert_eq!(
+		s0.try_read_exact_min_rate(&mut buf, &mut pos, 1024, Duration::from_secs(8)).unwrap_err(),
+		TimeoutIoError::TooSlow
+	);
+	assert!(start.elapsed() < Duration::from_secs(2));
+	assert!(pos > 0);
+}
+#[test]
+fn test_read_string_ok() {
+	let (mut s0, s1) = socket_pair();
+	write_delayed(s1.try_clone().unwrap(), "Grüße!Next".as_bytes(), Duration::from_secs(1));
+	
+	assert_eq!(s0.try_read_string(8, Duration::from_secs(4)).unwrap(), "Grüße!");
+	assert_eq!(s0.try_read_string(4, Duration::from_secs(4)).unwrap(), "Next");
+}
+#[test]
+fn test_read_string_invalid() {
+	let (mut s0, s1) = socket_pair();
+	write_delayed(s1.try_clone().unwrap(), b"Te\xFFt Next", Duration::from_secs(1));
+	
+	// The invalid field is consumed completely
+	assert_eq!(s0.try_read_string(4, Duration::from_secs(4)).unwrap_err(), TimeoutIoError::InvalidInput);
+	assert_eq!(s0.try_read_string(5, Duration::from_secs(4)).unwrap(), " Next");
+}
+#[test]
 fn test_read_exact_timeout() {
 	let (mut s0, s1) = socket_pair();
 	write_delayed(
@@ -109,12 +450,74 @@ fn test_read_exact_timeout() {
 	
 	let (mut buf, mut pos) = ([0u8; 9], 0);
 	assert_eq!(
-		s0.try_read_exact(&mut buf, &mut pos, Duration::from_secs(7)).unwrap_err(),
-		TimeoutIoError::TimedOut
+		s0.try_read_exact(&mut buf, &mut pos, Duration::from_secs(7)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
 	)
 }
 
 
+#[test]
+fn test_read_exact_shared_extended() {
+	let (mut s0, s1) = socket_pair();
+	write_delayed(
+		s1.try_clone().unwrap(), b"Test",
+		Duration::from_secs(1)
+	);
+	write_delayed(
+		s1.try_clone().unwrap(), b"olope",
+		Duration::from_secs(4)
+	);
+	
+	// Extend the deadline from another thread
+	let deadline = SharedDeadline::new(Duration::from_secs(2));
+	let deadline_ = deadline.clone();
+	thread::spawn(move || {
+		thread::sleep(Duration::from_secs(1));
+		deadline_.set(Duration::from_secs(6));
+	});
+	
+	let (mut buf, mut pos) = ([0u8; 9], 0);
+	s0.try_read_exact_shared(&mut buf, &mut pos, &deadline).unwrap();
+	assert_eq!(&buf, b"Testolope");
+}
+#[test]
+fn test_read_exact_shared_expire() {
+	let (mut s0, _s1) = socket_pair();
+	
+	// Expire the deadline from another thread during a stalled read
+	let deadline = SharedDeadline::new(Duration::from_secs(30));
+	let deadline_ = deadline.clone();
+	thread::spawn(move || {
+		thread::sleep(Duration::from_millis(500));
+		deadline_.expire();
+	});
+	
+	let start = Instant::now();
+	let (mut buf, mut pos) = ([0u8; 9], 0);
+	assert_eq!(
+		s0.try_read_exact_shared(&mut buf, &mut pos, &deadline).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+	assert!(start.elapsed() < Duration::from_secs(2));
+}
+#[test]
+fn test_read_exact_shared_timeout() {
+	let (mut s0, s1) = socket_pair();
+	write_delayed(
+		s1.try_clone().unwrap(), b"Test",
+		Duration::from_secs(1)
+	);
+	
+	let deadline = SharedDeadline::new(Duration::from_secs(3));
+	let (mut buf, mut pos) = ([0u8; 9], 0);
+	assert_eq!(
+		s0.try_read_exact_shared(&mut buf, &mut pos, &deadline).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+	assert_eq!(&buf[..pos], b"Test");
+}
+
+
 #[test]
 fn test_read_until_ok() {
 	let (mut s0, s1) = socket_pair();
@@ -184,5 +587,411 @@ fn test_read_until_timeout() {
 	assert_eq!(s0.try_read_until(
 		&mut buf, &mut pos,
 		b"\n", Duration::from_secs(7)
-	).unwrap_err(), TimeoutIoError::TimedOut)
-}
\ No newline at end of file
+	).unwrap_err().kind(), TimeoutIoErrorKind::TimedOut)
+}
+
+
+#[test]
+fn test_read_empty_or_full() {
+	let (mut s0, _s1) = socket_pair();
+	let start = Instant::now();
+	
+	// Empty buffers are no-ops
+	let (mut buf, mut pos) = ([0u8; 0], 0);
+	s0.try_read(&mut buf, &mut pos, Duration::from_secs(4)).unwrap();
+	s0.try_read_exact(&mut buf, &mut pos, Duration::from_secs(4)).unwrap();
+	assert!(!s0.try_read_until(&mut buf, &mut pos, b"\n", Duration::from_secs(4)).unwrap());
+	assert_eq!(pos, 0);
+	
+	// Already full buffers (or out-of-bounds positions) are no-ops, too
+	let mut buf = *b"Testolope\n";
+	for &start_pos in [buf.len(), buf.len() + 1].iter() {
+		let mut pos = start_pos;
+		s0.try_read(&mut buf, &mut pos, Duration::from_secs(4)).unwrap();
+		s0.try_read_exact(&mut buf, &mut pos, Duration::from_secs(4)).unwrap();
+		assert!(!s0.try_read_until(&mut buf, &mut pos, b"\n", Duration::from_secs(4)).unwrap());
+		assert_eq!(pos, start_pos);
+	}
+	
+	// Nothing waited for data
+	assert!(start.elapsed() < Duration::from_secs(1));
+}
+#[test]
+fn test_read_until_unescaped() {
+	let (mut s0, mut s1) = socket_pair();
+	s1.write_all(b"a\\;b\\\\;c\\\\\\;d;rest").unwrap();
+	
+	// An escaped delimiter is a literal, an escaped escape is not an escape
+	let (mut buf, mut pos) = ([0u8; 4096], 0);
+	assert!(s0.try_read_until_unescaped(
+		&mut buf, &mut pos, b';', b'\\',
+		Duration::from_secs(4)
+	).unwrap());
+	assert_eq!(&buf[..pos], b"a\\;b\\\\;");
+	
+	// An escaped escape followed by an escaped delimiter
+	let (mut buf, mut pos) = ([0u8; 4096], 0);
+	assert!(s0.try_read_until_unescaped(
+		&mut buf, &mut pos, b';', b'\\',
+		Duration::from_secs(4)
+	).unwrap());
+	assert_eq!(&buf[..pos], b"c\\\\\\;d;");
+	
+	// The escape state survives a split read
+	let (mut buf, mut pos) = ([0u8; 4096], 0);
+	s1.write_all(b"\\").unwrap();
+	assert!(!s0.try_read_until_unescaped(
+		&mut buf[..5], &mut pos, b';', b'\\',
+		Duration::from_secs(4)
+	).unwrap());
+	s1.write_all(b";x;").unwrap();
+	assert!(s0.try_read_until_unescaped(
+		&mut buf, &mut pos, b';', b'\\',
+		Duration::from_secs(4)
+	).unwrap());
+	assert_eq!(&buf[..pos], b"rest\\;x;");
+	
+	// Identical delimiter and escape are rejected
+	assert_eq!(s0.try_read_until_unescaped(
+		&mut buf, &mut 0, b';', b';',
+		Duration::from_secs(4)
+	).unwrap_err(), TimeoutIoError::InvalidInput);
+}
+#[test]
+fn test_read_until_limit_ok() {
+	let (mut s0, s1) = socket_pair();
+	write_delayed(
+		s1.try_clone().unwrap(), b"Testolope\r",
+		Duration::from_secs(1)
+	);
+	write_delayed(
+		s1.try_clone().unwrap(), b"\n!",
+		Duration::from_secs(3)
+	);
+	
+	let mut buf = Vec::new();
+	let end = s0.try_read_until_limit(
+		&mut buf, b"\r\n", 4096,
+		Duration::from_secs(7)
+	).unwrap();
+	assert_eq!(end, Some(11));
+	assert_eq!(&buf[..11], b"Testolope\r\n");
+}
+#[test]
+fn test_read_until_limit_exceeded() {
+	let (mut s0, s1) = socket_pair();
+	write_delayed(
+		s1.try_clone().unwrap(), b"Testolope\r\n",
+		Duration::from_secs(1)
+	);
+	
+	let mut buf = Vec::new();
+	assert_eq!(s0.try_read_until_limit(
+		&mut buf, b"\r\n", 4,
+		Duration::from_secs(7)
+	).unwrap(), None);
+	assert_eq!(buf, b"Test");
+}
+#[test]
+fn test_read_until_limit_timeout() {
+	let (mut s0, s1) = socket_pair();
+	write_delayed(
+		s1.try_clone().unwrap(), b"Testolope\r",
+		Duration::from_secs(1)
+	);
+	
+	let mut buf = Vec::new();
+	assert_eq!(s0.try_read_until_limit(
+		&mut buf, b"\r\n", 4096,
+		Duration::from_secs(4)
+	).unwrap_err().kind(), TimeoutIoErrorKind::TimedOut);
+	assert_eq!(buf, b"Testolope\r");
+	
+	// Continue after the timeout – the pattern now straddles two calls
+	write_delayed(s1, b"\n", Duration::from_secs(1));
+	assert_eq!(s0.try_read_until_limit(
+		&mut buf, b"\r\n", 4096,
+		Duration::from_secs(4)
+	).unwrap(), Some(11));
+}
+#[test]
+fn test_read_until_limit_prefilled() {
+	let (mut s0, _s1) = socket_pair();
+	
+	// The leftover of a pipelined request already contains a complete match
+	let mut buf = b"Testolope\r\nTest".to_vec();
+	assert_eq!(s0.try_read_until_limit(
+		&mut buf, b"\r\n", 4096,
+		Duration::from_secs(4)
+	).unwrap(), Some(11));
+	assert_eq!(buf, b"Testolope\r\nTest");
+}
+#[test]
+fn test_read_until_limit_linear() {
+	let (mut s0, s1) = socket_pair();
+	let mut s1 = s1.into_blocking().unwrap();
+	thread::spawn(move || s1.write_all(&vec![0; 1024 * 1024]));
+	
+	// A long pattern that almost matches everywhere but never matches
+	let mut pat = vec![0u8; 63];
+	pat.push(1);
+	
+	let (start, mut buf) = (Instant::now(), Vec::new());
+	assert_eq!(
+		s0.try_read_until_limit(&mut buf, &pat, 1024 * 1024, Duration::from_secs(8)).unwrap(),
+		None
+	);
+	assert_eq!(buf.len(), 1024 * 1024);
+	assert!(start.elapsed() < Duration::from_secs(4), "Scanning took {:?}", start.elapsed());
+}
+#[test] #[cfg(unix)]
+fn test_read_exact_mmap() {
+	use std::fs::{ self, OpenOptions };
+	const LEN: usize = 4 * 1024 * 1024;
+	
+	// Create and map the target file
+	let path = TempFile(std::env::temp_dir()
+		.join(format!("timeout_io_mmap_{}.dat", std::process::id())));
+	let file = OpenOptions::new().read(true).write(true).create(true).truncate(true)
+		.open(&path.0).unwrap();
+	file.set_len(LEN as u64).unwrap();
+	let mut map = unsafe{ memmap2::MmapMut::map_mut(&file) }.unwrap();
+	let start = map.as_ptr() as usize;
+	
+	// Write the data in the background (larger than a single `read` can fill)
+	let (s0, s1) = socket_pair();
+	let data: Vec<u8> = (0..LEN).map(|i| (i % 251) as u8).collect();
+	let expected = data.clone();
+	let mut s1 = s1.into_blocking().unwrap();
+	thread::spawn(move || s1.write_all(&data).unwrap());
+	
+	// Read directly into the mapped region
+	let (mut stream, mut pos) = (RecordingStream{ stream: s0, buffers: Vec::new() }, 0);
+	stream.try_read_exact(&mut map, &mut pos, Duration::from_secs(8)).unwrap();
+	assert_eq!(pos, LEN);
+	
+	// Validate that every `read` wrote into the mapped region at the right offset (i.e. no copy)
+	assert!(stream.buffers.len() > 1);
+	let mut offset = start;
+	for (address, read) in stream.buffers.iter().copied().filter(|(_, read)| *read > 0) {
+		assert_eq!(address, offset);
+		offset += read;
+	}
+	assert_eq!(offset, start + LEN);
+	
+	// Unmap the region and validate the file contents
+	drop(map);
+	drop(file);
+	assert!(fs::read(&path.0).unwrap() == expected);
+}
+#[test] #[cfg(unix)]
+fn test_read_exact_dyn() {
+	use std::os::unix::net::UnixStream;
+	
+	// Create heterogeneous stream pairs
+	let (tcp0, tcp1) = socket_pair();
+	let (unix0, unix1) = UnixStream::pair().unwrap();
+	let (unix0, unix1) = (unix0.into_nonblocking().unwrap(), unix1.into_nonblocking().unwrap());
+	let mut readers: Vec<Box<dyn Reader>> = vec![Box::new(tcp0), Box::new(unix0)];
+	let mut writers: Vec<Box<dyn Writer>> = vec![Box::new(tcp1), Box::new(unix1)];
+	
+	// Write and read through the trait objects
+	for writer in writers.iter_mut() {
+		writer.try_write_exact(b"Testolope", &mut 0, Duration::from_secs(4)).unwrap();
+	}
+	for reader in readers.iter_mut() {
+		let (mut buf, mut pos) = ([0; 9], 0);
+		reader.try_read_exact(&mut buf, &mut pos, Duration::from_secs(4)).unwrap();
+		assert_eq!(&buf, b"Testolope");
+	}
+}
+#[test] #[cfg(feature = "bytes")]
+fn test_read_buf_bytes() {
+	// Read into a `BytesMut` with and without spare capacity
+	let buffers = [bytes::BytesMut::new(), bytes::BytesMut::from(&b"Prefix"[..])];
+	for mut dst in buffers.iter().cloned() {
+		let (mut s0, s1) = socket_pair();
+		write_delayed(s1, b"Testolope", Duration::from_millis(200));
+		
+		let prefix = dst.len();
+		assert_eq!(s0.try_read_buf_bytes(&mut dst, Duration::from_secs(4)).unwrap(), 9);
+		assert_eq!(dst.len(), prefix + 9);
+		assert_eq!(&dst[prefix..], b"Testolope");
+		
+		// The peer has closed the connection
+		assert_eq!(
+			s0.try_read_buf_bytes(&mut dst, Duration::from_secs(4)).unwrap_err(),
+			TimeoutIoError::GracefulEof
+		);
+		assert_eq!(dst.len(), prefix + 9);
+	}
+}
+#[test]
+fn test_drain_read() {
+	let (mut s0, s1) = socket_pair();
+	let mut s1 = s1.into_blocking().unwrap();
+	
+	// Nothing is pending
+	let mut scratch = [0; 4];
+	s0.drain_read(|_| panic!("Unexpected chunk"), &mut scratch).unwrap();
+	
+	// Send several chunks and wait until they are readable
+	for chunk in [&b"Test"[..], b"olope", b"!"].iter() { s1.write_all(chunk).unwrap() }
+	thread::sleep(Duration::from_millis(200));
+	s0.wait_for_event(EventMask::new_r(), Duration::from_secs(4)).unwrap();
+	
+	// Drain everything in one call
+	let (mut drained, mut calls) = (Vec::new(), 0);
+	s0.drain_read(|chunk| { drained.extend_from_slice(chunk); calls += 1 }, &mut scratch).unwrap();
+	assert_eq!(drained, b"Testolope!");
+	assert!(calls >= 3);
+	assert_eq!(
+		s0.wait_for_event(EventMask::new_r(), Duration::from_millis(100)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+	
+	// The connection is closed
+	s1.write_all(b"Te").unwrap();
+	drop(s1);
+	thread::sleep(Duration::from_millis(200));
+	let mut drained = Vec::new();
+	assert_eq!(
+		s0.drain_read(|chunk| drained.extend_from_slice(chunk), &mut scratch).unwrap_err(),
+		TimeoutIoError::GracefulEof
+	);
+	assert_eq!(drained, b"Te");
+}
+#[test]
+fn test_read_exact_partial() {
+	let (mut s0, s1) = socket_pair();
+	let mut s1 = s1.into_blocking().unwrap();
+	
+	// Write a complete and a truncated record and close the connection
+	thread::spawn(move || {
+		s1.write_all(b"Testolope").unwrap();
+		thread::sleep(Duration::from_millis(200));
+		s1.write_all(b"Tes").unwrap();
+	});
+	
+	// Read the complete record
+	let (mut buf, mut pos) = ([0; 9], 0);
+	assert_eq!(s0.try_read_exact_partial(&mut buf, &mut pos, Duration::from_secs(4)).unwrap(), 9);
+	assert_eq!(&buf, b"Testolope");
+	
+	// Read the truncated record
+	let (mut buf, mut pos) = ([0; 9], 0);
+	assert_eq!(s0.try_read_exact_partial(&mut buf, &mut pos, Duration::from_secs(4)).unwrap(), 3);
+	assert_eq!(&buf[..pos], b"Tes");
+	
+	// Nothing is left
+	assert_eq!(s0.try_read_exact_partial(&mut buf, &mut 0, Duration::from_secs(4)).unwrap(), 0);
+}
+#[test]
+fn test_read_at_least() {
+	let (mut s0, s1) = socket_pair();
+	let mut s1 = s1.into_blocking().unwrap();
+	
+	// Write the data in chunks
+	thread::spawn(move || {
+		for chunk in [&b"Te"[..], b"st", b"olope"].iter() {
+			s1.write_all(chunk).unwrap();
+			thread::sleep(Duration::from_millis(200));
+		}
+	});
+	
+	// Read at least 4 bytes (which requires two chunks) but not the third chunk
+	let (mut buf, mut pos) = ([0; 16], 0);
+	s0.try_read_at_least(&mut buf, &mut pos, 4, Duration::from_secs(4)).unwrap();
+	assert_eq!(&buf[..pos], b"Test");
+	
+	// Already satisfied
+	s0.try_read_at_least(&mut buf, &mut pos, 2, Duration::from_secs(0)).unwrap();
+	assert_eq!(pos, 4);
+	
+	// Read at least 1 more byte; the rest of the chunk is grabbed opportunistically
+	thread::sleep(Duration::from_millis(400));
+	s0.try_read_at_least(&mut buf, &mut pos, 5, Duration::from_secs(4)).unwrap();
+	assert_eq!(&buf[..pos], b"Testolope");
+	
+	// The connection is closed before `min` bytes were read
+	let mut small = [0; 2];
+	assert_eq!(
+		s0.try_read_at_least(&mut small, &mut 0, 4, Duration::from_secs(4)).unwrap_err(),
+		TimeoutIoError::UnexpectedEof
+	);
+}
+#[test]
+fn test_read_exact_huge_timeout() {
+	let (mut s0, s1) = socket_pair();
+	write_delayed(s1, b"Testolope", Duration::from_millis(200));
+	
+	// A timeout that overflows `Instant` is effectively infinite
+	let (mut buf, mut pos) = ([0; 9], 0);
+	s0.try_read_exact(&mut buf, &mut pos, Duration::MAX).unwrap();
+	assert_eq!(&buf, b"Testolope");
+	s0.wait_for_event_precise(EventMask::new_w(), Duration::MAX).unwrap();
+}
+#[test]
+fn test_read_exact_from_either() {
+	let (mut a0, a1) = socket_pair();
+	let (mut b0, b1) = socket_pair();
+	
+	// Deliver the halves at different times (`b` completely before `a`)
+	write_delayed(a1.try_clone().unwrap(), b"Test", Duration::from_millis(200));
+	write_delayed(b1.try_clone().unwrap(), b"0123", Duration::from_millis(100));
+	write_delayed(b1.try_clone().unwrap(), b"4567", Duration::from_millis(300));
+	write_delayed(a1.try_clone().unwrap(), b"olope", Duration::from_millis(500));
+	
+	let start = Instant::now();
+	let (mut a_buf, mut b_buf) = ([0; 9], [0; 8]);
+	read_exact_from_either(&mut a0, &mut a_buf, &mut b0, &mut b_buf, Duration::from_secs(4))
+		.unwrap();
+	assert_eq!(&a_buf, b"Testolope");
+	assert_eq!(&b_buf, b"01234567");
+	assert!(start.elapsed() >= Duration::from_millis(450));
+	
+	// A stream that never delivers results in a timeout
+	write_delayed(a1.try_clone().unwrap(), b"!", Duration::from_millis(100));
+	let (mut a_buf, mut b_buf) = ([0; 1], [0; 1]);
+	let start = Instant::now();
+	let result = read_exact_from_either(&mut a0, &mut a_buf, &mut b0, &mut b_buf,
+		Duration::from_millis(500));
+	assert_eq!(result.unwrap_err().kind(), TimeoutIoErrorKind::TimedOut);
+	assert!(start.elapsed() >= Duration::from_millis(450));
+	assert_eq!(&a_buf, b"!");
+}
+#[test]
+fn test_read_varint() {
+	let (mut s0, mut s1) = socket_pair();
+	
+	// Decode known varints (including a non-minimal encoding and `u64::MAX`)
+	let known: [(&[u8], u64); 6] = [
+		(b"\x00", 0), (b"\x01", 1), (b"\x7f", 127), (b"\xac\x02", 300), (b"\x80\x00", 0),
+		(b"\xff\xff\xff\xff\xff\xff\xff\xff\xff\x01", u64::MAX)
+	];
+	for (encoded, value) in known.iter() {
+		s1.write_all(encoded).unwrap();
+		assert_eq!(s0.try_read_varint(Duration::from_secs(4)).unwrap(), *value);
+	}
+	
+	// Reject an overlong sequence and an overflowing 10th byte
+	s1.write_all(&[0x80; 16]).unwrap();
+	let error = s0.try_read_varint(Duration::from_secs(4)).unwrap_err();
+	assert_eq!(error, TimeoutIoError::InvalidInput);
+	let mut rest = [0; 6];
+	s0.try_read_exact(&mut rest, &mut 0, Duration::from_secs(4)).unwrap();
+	
+	s1.write_all(b"\xff\xff\xff\xff\xff\xff\xff\xff\xff\x02").unwrap();
+	let error = s0.try_read_varint(Duration::from_secs(4)).unwrap_err();
+	assert_eq!(error, TimeoutIoError::InvalidInput);
+	
+	// A varint split across writes and a truncated varint
+	write_delayed(s1.try_clone().unwrap(), b"\x02", Duration::from_millis(200));
+	s1.write_all(b"\xac").unwrap();
+	assert_eq!(s0.try_read_varint(Duration::from_secs(4)).unwrap(), 300);
+	
+	s1.write_all(b"\x80").unwrap();
+	drop(s1);
+	let error = s0.try_read_varint(Duration::from_secs(4)).unwrap_err();
+	assert_eq!(error, TimeoutIoError::UnexpectedEof);
+}
@@ -0,0 +1,76 @@
+use timeout_io::*;
+use std::{
+	thread,
+	io::{ self, Read, Write },
+	time::Duration,
+	net::{ TcpListener, TcpStream },
+	sync::mpsc::{ self, Receiver }
+};
+
+
+fn socket_pair() -> (TcpStream, TcpStream) {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let s0 = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	let s1 = listener.accept().unwrap().0;
+	(s0.into_nonblocking().unwrap(), s1.into_nonblocking().unwrap())
+}
+
+
+/// A read source without a raw fd that receives chunks over a channel and consumes one readiness
+/// signal per chunk
+struct ChannelSource {
+	chunks: Receiver<Vec<u8>>,
+	signals: TcpStream,
+	pending: Vec<u8>
+}
+impl Read for ChannelSource {
+	fn read(&mut self, buf: &mut[u8]) -> io::Result<usize> {
+		// Consume the next signal and receive the according chunk (which is sent before the signal)
+		if self.pending.is_empty() {
+			match self.signals.read(&mut [0])? {
+				0 => return Ok(0),
+				_ => self.pending = self.chunks.recv().unwrap()
+			}
+		}
+		
+		// Copy the pending data
+		let len = buf.len().min(self.pending.len());
+		buf[..len].copy_from_slice(&self.pending[..len]);
+		self.pending.drain(..len);
+		Ok(len)
+	}
+}
+
+
+#[test]
+fn test_reader_on() {
+	// The readiness source is a socket that is signaled for each chunk
+	let (signal_writer, signal_reader) = socket_pair();
+	let (sender, chunks) = mpsc::channel();
+	let signals = signal_reader.try_clone().unwrap();
+	let source = ChannelSource{ chunks, signals, pending: Vec::new() };
+	let mut reader = ReaderOn::new(source, signal_reader);
+	
+	// Send the chunks delayed
+	thread::spawn(move || {
+		let mut signal_writer = signal_writer.into_blocking().unwrap();
+		for chunk in [&b"Test"[..], b"olo", b"pe"].iter() {
+			thread::sleep(Duration::from_millis(100));
+			sender.send(chunk.to_vec()).unwrap();
+			signal_writer.write_all(&[1]).unwrap();
+		}
+		thread::sleep(Duration::from_secs(1));
+	});
+	
+	// Read via the adapter
+	let (mut buf, mut pos) = ([0; 9], 0);
+	reader.try_read_exact(&mut buf, &mut pos, Duration::from_secs(4)).unwrap();
+	assert_eq!(&buf, b"Testolope");
+	
+	// Without a signal, the reader times out
+	let (mut buf, mut pos) = ([0; 1], 0);
+	assert_eq!(
+		reader.try_read(&mut buf, &mut pos, Duration::from_millis(100)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+}
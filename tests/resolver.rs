@@ -1,7 +1,7 @@
 use timeout_io::*;
 use std::{
-	time::{ Duration, SystemTime, UNIX_EPOCH },
-	net::{ SocketAddr, SocketAddrV4, SocketAddrV6, Ipv4Addr, Ipv6Addr }
+	time::{ Duration, Instant, SystemTime, UNIX_EPOCH },
+	net::{ SocketAddr, SocketAddrV4, SocketAddrV6, IpAddr, Ipv4Addr, Ipv6Addr }
 };
 
 #[test]
@@ -20,11 +20,48 @@ fn test_dns_resolve_timeout() {
 		SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis()
 	);
 	assert_eq!(
-		domain.try_dns_resolve(Duration::from_secs(4)).unwrap_err(),
-		TimeoutIoError::TimedOut
+		domain.try_dns_resolve(Duration::from_secs(4)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
 	)
 }
 
+#[test]
+fn test_dns_resolve_missing_port() {
+	// This fails immediately instead of after resolution
+	let start = Instant::now();
+	assert_eq!(
+		"localhost".try_dns_resolve(Duration::from_secs(4)).unwrap_err(),
+		TimeoutIoError::InvalidInput
+	);
+	assert!(start.elapsed() < Duration::from_millis(100));
+}
+#[test]
+fn test_dns_resolve_host_port() {
+	let address = HostPort{ host: "localhost".to_string(), port: 80 };
+	assert_eq!(address.try_dns_resolve(Duration::from_secs(4)).unwrap().port(), 80);
+}
+
+#[test]
+fn test_host_port_ok() {
+	let address: HostPort = "crates.io:443".parse().unwrap();
+	assert_eq!(address, HostPort{ host: "crates.io".to_string(), port: 443 });
+	assert_eq!(address.to_string(), "crates.io:443");
+	
+	let address: HostPort = "[::1]:80".parse().unwrap();
+	assert_eq!(address, HostPort{ host: "::1".to_string(), port: 80 });
+	assert_eq!(address.to_string(), "[::1]:80");
+}
+#[test]
+fn test_host_port_err() {
+	let invalid = [
+		"localhost", "localhost:", "localhost:http", "localhost:65536", ":80",
+		"::1:80", "[::1]", "[::1]80", "[::1:80", "local host:80"
+	];
+	for address in invalid.iter() {
+		assert_eq!(address.parse::<HostPort>().unwrap_err(), TimeoutIoError::InvalidInput, "{}", address);
+	}
+}
+
 #[test]
 fn test_parse_ip_ok() {
 	assert_eq!(
@@ -44,4 +81,127 @@ fn test_parse_ip_ok() {
 #[test]
 fn test_parse_ip_err() {
 	assert_eq!("127.0.0.256:80".parse_ip().unwrap_err(), TimeoutIoError::InvalidInput);
-}
\ No newline at end of file
+}
+#[test]
+fn test_parse_cidr_v4() {
+	let cidr = "10.0.0.0/8".parse_cidr().unwrap();
+	assert_eq!(cidr.address(), IpAddr::V4(Ipv4Addr::new(10, 0, 0, 0)));
+	assert_eq!(cidr.prefix_len(), 8);
+	assert_eq!(cidr.to_string(), "10.0.0.0/8");
+	
+	// Boundary addresses
+	assert!(cidr.contains(&IpAddr::V4(Ipv4Addr::new(10, 0, 0, 0))));
+	assert!(cidr.contains(&IpAddr::V4(Ipv4Addr::new(10, 255, 255, 255))));
+	assert!(!cidr.contains(&IpAddr::V4(Ipv4Addr::new(9, 255, 255, 255))));
+	assert!(!cidr.contains(&IpAddr::V4(Ipv4Addr::new(11, 0, 0, 0))));
+	assert!(!cidr.contains(&"::ffff:10.0.0.1".parse().unwrap()));
+	
+	// Host-bits are cleared; "/0" and "/32" are valid
+	assert_eq!("10.1.2.3/8".parse_cidr().unwrap(), cidr);
+	assert!("0.0.0.0/0".parse_cidr().unwrap().contains(&IpAddr::V4(Ipv4Addr::BROADCAST)));
+	let host = "192.168.1.1/32".parse_cidr().unwrap();
+	assert!(host.contains(&IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1))));
+	assert!(!host.contains(&IpAddr::V4(Ipv4Addr::new(192, 168, 1, 2))));
+}
+#[test]
+fn test_parse_cidr_v6() {
+	let cidr = "2001:db8::/32".parse_cidr().unwrap();
+	assert_eq!(cidr.address(), IpAddr::V6(Ipv6Addr::new(0x2001, 0x0db8, 0, 0, 0, 0, 0, 0)));
+	assert_eq!(cidr.prefix_len(), 32);
+	assert_eq!(cidr.to_string(), "2001:db8::/32");
+	
+	// Boundary addresses
+	assert!(cidr.contains(&"2001:db8::".parse().unwrap()));
+	assert!(cidr.contains(&"2001:db8:ffff:ffff:ffff:ffff:ffff:ffff".parse().unwrap()));
+	assert!(!cidr.contains(&"2001:db7:ffff:ffff:ffff:ffff:ffff:ffff".parse().unwrap()));
+	assert!(!cidr.contains(&"2001:db9::".parse().unwrap()));
+	assert!(!cidr.contains(&IpAddr::V4(Ipv4Addr::new(32, 1, 13, 184))));
+	
+	// "/0" and "/128" are valid
+	assert!("::/0".parse_cidr().unwrap().contains(&"ffff::1".parse().unwrap()));
+	let host = "::1/128".parse_cidr().unwrap();
+	assert!(host.contains(&IpAddr::V6(Ipv6Addr::LOCALHOST)));
+	assert!(!host.contains(&IpAddr::V6(Ipv6Addr::UNSPECIFIED)));
+}
+#[test]
+fn test_parse_cidr_err() {
+	let invalid = [
+		"10.0.0.0/33", "2001:db8::/129", "10.0.0.0/256", "10.0.0.0/-1", "10.0.0.0/+8",
+		"10.0.0.0/", "10.0.0.0", "/8", "10.0.0.256/8", "localhost/8", "10.0.0.0/8/8"
+	];
+	for cidr in invalid.iter() {
+		assert_eq!(cidr.parse_cidr().unwrap_err(), TimeoutIoError::InvalidInput, "{}", cidr);
+	}
+}
+#[test]
+fn test_dns_resolve_ex_ok() {
+	let options = DnsOptions{
+		attempt_timeout: Duration::from_secs(2), total_timeout: Duration::from_secs(4), max_attempts: 2
+	};
+	assert_eq!("localhost:80".try_dns_resolve_ex(options).unwrap().port(), 80);
+	assert_eq!("localhost".try_dns_resolve_ex(options).unwrap_err(), TimeoutIoError::InvalidInput);
+}
+#[test]
+fn test_resolve_with_options_attempts() {
+	use std::sync::{ Arc, atomic::{ AtomicUsize, Ordering::SeqCst } };
+	let options = DnsOptions{
+		attempt_timeout: Duration::from_millis(100), total_timeout: Duration::from_secs(4),
+		max_attempts: 3
+	};
+	
+	// Fail repeatedly with a retryable error
+	let attempts = Arc::new(AtomicUsize::new(0));
+	let counter = attempts.clone();
+	let result = resolve_with_options(options, move || {
+		counter.fetch_add(1, SeqCst);
+		Err(TimeoutIoError::InterruptedSyscall)
+	});
+	assert_eq!(result.unwrap_err(), TimeoutIoError::InterruptedSyscall);
+	assert_eq!(attempts.load(SeqCst), 3);
+	
+	// Hang on the first attempts and succeed on the last one
+	let attempts = Arc::new(AtomicUsize::new(0));
+	let counter = attempts.clone();
+	let start = Instant::now();
+	let address = resolve_with_options(options, move || match counter.fetch_add(1, SeqCst) {
+		0 | 1 => {
+			std::thread::sleep(Duration::from_secs(2));
+			Err(TimeoutIoError::NotFound)
+		},
+		_ => Ok("127.0.0.1:80".parse().unwrap())
+	}).unwrap();
+	assert_eq!(address, "127.0.0.1:80".parse::<SocketAddr>().unwrap());
+	assert!(start.elapsed() < Duration::from_secs(1));
+	
+	// A non-retryable error is returned immediately
+	let attempts = Arc::new(AtomicUsize::new(0));
+	let counter = attempts.clone();
+	let result = resolve_with_options(options, move || {
+		counter.fetch_add(1, SeqCst);
+		Err(TimeoutIoError::NotFound)
+	});
+	assert_eq!(result.unwrap_err(), TimeoutIoError::NotFound);
+	assert_eq!(attempts.load(SeqCst), 1);
+	
+	// The total timeout is respected
+	let options = DnsOptions{ total_timeout: Duration::from_millis(150), ..options };
+	let result = resolve_with_options(options, || {
+		std::thread::sleep(Duration::from_secs(2));
+		Err(TimeoutIoError::NotFound)
+	});
+	assert_eq!(result.unwrap_err().kind(), TimeoutIoErrorKind::TimedOut);
+}
+#[test]
+fn test_resolved_addrs_connect() {
+	use std::net::{ TcpListener, TcpStream };
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let port = listener.local_addr().unwrap().port();
+	
+	// Resolve and connect using the cached addresses
+	let resolved = format!("127.0.0.1:{}", port).try_dns_resolve_all(Duration::from_secs(4))
+		.unwrap();
+	assert_eq!(resolved.addrs(), &[listener.local_addr().unwrap()]);
+	let stream = TcpStream::connect(resolved.clone()).unwrap();
+	assert_eq!(stream.peer_addr().unwrap(), listener.local_addr().unwrap());
+	assert_eq!(resolved.into_inner().len(), 1);
+}
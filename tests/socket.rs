@@ -0,0 +1,144 @@
+use timeout_io::*;
+use std::{
+	thread, time::Duration,
+	net::{ TcpListener, TcpStream }
+};
+
+
+fn socket_pair() -> (TcpStream, TcpStream) {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let s0 = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	let s1 = listener.accept().unwrap().0;
+	(s0.into_nonblocking().unwrap(), s1.into_nonblocking().unwrap())
+}
+
+
+#[test]
+fn test_linger_ok() {
+	let (s0, _s1) = socket_pair();
+	s0.set_linger_t(Some(Duration::from_millis(1500))).unwrap();
+	s0.set_linger_t(None).unwrap();
+}
+#[test]
+fn test_linger_reset() {
+	let (s0, mut s1) = socket_pair();
+	
+	// Closing with a zero linger time resets the connection
+	s0.set_linger_t(Some(Duration::from_secs(0))).unwrap();
+	drop(s0);
+	thread::sleep(Duration::from_secs(1));
+	
+	assert_eq!(
+		s1.try_read(&mut [0; 4], &mut 0, Duration::from_secs(4)).unwrap_err().kind(),
+		TimeoutIoErrorKind::ConnectionLost
+	);
+}
+#[test] #[cfg(unix)]
+fn test_linger_invalid_fd() {
+	use std::{ mem::ManuallyDrop, os::unix::io::FromRawFd };
+	
+	let invalid = ManuallyDrop::new(unsafe{ TcpStream::from_raw_fd(1_000_000) });
+	match invalid.set_linger_t(None).unwrap_err() {
+		TimeoutIoError::Other{ .. } => (),
+		e => panic!("Invalid error returned: {:?}", e)
+	}
+}
+
+#[test]
+fn test_addrs_ok() {
+	let (s0, s1) = socket_pair();
+	assert_eq!(s0.peer_addr_t().unwrap(), s1.local_addr_t().unwrap());
+	assert_eq!(s0.local_addr_t().unwrap(), s1.peer_addr_t().unwrap());
+	assert_eq!(s0.peer_addr_t().unwrap(), s0.peer_addr().unwrap());
+}
+#[test] #[cfg(unix)]
+fn test_addrs_invalid_fd() {
+	use std::{ mem::ManuallyDrop, os::unix::io::FromRawFd };
+	
+	let invalid = ManuallyDrop::new(unsafe{ TcpStream::from_raw_fd(1_000_000) });
+	for result in [invalid.peer_addr_t(), invalid.local_addr_t()].iter() {
+		match result {
+			Err(TimeoutIoError::Other{ .. }) => (),
+			result => panic!("Invalid result returned: {:?}", result)
+		}
+	}
+}
+#[test] #[cfg(target_os = "linux")]
+fn test_with_cork() {
+	let (mut s0, mut s1) = socket_pair();
+	s0.set_nodelay_t(true).unwrap();
+	
+	// The corked parts are held back until the socket is uncorked
+	let (mut buf, mut pos) = ([0; 16], 0);
+	s0.with_cork(Duration::from_secs(1), |corked| {
+		corked.try_write_exact(b"Test", &mut 0, Duration::from_secs(1))?;
+		assert_eq!(
+			s1.try_read(&mut buf, &mut pos, Duration::from_millis(100)).unwrap_err().kind(),
+			TimeoutIoErrorKind::TimedOut
+		);
+		corked.try_write_exact(b"olope", &mut 0, Duration::from_secs(1))
+	}).unwrap();
+	
+	// The parts arrive coalesced (i.e. with a single read)
+	s1.try_read(&mut buf, &mut pos, Duration::from_secs(1)).unwrap();
+	assert_eq!(&buf[..pos], b"Testolope");
+	
+	// Uncorked writes are sent immediately
+	s0.try_write_exact(b"Test", &mut 0, Duration::from_secs(1)).unwrap();
+	let (mut buf, mut pos) = ([0; 16], 0);
+	s1.try_read(&mut buf, &mut pos, Duration::from_millis(100)).unwrap();
+	assert_eq!(&buf[..pos], b"Test");
+}
+#[test]
+fn test_with_cork_error() {
+	let (s0, mut s1) = socket_pair();
+	let result = s0.with_cork(Duration::from_secs(1), |corked| {
+		corked.try_write_exact(b"Testolope", &mut 0, Duration::from_secs(1))?;
+		Err(TimeoutIoError::LimitExceeded)
+	});
+	assert_eq!(result.unwrap_err(), TimeoutIoError::LimitExceeded);
+	
+	// The data that was written before the error is sent nonetheless
+	let (mut buf, mut pos) = ([0; 9], 0);
+	s1.try_read_exact(&mut buf, &mut pos, Duration::from_secs(1)).unwrap();
+	assert_eq!(&buf, b"Testolope");
+}
+#[test]
+fn test_ttl() {
+	let (s0, _s1) = socket_pair();
+	s0.set_ttl_t(42).unwrap();
+	assert_eq!(s0.ttl().unwrap(), 42);
+	
+	let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+	socket.set_ttl_t(255).unwrap();
+	assert_eq!(socket.ttl().unwrap(), 255);
+	
+	// Invalid TTLs are rejected up front
+	for ttl in [0, 256, u32::MAX].iter() {
+		assert_eq!(s0.set_ttl_t(*ttl).unwrap_err(), TimeoutIoError::InvalidInput);
+	}
+	assert_eq!(s0.ttl().unwrap(), 42);
+}
+#[test] #[cfg(target_os = "linux")]
+fn test_tos() {
+	use std::os::unix::io::AsRawFd;
+	
+	/// Gets the `IP_TOS`-option of `fd`
+	fn tos(fd: libc::c_int) -> libc::c_int {
+		let (mut tos, mut len) = (0 as libc::c_int, std::mem::size_of::<libc::c_int>() as _);
+		let result = unsafe{ libc::getsockopt(
+			fd, libc::IPPROTO_IP, libc::IP_TOS, &mut tos as *mut _ as *mut libc::c_void, &mut len
+		) };
+		assert_eq!(result, 0);
+		tos
+	}
+	
+	// Mark the traffic as "expedited forwarding" (DSCP 46)
+	let (s0, _s1) = socket_pair();
+	s0.set_tos(46 << 2).unwrap();
+	assert_eq!(tos(s0.as_raw_fd()), 0xB8);
+	
+	let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+	socket.set_tos(0x28).unwrap();
+	assert_eq!(tos(socket.as_raw_fd()), 0x28);
+}
@@ -0,0 +1,73 @@
+use timeout_io::*;
+use std::{
+	thread,
+	io::{ Read, Write },
+	time::{ Duration, Instant },
+	net::{ TcpListener, TcpStream }
+};
+
+
+fn socket_pair() -> (TcpStream, TcpStream) {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let s0 = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	let s1 = listener.accept().unwrap().0;
+	(s0.into_nonblocking().unwrap(), s1)
+}
+
+
+#[test]
+fn test_throttled_write() {
+	let (s0, mut s1) = socket_pair();
+	
+	// Drain the data in the background
+	let reader = thread::spawn(move || {
+		let mut buf = Vec::new();
+		s1.read_to_end(&mut buf).unwrap();
+		buf
+	});
+	
+	// Write 3 seconds worth of data (minus the initial burst of 100ms)
+	let data = vec![0x2A; 3 * 64 * 1024];
+	let mut stream = ThrottledStream::new(s0, 64 * 1024);
+	let start = Instant::now();
+	stream.try_write_exact(&data, &mut 0, Duration::from_secs(8)).unwrap();
+	let elapsed = start.elapsed();
+	assert!(elapsed >= Duration::from_millis(2700), "{:?}", elapsed);
+	assert!(elapsed < Duration::from_millis(3500), "{:?}", elapsed);
+	
+	drop(stream);
+	assert_eq!(reader.join().unwrap(), data);
+}
+#[test]
+fn test_throttled_read() {
+	let (s0, mut s1) = socket_pair();
+	s1.write_all(&[0x2A; 4096]).unwrap();
+	thread::sleep(Duration::from_millis(100));
+	
+	// Read 1 second worth of data (minus the initial burst of 100ms)
+	let mut stream = ThrottledStream::new(s0, 2048);
+	let (mut buf, mut pos) = ([0; 2048], 0);
+	let start = Instant::now();
+	stream.try_read_exact(&mut buf, &mut pos, Duration::from_secs(4)).unwrap();
+	let elapsed = start.elapsed();
+	assert!(elapsed >= Duration::from_millis(800), "{:?}", elapsed);
+	assert!(elapsed < Duration::from_millis(1300), "{:?}", elapsed);
+	assert_eq!(buf, [0x2A; 2048]);
+}
+#[test]
+fn test_throttled_timeout() {
+	let (s0, mut s1) = socket_pair();
+	s1.write_all(&[0x2A; 4096]).unwrap();
+	thread::sleep(Duration::from_millis(100));
+	
+	// The throttling delays the read beyond the timeout; so we time out – but not too early
+	let mut stream = ThrottledStream::new(s0, 100);
+	let (mut buf, mut pos) = ([0; 1024], 0);
+	let start = Instant::now();
+	assert_eq!(
+		stream.try_read_exact(&mut buf, &mut pos, Duration::from_millis(500)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+	assert!(start.elapsed() >= Duration::from_millis(500));
+	assert!(pos > 0 && pos < 100, "{}", pos);
+}
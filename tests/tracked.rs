@@ -0,0 +1,61 @@
+use timeout_io::*;
+use std::{
+	time::Duration,
+	net::{ TcpListener, TcpStream }
+};
+#[cfg(debug_assertions)]
+use std::{
+	env, panic, process::Command,
+	io::{ BufWriter, Write }
+};
+
+
+fn socket_pair() -> (TcpStream, TcpStream) {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let s0 = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	let s1 = listener.accept().unwrap().0;
+	(s0.into_nonblocking().unwrap(), s1.into_nonblocking().unwrap())
+}
+
+
+#[test]
+fn test_tracked_flushed() {
+	let (s0, _s1) = socket_pair();
+	let mut stream = TrackedStream::new(s0, DropPolicy::Panic);
+	
+	stream.try_write_all(b"Testolope", Duration::from_secs(1)).unwrap();
+	assert!(!stream.may_lose_data());
+}
+#[test] #[cfg(debug_assertions)]
+fn test_tracked_unflushed() {
+	let (s0, _s1) = socket_pair();
+	
+	// Write into a buffer without flushing it and drop the stream
+	let result = panic::catch_unwind(move || {
+		let mut stream = TrackedStream::new(BufWriter::new(s0), DropPolicy::Panic);
+		stream.write_all(b"Testolope").unwrap();
+		assert!(stream.may_lose_data());
+	});
+	assert!(result.is_err());
+}
+#[test] #[cfg(debug_assertions)]
+fn test_tracked_unflushed_warn() {
+	// Drop the stream in a child process (i.e. this test in the re-executed test binary) so that we
+	// can capture the warning on stderr
+	if env::var_os("TIMEOUT_IO_TRACKED_CHILD").is_some() {
+		let (s0, _s1) = socket_pair();
+		let mut stream = TrackedStream::new(BufWriter::new(s0), DropPolicy::Warn);
+		stream.write_all(b"Testolope").unwrap();
+		assert!(stream.may_lose_data());
+		return;
+	}
+	let output = Command::new(env::current_exe().unwrap())
+		.args(["--exact", "test_tracked_unflushed_warn", "--nocapture"])
+		.env("TIMEOUT_IO_TRACKED_CHILD", "1")
+		.output().unwrap();
+	
+	// Dropping the stream warns but does not panic
+	let stderr = String::from_utf8_lossy(&output.stderr);
+	assert!(output.status.success(), "{}", stderr);
+	assert!(stderr.contains("TrackedStream dropped with unflushed data"), "{}", stderr);
+}
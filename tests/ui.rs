@@ -0,0 +1,9 @@
+/// The compile-fail snapshots depend on the exact compiler output (which changes between Rust
+/// versions); so they only run if `TIMEOUT_IO_UI_TESTS` is set (e.g.
+/// `TIMEOUT_IO_UI_TESTS=1 cargo test --test ui`)
+#[test] #[cfg(unix)]
+fn test_ui() {
+	let cases = trybuild::TestCases::new();
+	if std::env::var_os("TIMEOUT_IO_UI_TESTS").is_some() { cases.compile_fail("tests/ui/*.rs") }
+	cases.pass("tests/ui/pass/*.rs");
+}
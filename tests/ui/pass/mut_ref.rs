@@ -0,0 +1,26 @@
+use timeout_io::{ Reader, Writer };
+use std::{ time::Duration, net::TcpStream };
+
+
+fn read_generic<R: Reader>(reader: &mut R) {
+	let _ = reader.try_read(&mut [0; 4], &mut 0, Duration::from_secs(0));
+}
+fn write_generic<W: Writer>(writer: &mut W) {
+	let _ = writer.try_write(b"Test", &mut 0, Duration::from_secs(0));
+}
+fn helper(stream: &mut TcpStream) {
+	// Pass the reference on without an explicit reborrow
+	read_generic(stream);
+	write_generic(stream);
+}
+fn usage(mut stream: TcpStream) {
+	read_generic(&mut stream);
+	write_generic(&mut stream);
+	helper(&mut stream);
+}
+
+
+fn main() {
+	// This is a compile-test only
+	let _ = usage;
+}
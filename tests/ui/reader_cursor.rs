@@ -0,0 +1,9 @@
+use timeout_io::Reader;
+use std::{ io::Cursor, time::Duration };
+
+
+fn main() {
+	let mut cursor = Cursor::new(b"Testolope".to_vec());
+	let (mut buf, mut pos) = ([0u8; 9], 0);
+	Reader::try_read(&mut cursor, &mut buf, &mut pos, Duration::from_secs(1)).unwrap();
+}
@@ -0,0 +1,8 @@
+use timeout_io::Writer;
+use std::{ io::Cursor, time::Duration };
+
+
+fn main() {
+	let mut cursor = Cursor::new(Vec::new());
+	Writer::try_write(&mut cursor, b"Testolope", &mut 0, Duration::from_secs(1)).unwrap();
+}
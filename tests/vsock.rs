@@ -0,0 +1,128 @@
+#![cfg(target_os = "linux")]
+
+use timeout_io::*;
+use std::{
+	fs::File, mem, ptr,
+	time::Duration,
+	os::unix::io::FromRawFd
+};
+
+
+/// Creates a vsock-address
+fn vsock_address(cid: libc::c_uint, port: libc::c_uint) -> libc::sockaddr_vm {
+	let mut address: libc::sockaddr_vm = unsafe{ mem::zeroed() };
+	address.svm_family = libc::AF_VSOCK as libc::sa_family_t;
+	address.svm_cid = cid;
+	address.svm_port = port;
+	address
+}
+
+/// Creates a connected `AF_VSOCK`-stream pair over the local loopback (or `None` if vsock or the
+/// loopback transport is not available)
+fn vsock_pair() -> Option<(File, File)> {
+	unsafe {
+		// Create and bind the listener to a random port
+		let listener = libc::socket(libc::AF_VSOCK, libc::SOCK_STREAM | libc::SOCK_CLOEXEC, 0);
+		if listener == -1 { return None }
+		let listener = File::from_raw_fd(listener);
+		let listener_fd = listener.raw_fd() as libc::c_int;
+		
+		let mut address = vsock_address(libc::VMADDR_CID_ANY, libc::VMADDR_PORT_ANY);
+		let mut len = mem::size_of::<libc::sockaddr_vm>() as libc::socklen_t;
+		let address_ptr = &mut address as *mut libc::sockaddr_vm as *mut libc::sockaddr;
+		if libc::bind(listener_fd, address_ptr, len) == -1 { return None }
+		if libc::listen(listener_fd, 1) == -1 { return None }
+		if libc::getsockname(listener_fd, address_ptr, &mut len) == -1 { return None }
+		
+		// Connect to the listener via the local CID and accept the connection
+		let s0 = libc::socket(libc::AF_VSOCK, libc::SOCK_STREAM | libc::SOCK_CLOEXEC, 0);
+		if s0 == -1 { return None }
+		let s0 = File::from_raw_fd(s0);
+		let mut address = vsock_address(libc::VMADDR_CID_LOCAL, address.svm_port);
+		let address_ptr = &mut address as *mut libc::sockaddr_vm as *mut libc::sockaddr;
+		if libc::connect(s0.raw_fd() as libc::c_int, address_ptr, len) == -1 { return None }
+		
+		let s1 = libc::accept4(listener_fd, ptr::null_mut(), ptr::null_mut(), libc::SOCK_CLOEXEC);
+		if s1 == -1 { return None }
+		Some((s0, File::from_raw_fd(s1)))
+	}
+}
+
+
+/// Sends a message from `s0` to `s1` and echoes it back
+fn echo(s0: &mut (impl Reader + Writer), s1: &mut (impl Reader + Writer)) {
+	s0.try_write_exact(b"Testolope", &mut 0, Duration::from_secs(4)).unwrap();
+	let (mut buf, mut pos) = ([0u8; 9], 0);
+	s1.try_read_exact(&mut buf, &mut pos, Duration::from_secs(4)).unwrap();
+	s1.try_write_exact(&buf, &mut 0, Duration::from_secs(4)).unwrap();
+	
+	let (mut buf, mut pos) = ([0u8; 9], 0);
+	s0.try_read_exact(&mut buf, &mut pos, Duration::from_secs(4)).unwrap();
+	assert_eq!(&buf, b"Testolope");
+}
+
+
+// Requires the AF_VSOCK-loopback transport (e.g. `modprobe vsock_loopback`), so it must be run
+// explicitly via `cargo test -- --ignored`
+#[test] #[ignore]
+fn test_vsock_echo() {
+	let (mut s0, mut s1) = vsock_pair().expect("AF_VSOCK-loopback is not available");
+	s0.set_blocking_mode(false).unwrap();
+	s1.set_blocking_mode(false).unwrap();
+	
+	// Send a message and echo it back
+	echo(&mut s0, &mut s1);
+	
+	// Time out if there is no data
+	assert_eq!(
+		s0.try_read(&mut [0u8; 9], &mut 0, Duration::from_millis(100)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+}
+#[cfg(feature = "vsock")]
+// Requires the AF_VSOCK-loopback transport (see `test_vsock_echo`)
+#[test] #[ignore]
+fn test_vsock_accept_echo() {
+	use vsock::{ VsockListener, VsockStream, VMADDR_CID_ANY, VMADDR_CID_LOCAL };
+	
+	// Bind the listener to a random port and connect to it via the local CID
+	let listener = VsockListener::bind_with_cid_port(VMADDR_CID_ANY, libc::VMADDR_PORT_ANY)
+		.expect("AF_VSOCK-loopback is not available");
+	let port = listener.local_addr().unwrap().port();
+	let mut s0 = VsockStream::connect_with_cid_port(VMADDR_CID_LOCAL, port).unwrap();
+	s0.set_blocking_mode(false).unwrap();
+	
+	// Accept the connection and echo a message
+	let mut s1: VsockStream = listener.try_accept(Duration::from_secs(4)).unwrap();
+	s1.set_blocking_mode(false).unwrap();
+	echo(&mut s0, &mut s1);
+	
+	// Time out if there is no pending connection
+	assert!(Acceptor::<VsockStream>::try_accept(&listener, Duration::from_millis(100)).unwrap_err()
+		.is_timeout());
+}
+#[cfg(feature = "vsock")]
+#[test]
+fn test_vsock_accept_mock() {
+	use std::os::unix::{ io::IntoRawFd, net::{ UnixListener, UnixStream } };
+	use vsock::{ VsockListener, VsockStream };
+	
+	// `VsockListener` only wraps the FD, so we can exercise the acceptor-glue without the
+	// AF_VSOCK-transport by backing it with a unix-listener
+	let path = std::env::temp_dir().join(format!("timeout_io_vsock_{}.sock", std::process::id()));
+	let _ = std::fs::remove_file(&path);
+	let unix = UnixListener::bind(&path).unwrap();
+	let listener = unsafe{ VsockListener::from_raw_fd(unix.into_raw_fd()) };
+	
+	// Time out if there is no pending connection
+	assert!(Acceptor::<VsockStream>::try_accept(&listener, Duration::from_millis(100)).unwrap_err()
+		.is_timeout());
+	
+	// Connect, accept the connection and echo a message
+	let mut s0 = UnixStream::connect(&path).unwrap();
+	s0.set_blocking_mode(false).unwrap();
+	let mut s1: VsockStream = listener.try_accept(Duration::from_secs(4)).unwrap();
+	s1.set_blocking_mode(false).unwrap();
+	echo(&mut s0, &mut s1);
+	std::fs::remove_file(&path).unwrap();
+}
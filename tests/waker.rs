@@ -0,0 +1,78 @@
+use timeout_io::*;
+use std::{
+	thread,
+	sync::{ Arc, mpsc },
+	time::{ Duration, Instant },
+	net::{ TcpListener, TcpStream }
+};
+
+
+fn socket_pair() -> (TcpStream, TcpStream) {
+	// Create listener
+	let (listener, address) = {
+		// Create listener (to capture the address) and channels
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let address = listener.local_addr().unwrap();
+		let (sender, receiver) = mpsc::channel();
+		
+		// Listen in background
+		thread::spawn(move || sender.send(listener.accept().unwrap().0).unwrap());
+		(receiver, address)
+	};
+	
+	// Create and connect stream
+	let (s0, s1) = (TcpStream::connect(address).unwrap(), listener.recv().unwrap());
+	s0.set_blocking_mode(false).unwrap();
+	s1.set_blocking_mode(false).unwrap();
+	
+	(s0, s1)
+}
+
+
+#[test]
+fn test_wake() {
+	let (s0, _s1) = socket_pair();
+	let waker = Arc::new(Waker::new().unwrap());
+	
+	// Wake from another thread
+	let remote = waker.clone();
+	thread::spawn(move || {
+		thread::sleep(Duration::from_millis(200));
+		remote.wake().unwrap();
+	});
+	
+	// The wait is interrupted promptly
+	let start = Instant::now();
+	assert_eq!(
+		s0.wait_for_event_or_wake(EventMask::new_r(), &waker, Duration::from_secs(4)).unwrap(),
+		WokenOr::Woken
+	);
+	assert!(start.elapsed() < Duration::from_secs(1));
+	
+	// The waker remains signaled until it is reset
+	assert!(waker.is_woken().unwrap());
+	waker.wake().unwrap();
+	waker.reset().unwrap();
+	assert!(!waker.is_woken().unwrap());
+}
+#[test]
+fn test_wake_event() {
+	let (s0, mut s1) = socket_pair();
+	let waker = Waker::new().unwrap();
+	
+	s1.try_write_exact(b"Testolope", &mut 0, Duration::from_secs(1)).unwrap();
+	match s0.wait_for_event_or_wake(EventMask::new_r(), &waker, Duration::from_secs(4)).unwrap() {
+		WokenOr::Event(event) => assert!(event.rwe().0),
+		WokenOr::Woken => panic!("Unexpected wake")
+	}
+}
+#[test]
+fn test_wake_timeout() {
+	let (s0, _s1) = socket_pair();
+	let waker = Waker::new().unwrap();
+	assert_eq!(
+		s0.wait_for_event_or_wake(EventMask::new_r(), &waker, Duration::from_millis(100))
+			.unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+}
@@ -1,8 +1,12 @@
+// Some tests pass mutable data on purpose (this must keep working for existing callers)
+#![allow(clippy::unnecessary_mut_passed)]
+
 use timeout_io::*;
 use std::{
-	thread, time::Duration, io::Read,
+	thread, time::Duration,
+	io::{ self, Read, Write, BufWriter },
 	net::{ TcpListener, TcpStream, Shutdown },
-	sync::mpsc::{ self, Receiver },
+	sync::{ Arc, mpsc::{ self, Receiver }, atomic::{ AtomicUsize, Ordering::SeqCst } },
 };
 
 
@@ -41,6 +45,41 @@ fn socket_pair() -> (TcpStream, TcpStream) {
 	(s0, s1)
 }
 
+/// A buffered stream that exposes the raw fd of the underlying stream
+#[cfg(unix)]
+struct BufferedStream {
+	writer: BufWriter<TcpStream>
+}
+#[cfg(unix)]
+impl Write for BufferedStream {
+	fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+		self.writer.write(data)
+	}
+	fn flush(&mut self) -> io::Result<()> {
+		self.writer.flush()
+	}
+}
+#[cfg(unix)]
+impl std::os::unix::io::AsRawFd for BufferedStream {
+	fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+		self.writer.get_ref().as_raw_fd()
+	}
+}
+
+/// An observer that counts the waits on `fd`
+struct WaitCounter {
+	fd: u64,
+	waits: AtomicUsize
+}
+impl IoObserver for WaitCounter {
+	fn on_wait(&self, _op: Operation, fd: u64, _waited: Duration,
+		_result: &Result<(), TimeoutIoError>)
+	{
+		// The observer is process-wide, so we ignore the other tests' FDs
+		if fd == self.fd { self.waits.fetch_add(1, SeqCst); }
+	}
+}
+
 fn rand(len: usize) -> Vec<u8> {
 	let block: &[u8] = include_bytes!("rand.dat");
 	
@@ -63,6 +102,17 @@ fn test_write_oneshot_ok() {
 	s0.try_write(&mut data.clone(), &mut pos, Duration::from_secs(1)).unwrap();
 	assert_eq!(fut.recv().unwrap(), data);
 }
+#[test]
+fn test_write_oneshot_offset() {
+	let (mut s0, s1) = socket_pair();
+	let fut = read_async(s1, 5);
+	
+	// Only `data[pos..]` is written
+	let mut pos = 4;
+	s0.try_write(b"Testolope", &mut pos, Duration::from_secs(1)).unwrap();
+	assert_eq!(pos, 9);
+	assert_eq!(fut.recv().unwrap(), b"olope");
+}
 #[test] #[ignore]
 fn test_write_oneshot_err_broken_pipe() {
 	let mut s0 = socket_pair().0;
@@ -74,8 +124,8 @@ fn test_write_oneshot_err_broken_pipe() {
 	thread::sleep(Duration::from_secs(90));
 	let (mut data, mut pos) = (rand(16 * 1024 * 1024), 0);
 	assert_eq!(
-		s0.try_write(&mut data, &mut pos, Duration::from_secs(1)).unwrap_err(),
-		TimeoutIoError::ConnectionLost
+		s0.try_write(&mut data, &mut pos, Duration::from_secs(1)).unwrap_err().kind(),
+		TimeoutIoErrorKind::ConnectionLost
 	)
 }
 #[test]
@@ -87,7 +137,7 @@ fn test_write_oneshot_err() {
 	let err = s0.try_write(&mut data, &mut pos, Duration::from_secs(1)).unwrap_err();
 	
 	#[cfg(unix)]
-	assert_eq!(err, TimeoutIoError::ConnectionLost);
+	assert_eq!(err.kind(), TimeoutIoErrorKind::ConnectionLost);
 	
 	#[cfg(windows)]
 	match err {
@@ -104,18 +154,32 @@ fn test_write_oneshot_timeout() {
 	loop {
 		let (mut data, mut pos) = (rand(64 * 1024 * 1024), 0);
 		if let Err(e) = s0.try_write(&mut data, &mut pos, Duration::from_secs(1)) {
-			if e == TimeoutIoError::TimedOut { break }
-				else { panic!(e) }
+			if e.is_timeout() { break }
+				else { panic!("{}", e) }
 		}
 	}
 	
 	// Final test
 	let (mut data, mut pos) = (rand(64 * 1024 * 1024), 0);
 	assert_eq!(
-		s0.try_write(&mut data, &mut pos, Duration::from_secs(1)).unwrap_err(),
-		TimeoutIoError::TimedOut
+		s0.try_write(&mut data, &mut pos, Duration::from_secs(1)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
 	)
 }
+#[test]
+fn test_write_oneshot_zero_timeout() {
+	let (mut s0, _s1) = socket_pair();
+	
+	// Write until the connection buffer is apparently filled
+	let data = rand(64 * 1024);
+	loop {
+		match s0.try_write(&data, &mut 0, Duration::from_secs(0)) {
+			Ok(_) => continue,
+			Err(TimeoutIoError::TimedOut{ .. }) => break,
+			Err(e) => panic!("{}", e)
+		}
+	}
+}
 
 
 #[test]
@@ -132,6 +196,26 @@ fn test_write_exact_ok() {
 	assert_eq!(fut.recv().unwrap(), data)
 }
 #[test]
+fn test_write_exact_fast_path() {
+	let (mut s0, s1) = socket_pair();
+	let fut = read_async(s1, 18);
+	let counter = Arc::new(WaitCounter{ fd: s0.raw_fd(), waits: AtomicUsize::new(0) });
+	set_observer(counter.clone());
+	
+	// A writable socket is written without waiting first
+	s0.try_write_exact(b"Testolope", &mut 0, Duration::from_secs(4)).unwrap();
+	let fast_path = counter.waits.swap(0, SeqCst);
+	
+	// The wait-first strategy (as used by `try_write`) waits even if the socket is writable
+	s0.try_write(b"Testolope", &mut 0, Duration::from_secs(4)).unwrap();
+	let wait_first = counter.waits.load(SeqCst);
+	clear_observer();
+	
+	assert_eq!(fast_path, 0);
+	assert_eq!(wait_first, 1);
+	assert_eq!(fut.recv().unwrap(), b"TestolopeTestolope");
+}
+#[test]
 fn test_write_exact_err() {
 	let (mut s0, _s1) = socket_pair();
 	s0.shutdown(Shutdown::Both).unwrap();
@@ -142,7 +226,7 @@ fn test_write_exact_err() {
 		.unwrap_err();
 	
 	#[cfg(unix)]
-	assert_eq!(err, TimeoutIoError::ConnectionLost);
+	assert_eq!(err.kind(), TimeoutIoErrorKind::ConnectionLost);
 	
 	#[cfg(windows)]
 		match err {
@@ -158,5 +242,96 @@ fn test_write_exact_timeout() {
 	assert_eq!(s0.try_write_exact(
 		&mut data.clone(), &mut pos,
 		Duration::from_secs(1)
-	).unwrap_err(), TimeoutIoError::TimedOut)
-}
\ No newline at end of file
+	).unwrap_err().kind(), TimeoutIoErrorKind::TimedOut)
+}
+
+
+#[test] #[cfg(unix)]
+fn test_write_all_flushes() {
+	let (s0, mut s1) = socket_pair();
+	let mut s0 = BufferedStream{ writer: BufWriter::new(s0) };
+	
+	// The data is buffered after a write
+	s0.try_write_exact(b"Testolope", &mut 0, Duration::from_secs(1)).unwrap();
+	let (mut buf, mut pos) = ([0u8; 9], 0);
+	assert_eq!(
+		s1.try_read_exact(&mut buf, &mut pos, Duration::from_secs(1)).unwrap_err().kind(),
+		TimeoutIoErrorKind::TimedOut
+	);
+	
+	// The data is visible after `try_write_all`
+	s0.try_write_all(b"!", Duration::from_secs(1)).unwrap();
+	let (mut buf, mut pos) = ([0u8; 10], 0);
+	s1.try_read_exact(&mut buf, &mut pos, Duration::from_secs(4)).unwrap();
+	assert_eq!(&buf, b"Testolope!");
+}
+#[test]
+fn test_write_chunks() {
+	let (mut s0, s1) = socket_pair();
+	let fut = read_async(s1, 13);
+	
+	// Write the chunks lazily
+	let mut yielded = 0;
+	let chunks = [&b"Test"[..], b"olope", b"!!!!"];
+	let chunks = chunks.iter().map(|chunk| {
+		yielded += 1;
+		*chunk
+	});
+	s0.try_write_chunks(chunks, Duration::from_secs(4)).unwrap();
+	assert_eq!(yielded, 3);
+	assert_eq!(fut.recv().unwrap(), b"Testolope!!!!");
+}
+#[test]
+fn test_write_counted() {
+	let (mut s0, s1) = socket_pair();
+	
+	// An empty send buffer does not block
+	let mut pos = 0;
+	assert_eq!(s0.try_write_counted(b"Testolope", &mut pos, Duration::from_secs(4)).unwrap(), (9, 0));
+	assert_eq!(pos, 9);
+	
+	// Fill the send buffer completely
+	let (block, mut filled) = ([0u8; 65536], 9);
+	loop {
+		match s0.write(&block) {
+			Ok(written) => filled += written,
+			Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+			Err(error) => panic!("{}", error)
+		}
+	}
+	
+	// Drain the buffer in the background (delayed, so that the write must wait) and write again
+	let (sender, fut) = mpsc::channel();
+	thread::spawn(move || {
+		thread::sleep(Duration::from_millis(500));
+		sender.send(read_async(s1, filled + 9).recv().unwrap()).unwrap();
+	});
+	let mut pos = 0;
+	let (written, would_block_count) =
+		s0.try_write_counted(b"Testolope", &mut pos, Duration::from_secs(8)).unwrap();
+	assert!(written > 0);
+	assert!(would_block_count > 0);
+	s0.try_write_exact(b"Testolope", &mut pos, Duration::from_secs(8)).unwrap();
+	assert_eq!(fut.recv().unwrap().len(), filled + 9);
+}
+#[test]
+fn test_sendfile() {
+	let (mut s0, s1) = socket_pair();
+	
+	// Create a file that is larger than the socket buffers
+	let data = rand(4 * 1024 * 1024 + 7);
+	let path = std::env::temp_dir().join(format!("timeout_io_sendfile_{}.dat", std::process::id()));
+	std::fs::write(&path, &data).unwrap();
+	let file = std::fs::File::open(&path).unwrap();
+	std::fs::remove_file(&path).unwrap();
+	
+	// Send the file starting at an offset and validate the received data
+	let fut = read_async(s1, data.len() - 7);
+	let mut offset = 7;
+	let sent = s0.try_sendfile(&file, &mut offset, data.len(), Duration::from_secs(8)).unwrap();
+	assert_eq!((sent, offset), (data.len() - 7, data.len() as u64));
+	assert_eq!(fut.recv().unwrap(), &data[7..]);
+	
+	// The end of the file has been reached
+	assert_eq!(s0.try_sendfile(&file, &mut offset, 16, Duration::from_secs(1)).unwrap(), 0);
+}